@@ -3,7 +3,7 @@ use std::time::{Duration, SystemTime};
 
 
 use prospect::abstraction::shader::ProspectShader;
-use prospect::parse_obj;
+use prospect::formats::read_obj;
 use prospect::utils::prospect_fs::{path_with_respect_to_cwd_str, path_with_respect_to_cwd};
 use prospect::wgpu::{SurfaceError, Texture};
 use prospect::winit::{
@@ -17,14 +17,12 @@ use prospect::{
         mesh::{Mesh, Meshable},
         prospect_window::ProspectWindow,
         shader::BasicShader,
-        vertex::Vertex,
     },
     model::Model3D,
     prospect_app::{ProcessResponse, ProspectApp, ProspectEvent},
     prospect_camera::ProspectCamera,
     prospect_camera_controller::CameraController,
     prospect_light::ProspectPointLight,
-    prospect_shape::ProspectShape,
     shaders::{default_3d::Default3D, textured_shader::TexturedShader},
     utils::prospect_fs::{
         read_file_panic, read_file_with_respect_to_cwd, read_file_with_respect_to_cwd_bytes,
@@ -41,25 +39,6 @@ fn main() {
     window.run_with_app(Box::new(app));
 }
 
-fn to_shape(str: &str) -> ProspectShape<Vec<Vertex>, Vec<u32>> {
-    let mut mesh = parse_obj(str);
-    let verts = mesh.extract_vertices_and_uv_and_normals();
-    let mut shape: ProspectShape<Vec<Vertex>, Vec<u32>> = ProspectShape {
-        vertices: Vec::new(),
-        indices: None,
-    };
-
-    for vert in verts {
-        shape.vertices.push(Vertex {
-            position: [vert.0.x, vert.0.y, vert.0.z],
-            uv: [vert.1.x, 1. - vert.1.y],
-            normal: [vert.2.x, vert.2.y, vert.2.z],
-        })
-    }
-
-    shape
-}
-
 pub struct ObjPreviewer {
     main_model: Model3D,
     main_mesh: Mesh,
@@ -86,7 +65,7 @@ impl ObjPreviewer {
         );
 
         let mut main_mesh = Mesh::from_shape(
-            &to_shape(include_str!("../res/car01.obj")),
+            &read_obj(include_str!("../res/car01.obj")).expect("Invalid OBJ").to_shape(),
             window.get_device(),
             &default_shader_key,
         );
@@ -0,0 +1,179 @@
+use std::time::{Duration, SystemTime};
+
+use prospect::abstraction::shader::ProspectShader;
+use prospect::formats::read_obj;
+use prospect::post_process::{Bloom, CustomEffect, Fxaa, PostProcessChain, Vignette};
+use prospect::prospect_cubemap::ProspectCubemap;
+use prospect::prospect_skybox::ProspectSkybox;
+use prospect::text::{TextRenderer, TextStyle};
+use prospect::wgpu::SurfaceError;
+use prospect::winit::event::{ElementState, VirtualKeyCode};
+use prospect::{
+    abstraction::{
+        high_level_abstraction::HighLevelGraphicsContext,
+        mesh::Mesh,
+        prospect_window::ProspectWindow,
+    },
+    model::Model3D,
+    prospect_app::{ProcessResponse, ProspectApp, ProspectEvent},
+    prospect_camera::ProspectCamera,
+    prospect_camera_controller::CameraController,
+    prospect_light::ProspectPointLight,
+    shaders::default_3d::Default3D,
+};
+use prospect::linear::{Vector, VectorTrait};
+
+const CHROMATIC_ABERRATION : &str = "
+@fragment
+fn fs_main(in: FullscreenOutput) -> @location(0) vec4<f32>
+{
+    let offset = (in.uv - vec2<f32>(0.5)) * params.values[0].x;
+    let r = textureSample(t_input, s_input, in.uv + offset).r;
+    let g = textureSample(t_input, s_input, in.uv).g;
+    let b = textureSample(t_input, s_input, in.uv - offset).b;
+    return vec4<f32>(r, g, b, 1.0);
+}
+";
+
+fn main() {
+    let mut window = ProspectWindow::new("Post Processing", 480, 480);
+    let app = PostProcessExample::new(&mut window);
+    window.run_with_app(Box::new(app));
+}
+
+pub struct PostProcessExample {
+    main_model: Model3D,
+    main_mesh: Mesh,
+    camera: ProspectCamera,
+    cam_controller: CameraController,
+    last_frame: SystemTime,
+    light: ProspectPointLight,
+    post_process: PostProcessChain,
+    skybox: ProspectSkybox,
+    text: TextRenderer,
+}
+
+impl PostProcessExample {
+    fn new(window: &mut ProspectWindow) -> Self {
+        let camera = ProspectCamera::new(window.get_device());
+        let mut light = ProspectPointLight::new(window);
+        light.position = Vector::new3(4., 4., 4.);
+        light.colour = Vector::new3(1., 1., 1.);
+
+        let default_shader = Default3D::new(&window);
+        let default_shader_key = default_shader.build_render_pipeline(window.get_device(), vec![camera.get_layout(), light.get_layout()]).into();
+
+        let texture = default_shader.register_texture(
+            "texture",
+            include_bytes!("../res/car01_Car_Pallete.png"),
+            window,
+        );
+
+        let mut main_mesh = Mesh::from_shape(
+            &read_obj(include_str!("../res/car01.obj")).expect("Invalid OBJ").to_shape(),
+            window.get_device(),
+            &default_shader_key,
+        );
+        main_mesh.set_bind_group(1, &texture);
+        main_mesh.set_bind_group(2, &light.get_bind_group());
+        let main_model = Model3D::new(&default_shader, window);
+
+        let mut post_process = PostProcessChain::new(window);
+        post_process.push(Bloom::new(window));
+        let mut aberration = CustomEffect::new(window, "Chromatic Aberration", CHROMATIC_ABERRATION);
+        aberration.params[0][0] = 0.01;
+        post_process.push(aberration);
+        post_process.push(Vignette::new(window));
+        post_process.push(Fxaa::new(window));
+
+        let sky = ProspectCubemap::from_gradient("Sky", 64, [70, 130, 220, 255], [200, 220, 240, 255], [60, 60, 70, 255], window).unwrap();
+        let skybox = ProspectSkybox::new(window, &sky);
+
+        let text = TextRenderer::with_default_font(window, camera.get_layout());
+
+        Self {
+            main_mesh,
+            main_model,
+            camera,
+            last_frame: SystemTime::now(),
+            cam_controller: CameraController::new(),
+            light,
+            post_process,
+            skybox,
+            text,
+        }
+    }
+}
+
+impl ProspectApp for PostProcessExample {
+    fn setup(&mut self, _window: &mut ProspectWindow) {}
+
+    fn draw(&mut self, window: &mut ProspectWindow) -> Result<(), SurfaceError> {
+        /* update */
+        let this_time = SystemTime::now();
+        let delta = this_time
+            .duration_since(self.last_frame)
+            .unwrap_or(Duration::from_secs_f32(1. / 60.))
+            .as_secs_f32();
+        self.last_frame = this_time;
+
+        self.light.process_frame(window);
+        self.cam_controller.process(delta, &mut self.camera, window);
+        self.camera.process_frame(
+            window.size.0 as f32,
+            window.size.1 as f32,
+            window.get_queue(),
+        );
+        self.skybox.process_frame(window, &self.camera);
+        self.text.queue_screen(&format!("{:.0} FPS", 1. / delta.max(0.0001)), (8., 8.), TextStyle::new(24., [1., 1., 1., 1.]));
+        self.text.process_frame(window);
+        self.post_process.process_frame(window);
+
+        let clear_colour = (0.5, 0.0, 0.5);
+
+        /* draw */
+        let (output, view, mut command_encoder) = HighLevelGraphicsContext::init_view(window);
+        let mut render_pass = HighLevelGraphicsContext::start_render(
+            clear_colour,
+            self.post_process.get_scene_view(),
+            window.get_depth_buffer(),
+            &mut command_encoder,
+        );
+
+        self.main_model
+            .draw(&mut render_pass, window, &self.camera, &self.main_mesh);
+        self.skybox.draw(&mut render_pass);
+        self.text.draw(&mut render_pass, &self.camera);
+
+        drop(render_pass);
+
+        self.post_process.apply(window, &mut command_encoder, &view);
+
+        HighLevelGraphicsContext::finish_render(window, command_encoder, output);
+        Ok(())
+    }
+
+    fn process(&mut self, event: ProspectEvent, window: &mut ProspectWindow) -> ProcessResponse {
+        self.cam_controller.input_event(event, window);
+        match event {
+            ProspectEvent::KeyboardInput(Some(key), ElementState::Pressed) => {
+                // Number keys toggle each effect in the chain
+                let index = match key {
+                    VirtualKeyCode::Key1 => Some(0),
+                    VirtualKeyCode::Key2 => Some(1),
+                    VirtualKeyCode::Key3 => Some(2),
+                    VirtualKeyCode::Key4 => Some(3),
+                    _ => None,
+                };
+
+                if let Some(index) = index {
+                    let enabled = self.post_process.is_enabled(index);
+                    self.post_process.set_enabled(index, !enabled);
+                }
+
+                ProcessResponse::ProspectProcess
+            }
+            _ => ProcessResponse::ProspectProcess,
+        }
+    }
+}
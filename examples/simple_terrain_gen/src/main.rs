@@ -1,6 +1,6 @@
 use std::time::{Duration, SystemTime};
 use prospect::abstraction::shader::ProspectShader;
-use prospect::parse_obj;
+use prospect::formats::read_obj;
 use prospect::prospect_texture::ProspectTexture;
 use prospect::smart::{SmartRenderPipeline, SmartBindGroup};
 use prospect::trig::to_radians;
@@ -65,7 +65,7 @@ impl SimpleTerrainGen {
             window,
         );
         let mut light_mesh = Mesh::from_shape(
-            &to_shape(include_str!("../res/light.obj")),
+            &read_obj(include_str!("../res/light.obj")).expect("Invalid OBJ").to_shape(),
             window.get_device(),
             &default_shader_key,
         );
@@ -271,22 +271,3 @@ impl ProspectApp for SimpleTerrainGen {
         }
     }
 }
-
-fn to_shape(str: &str) -> ProspectShape<Vec<Vertex>, Vec<u32>> {
-    let mut mesh = parse_obj(str);
-    let verts = mesh.extract_vertices_and_uv_and_normals();
-    let mut shape: ProspectShape<Vec<Vertex>, Vec<u32>> = ProspectShape {
-        vertices: Vec::new(),
-        indices: None,
-    };
-
-    for vert in verts {
-        shape.vertices.push(Vertex {
-            position: [vert.0.x, vert.0.y, vert.0.z],
-            uv: [vert.1.x, 1. - vert.1.y],
-            normal: [vert.2.x, vert.2.y, vert.2.z],
-        })
-    }
-
-    shape
-}
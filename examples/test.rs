@@ -6,12 +6,12 @@ use prospect::{
         mesh::{Mesh, Meshable},
         prospect_window::ProspectWindow,
         shader::{BasicShader, ProspectShader},
-        vertex::Vertex, graphics_context::GraphicsContext,
+        graphics_context::GraphicsContext,
     },
     prospect_app::{ProcessResponse, ProspectApp, ProspectEvent},
-    prospect_shape::ProspectShape, shaders::{textured_shader::TexturedShader, default_3d::Default3D}, prospect_camera::ProspectCamera, prospect_camera_controller::CameraController, prospect_light::ProspectPointLight, model::Model3D, smart::SmartRenderPipeline,
+    shaders::{textured_shader::TexturedShader, default_3d::Default3D}, prospect_camera::ProspectCamera, prospect_camera_controller::CameraController, prospect_light::ProspectPointLight, model::Model3D, smart::SmartRenderPipeline, prospect_debug_draw::DebugDraw,
 };
-use prospect_obj::parse_obj;
+use prospect::formats::read_obj;
 use vecto_rs::{linear::{Vector, VectorTrait}, trig::to_degrees};
 use wgpu::SurfaceError;
 use winit::{event::{ElementState, VirtualKeyCode, MouseButton}, window::CursorGrabMode};
@@ -23,24 +23,8 @@ fn main() {
     window.run_with_app(Box::new(app));
 }
 
-fn to_shape(str : &str) -> ProspectShape<Vec<Vertex>, Vec<u32>>
-{
-
-    let mut mesh = parse_obj(str);
-    let verts = mesh.extract_vertices_and_uv_and_normals();
-    let mut shape : ProspectShape<Vec<Vertex>, Vec<u32>> = ProspectShape { vertices: Vec::new(), indices: None };
-
-    for vert in verts
-    {
-        shape.vertices.push(Vertex { position: [vert.0.x, vert.0.y, vert.0.z], uv: [vert.1.x, 1. - vert.1.y], normal : [vert.2.x, vert.2.y, vert.2.z] })
-    }
-
-    shape
-}
-
 pub struct TestApp {
-    light_mesh: Mesh,
-    light_model : Model3D,
+    debug_draw : DebugDraw,
     car_mesh : Mesh,
     mario_mesh : Mesh,
     car1: Model3D,
@@ -64,29 +48,23 @@ impl TestApp {
         let default_shader = Default3D::new(&window);
         
         let car_texture = default_shader.register_texture("Car Texture", include_bytes!("../res/car01_Car_Pallete.png"), window);
-        let light_texture = default_shader.register_texture("Light Texture", include_bytes!("../res/light.png"), window);
         let mario_texture = default_shader.register_texture("Mario Texture", include_bytes!("../res/mario.png"), window);
         let default_pipeline : SmartRenderPipeline = default_shader.build_render_pipeline(window.get_device(), vec![camera.get_layout(), light.get_layout()]).into();
 
-        let mut car_mesh = Mesh::from_shape(&to_shape(include_str!("../res/car01.obj")), window.get_device(), &default_pipeline);
+        let mut car_mesh = Mesh::from_shape(&read_obj(include_str!("../res/car01.obj")).expect("Invalid OBJ").to_shape(), window.get_device(), &default_pipeline);
         car_mesh.set_bind_group(1, &car_texture);
         car_mesh.set_bind_group(2, &light_bind_group);
         let car1 = Model3D::new(&default_shader, window);
 
-        let mut mario_mesh = Mesh::from_shape(&to_shape(include_str!("../res/mario.obj")), window.get_device(), &default_pipeline);
+        let mut mario_mesh = Mesh::from_shape(&read_obj(include_str!("../res/mario.obj")).expect("Invalid OBJ").to_shape(), window.get_device(), &default_pipeline);
         mario_mesh.set_bind_group(1, &mario_texture);
         mario_mesh.set_bind_group(2, &light_bind_group);
         let mario = Model3D::new(&default_shader, window);
         
-        let mut light_mesh = Mesh::from_shape(&to_shape(include_str!("../res/light.obj")), window.get_device(), &default_pipeline);
-        light_mesh.set_bind_group(1, &light_texture);
-        light_mesh.set_bind_group(2, &light_bind_group);
-
-        let light_model = Model3D::new(&default_shader, window);
+        let debug_draw = DebugDraw::new(window, camera.get_layout());
 
         Self {
-            light_mesh,
-            light_model,
+            debug_draw,
             mario_mesh,
             car_mesh,
             car1,
@@ -118,7 +96,14 @@ impl ProspectApp for TestApp {
         self.mario.transform.position.x = (self.frame / 2.).sin() * 5.;
         self.mario.transform.position.z = (self.frame / 2.).cos() * 5.;
         self.mario.transform.scale = 0.1;
-        self.light_model.transform.position = self.light.position;
+
+        self.debug_draw.grid(Vector::new3(0., -1., 0.), 1., 20, [0.5, 0.5, 0.5, 1.]);
+        self.debug_draw.wire_sphere(self.light.position, 0.25, [1., 1., 0., 1.]);
+        self.debug_draw.set_depth_test(false);
+        self.debug_draw.transform_axes(&self.car1.transform, 2.);
+        self.debug_draw.transform_axes(&self.mario.transform, 2.);
+        self.debug_draw.set_depth_test(true);
+        self.debug_draw.process_frame(window);
 
         let clear_colour = (
             0.5,
@@ -131,9 +116,9 @@ impl ProspectApp for TestApp {
         let mut render_pass =
             HighLevelGraphicsContext::start_render(clear_colour, &view, window.get_depth_buffer(), &mut command_encoder);
         
-        self.light_model.draw(&mut render_pass, window, &self.camera, &self.light_mesh);
         self.car1.draw(&mut render_pass, window, &self.camera, &self.car_mesh);
         self.mario.draw(&mut render_pass, window, &self.camera, &self.mario_mesh);
+        self.debug_draw.draw(&mut render_pass, &self.camera);
 
         drop(render_pass);
 
@@ -8,6 +8,7 @@ use prospect::{
         prospect_window::ProspectWindow,
         shader::ProspectShader,
         vertex::Vertex,
+        mipmaps::AtlasLayout,
     },
     linear::{Vector, VectorTrait, vector3},
     model::Model3D,
@@ -28,6 +29,9 @@ pub const BLOCK_TYPES: &[(f32, f32, &str)] = &[
     (BLOCK_UV * 1., BLOCK_UV * 10., "grass"),
 ];
 pub const BLOCK_UV: f32 = 1. / 32.;
+pub const ATLAS_TILE_SIZE: u32 = 16;
+/// Texels of each tile's edges repeated around it, enough to keep the first couple of mip levels from bleeding
+pub const ATLAS_PADDING: u32 = 4;
 pub const BLOCK_TYPES_SIZE: u8 = 6;
 
 pub const CHUNK_LWH: u32 = 32;
@@ -455,10 +459,31 @@ impl ChunkData {
             }
         }
 
+        pad_uvs(&mut vertices);
+
         ChunkData { x, y, z, vertices, indices, entry: from_vector(vector3(x, y, z)) }
     }
 }
 
+/// Faces are built on the unpadded grid of `BLOCK_UV` sized tiles, this moves each one onto its tile in the padded atlas
+fn pad_uvs(vertices: &mut [Vertex]) {
+    let tiles = (1. / BLOCK_UV).round() as u32;
+    let layout = AtlasLayout { tile_size: (ATLAS_TILE_SIZE + ATLAS_PADDING * 2, ATLAS_TILE_SIZE + ATLAS_PADDING * 2), padding: ATLAS_PADDING };
+    let atlas_size = (tiles * layout.tile_size.0, tiles * layout.tile_size.1);
+
+    // Every face is 4 vertices, each on either the low or high edge of its tile
+    for face in vertices.chunks_exact_mut(4) {
+        let min = [0, 1].map(|axis| face.iter().map(|vertex| vertex.uv[axis]).fold(f32::MAX, f32::min));
+        let [min_u, min_v, max_u, max_v] = layout.get_tile_uv(atlas_size, (min[0] / BLOCK_UV).round() as u32, (min[1] / BLOCK_UV).round() as u32);
+        for vertex in face {
+            vertex.uv = [
+                if vertex.uv[0] > min[0] { max_u } else { min_u },
+                if vertex.uv[1] > min[1] { max_v } else { min_v },
+            ];
+        }
+    }
+}
+
 pub struct Chunk {
     mesh: Mesh,
     model: Model3D,
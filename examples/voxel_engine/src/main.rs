@@ -1,21 +1,23 @@
 use std::{
     borrow::BorrowMut,
     sync::{Arc, Mutex},
-    thread, time::{SystemTime, Duration, Instant}, collections::HashMap,
+    thread, time::{SystemTime, Duration, Instant}, collections::HashMap, rc::Rc,
 };
 
 use noise::Perlin;
 use prospect::{
     abstraction::{
-        high_level_abstraction::HighLevelGraphicsContext, prospect_window::ProspectWindow, shader::ProspectShader,
+        high_level_abstraction::HighLevelGraphicsContext, prospect_window::ProspectWindow, shader::ProspectShader, mipmaps::AtlasLayout,
+        shader_registry::ShaderRegistry,
     },
     linear::{Vector, VectorTrait, vector3},
     prospect_app::{ProcessResponse, ProspectApp, ProspectEvent},
     prospect_light::ProspectPointLight,
-    prospect_texture::ProspectTexture, smart::{SmartRenderPipeline, SmartBindGroup}, winit::event::{VirtualKeyCode, ElementState},
+    prospect_texture::{ProspectTexture, TextureOptions, SamplerOptions}, smart::{SmartRenderPipeline, SmartBindGroup}, winit::event::{VirtualKeyCode, ElementState},
+    prospect_egui::ProspectEgui, egui,
 };
 use voxel_engine::{
-    chunk::{Chunk, ChunkData, CHUNK_LWH, CHUNK_SIZE, BLOCKS_PER_CHUNK, ChunkEntry, from_vector, to_vector},
+    chunk::{Chunk, ChunkData, CHUNK_LWH, CHUNK_SIZE, BLOCKS_PER_CHUNK, ChunkEntry, from_vector, to_vector, ATLAS_TILE_SIZE, ATLAS_PADDING},
     player::Player,
     voxel_shader::VoxelShader,
 };
@@ -35,22 +37,28 @@ pub struct VoxelEngine {
     chunks: HashMap<ChunkEntry, Chunk>,
     chunk_data: Arc<Mutex<Vec<ChunkData>>>,
     chunk_remove: Arc<Mutex<Vec<ChunkEntry>>>,
-    shader : VoxelShader,
+    shader : Rc<VoxelShader>,
+    shaders : ShaderRegistry,
     shader_key : SmartRenderPipeline,
     light : ProspectPointLight,
     texture_index : SmartBindGroup,
     running : Arc<Mutex<bool>>,
     thread_has_stopped : Arc<Mutex<bool>>,
     lock_player_pos : bool,
+    egui : ProspectEgui,
 }
 
 impl VoxelEngine {
     pub fn new(window: &mut ProspectWindow) -> Self {
-        let mut block_atlas = ProspectTexture::image_file_from_bytes(
-            "BlockAtlas",
-            include_bytes!("textures/block_atlas.png"),
-            window,
-        );
+        // 16x16 tiles, padded with their own edges and mipmapped per tile so distant blocks don't shimmer or pick up their neighbours' colours
+        let atlas_image = image::load_from_memory(include_bytes!("textures/block_atlas.png")).expect("Invalid block atlas").to_rgba8();
+        let (atlas_image, atlas_layout) = AtlasLayout::pad_image(&atlas_image, (ATLAS_TILE_SIZE, ATLAS_TILE_SIZE), ATLAS_PADDING);
+        let atlas_options = TextureOptions {
+            atlas: Some(atlas_layout),
+            sampler: SamplerOptions::pixelated(),
+            ..Default::default()
+        };
+        let mut block_atlas = ProspectTexture::from_image_with_options("BlockAtlas", atlas_image, &atlas_options, window);
 
         let player = Player::new(window);
 
@@ -58,11 +66,24 @@ impl VoxelEngine {
         light.position = Vector::new3(0., 0., 0.);
         light.process_frame(window);
 
-        let shader = VoxelShader::new(&window);
-        let shader_key = shader.build_render_pipeline(window.get_device(), vec![player.get_camera().get_layout(), light.get_layout()]).into();
+        let shader = Rc::new(VoxelShader::new(&window));
+        let layout = shader.create_pipeline_layout(window.get_device(), vec![player.get_camera().get_layout(), light.get_layout()]);
+
+        // Edit voxel_shader.wgsl while running to see the changes, falling back to the built in copy if it can't be found
+        let mut shaders = ShaderRegistry::new();
+        let shader_key = match shaders.load(concat!(env!("CARGO_MANIFEST_DIR"), "/src/shader/voxel_shader.wgsl"), window)
+        {
+            Ok(handle) =>
+            {
+                let voxel_shader = shader.clone();
+                shaders.create_pipeline(handle, window, move |device, module| voxel_shader.build_render_pipeline_with_module(device, &layout, module))
+            }
+            Err(_) => shader.build_render_pipeline_with_module(window.get_device(), &layout, shader.get_module()).into(),
+        };
         let texture_index = shader.bind_prospect_texture(&block_atlas, window);
 
         let noise = Perlin::new(55);
+        let egui = ProspectEgui::new(window);
 
         let chunk_data = Arc::new(Mutex::new(vec![]));
         let chunk_remove = Arc::new(Mutex::new(vec![]));
@@ -87,13 +108,15 @@ impl VoxelEngine {
             chunk_data,
             chunks,
             shader,
+            shaders,
             shader_key,
             light,
             texture_index,
             running : Arc::new(Mutex::new(true)),
             thread_has_stopped : Arc::new(Mutex::new(false)),
             chunk_remove,
-            lock_player_pos : false
+            lock_player_pos : false,
+            egui,
         }
     }
 
@@ -187,6 +210,7 @@ impl ProspectApp for VoxelEngine {
 
     fn draw(&mut self, window: &mut ProspectWindow) -> Result<(), prospect::wgpu::SurfaceError> {
         self.player.update(window);
+        self.shaders.process_frame(window);
 
         if !self.lock_player_pos
         {
@@ -200,7 +224,7 @@ impl ProspectApp for VoxelEngine {
             while len > 0
             {
                 let first_chunk = self.chunk_data.lock().unwrap().remove(0);
-                self.chunks.insert(first_chunk.entry, Chunk::new(first_chunk, window, &self.shader_key, &self.shader, &self.light, &self.texture_index));
+                self.chunks.insert(first_chunk.entry, Chunk::new(first_chunk, window, &self.shader_key, self.shader.as_ref(), &self.light, &self.texture_index));
                 len -= 1;
             }
         }
@@ -217,6 +241,19 @@ impl ProspectApp for VoxelEngine {
             }
         }
 
+        let chunk_count = self.chunks.len();
+        let eye = self.player.get_camera().eye;
+        let lock_player_pos = &mut self.lock_player_pos;
+        self.egui.run(window, |context|
+        {
+            egui::Window::new("Voxel Engine").show(context, |ui|
+            {
+                ui.label(format!("Position: {:.1} {:.1} {:.1}", eye.x, eye.y, eye.z));
+                ui.label(format!("Chunks loaded: {}", chunk_count));
+                ui.checkbox(lock_player_pos, "Stop loading chunks (Q)");
+            });
+        });
+
         let clear_colour = (0.5, 0.0, 0.5);
         let (output, view, mut command_encoder) = HighLevelGraphicsContext::init_view(window);
         let mut render_pass = HighLevelGraphicsContext::start_render(
@@ -241,12 +278,18 @@ impl ProspectApp for VoxelEngine {
         }
 
         drop(render_pass);
+        self.egui.draw(&view, &mut command_encoder);
         HighLevelGraphicsContext::finish_render(window, command_encoder, output);
         Ok(())
     }
 
     fn process(&mut self, event: ProspectEvent, window: &mut ProspectWindow) -> ProcessResponse {
-        self.player.process(event, window);
+        // Don't move the camera while dragging windows or typing into them
+        if !self.egui.handle_event(&event)
+        {
+            self.player.process(event, window);
+        }
+
         match event
         {
             ProspectEvent::KeyboardInput(Some(VirtualKeyCode::Escape), ElementState::Pressed) =>
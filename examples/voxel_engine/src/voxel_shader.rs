@@ -1,9 +1,8 @@
 use prospect::abstraction::graphics_context::GraphicsContext;
-use prospect::abstraction::high_level_abstraction::HighLevelGraphicsContext;
 use prospect::abstraction::prospect_window::ProspectWindow;
 use prospect::abstraction::shader::ProspectShader;
-use prospect::abstraction::vertex::Vertex;
-use prospect::prospect_texture::ProspectTexture;
+use prospect::abstraction::vertex::{Vertex, VertexLayout};
+use prospect::prospect_texture::{ProspectTexture, TextureBindLayout};
 use prospect::smart::SmartBindGroup;
 use prospect::wgpu::{*, self};
 
@@ -12,8 +11,7 @@ pub struct VoxelShader
     module: ShaderModule,
     color_target_state: Vec<Option<ColorTargetState>>,
     matrix_bind_group_layout : BindGroupLayout,
-    sampler : Sampler,
-    bind_layout : BindGroupLayout
+    textures : TextureBindLayout
 }
 
 impl ProspectShader for VoxelShader
@@ -23,7 +21,7 @@ impl ProspectShader for VoxelShader
     }
 
     fn get_module(&self) -> &prospect::wgpu::ShaderModule {
-        todo!()
+        &self.module
     }
 
     fn fragment_state(&self) -> prospect::wgpu::FragmentState {
@@ -47,11 +45,8 @@ impl ProspectShader for VoxelShader
     }
 
     fn build_render_pipeline(&self, device: &Device, bind_groups : Vec<&BindGroupLayout>) -> RenderPipeline {
-        let mut bind_groups = bind_groups;
-        bind_groups.insert(2, &self.matrix_bind_group_layout);
-        bind_groups.insert(3, &self.bind_layout);
-
-        HighLevelGraphicsContext::create_render_pipeline("Voxel Render Pipeline", device, self, Some(&bind_groups))
+        let layout = self.create_pipeline_layout(device, bind_groups);
+        self.build_render_pipeline_with_module(device, &layout, &self.module)
     }
 }
 
@@ -66,11 +61,7 @@ impl VoxelShader
         let src = include_str!("shader/voxel_shader.wgsl");
 
         let sampler = GraphicsContext::create_sampler("Voxel Shader Sampler", device, Some(FilterMode::Nearest), Some(FilterMode::Nearest));
-        let entries = vec![
-            GraphicsContext::create_bind_group_layout_entry(0, ShaderStages::FRAGMENT, GraphicsContext::create_texture_binding_type(false, TextureViewDimension::D2, TextureSampleType::Float { filterable: true })),
-            GraphicsContext::create_bind_group_layout_entry(1, ShaderStages::FRAGMENT, GraphicsContext::create_sample_binding_type(wgpu::SamplerBindingType::Filtering))
-        ];
-        let bind_layout = GraphicsContext::create_bind_group_layout(device, "Voxel Shader Bind Group", &entries);
+        let textures = TextureBindLayout::new(device, "Voxel Shader Bind Group", sampler);
 
         let matrix_bind_group_layout = vec![
             GraphicsContext::create_bind_group_layout_entry(0, ShaderStages::VERTEX, GraphicsContext::create_uniform_binding_type())
@@ -85,28 +76,40 @@ impl VoxelShader
                 blend: Some(BlendState::REPLACE),
                 write_mask: ColorWrites::ALL,
             })],
-            sampler,
-            bind_layout
+            textures
         }
     }
 
+    /// `bind_groups` are the camera and light layouts, the shader's own are added in between
+    pub fn create_pipeline_layout(&self, device : &Device, bind_groups : Vec<&BindGroupLayout>) -> PipelineLayout
+    {
+        let mut bind_groups = bind_groups;
+        bind_groups.insert(2, &self.matrix_bind_group_layout);
+        bind_groups.insert(3, self.textures.get_layout());
+
+        GraphicsContext::create_pipeline_layout("Voxel Render Pipeline", device, &bind_groups)
+    }
+
+    /// For building with a reloaded `module` from a `ShaderRegistry`
+    pub fn build_render_pipeline_with_module(&self, device : &Device, layout : &PipelineLayout, module : &ShaderModule) -> RenderPipeline
+    {
+        let fragment_state = FragmentState { module, ..self.fragment_state() };
+        let vertex_state = VertexState { module, ..self.vertex_state() };
+        GraphicsContext::create_render_pipeline("Voxel Render Pipeline", layout, fragment_state, vertex_state, device)
+    }
+
     pub fn create_texture(&self, window : &ProspectWindow, texture : &TextureView, name : &str) -> BindGroup
     {
-        let view_resource = GraphicsContext::create_texture_view_resource(0, texture);
-        let sampler_resource = GraphicsContext::create_sampler_resource(1, &self.sampler);
-        GraphicsContext::create_bind_group(window.get_device(), name, &self.bind_layout, &vec![view_resource, sampler_resource])
+        self.textures.create_texture(window, texture, name)
     }
 
     pub fn register_texture(&self, name: &str, bytes : &[u8], window: &mut ProspectWindow) -> SmartBindGroup
     {
-        let texture_view = HighLevelGraphicsContext::create_texture_from_file(name, bytes, window);
-        let bind_group = self.create_texture(window, &texture_view, name);
-        bind_group.into()
+        self.textures.register_texture(name, bytes, window)
     }
 
     pub fn bind_prospect_texture(&self, prospect_texture : &ProspectTexture, window: &mut ProspectWindow) -> SmartBindGroup
     {
-        let bind_group = self.create_texture(window, prospect_texture.get_texture_view(), &prospect_texture.get_name());
-        bind_group.into()
-    }  
+        self.textures.bind_prospect_texture(prospect_texture, window)
+    }
 }
\ No newline at end of file
@@ -0,0 +1,150 @@
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, LitStr, Type, Ident, Error};
+
+/// Implements `prospect::abstraction::vertex::VertexLayout` for a `#[repr(C)]` struct.
+///
+/// Each field becomes an attribute with `shader_location` counting up from 0 in declaration order.
+/// The format is worked out from the field type (`f32`, `[f32; 3]`, `[u32; 2]` etc.),
+/// use `#[vertex(format = "Unorm8x4")]` to pick a different one, e.g for normalised colours or packed normals
+#[proc_macro_derive(VertexLayout, attributes(vertex))]
+pub fn derive_vertex_layout(input : TokenStream) -> TokenStream
+{
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match expand(&input)
+    {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+fn expand(input : &DeriveInput) -> Result<proc_macro2::TokenStream, Error>
+{
+    let name = &input.ident;
+
+    let fields = match &input.data
+    {
+        Data::Struct(data) => match &data.fields
+        {
+            Fields::Named(fields) => fields.named.iter().collect::<Vec<_>>(),
+            Fields::Unnamed(fields) => fields.unnamed.iter().collect::<Vec<_>>(),
+            Fields::Unit => return Err(Error::new_spanned(input, "VertexLayout needs at least one field")),
+        },
+        _ => return Err(Error::new_spanned(input, "VertexLayout can only be derived for structs")),
+    };
+
+    let mut attributes = vec![];
+    let mut previous : Vec<&Type> = vec![];
+
+    for (location, field) in fields.iter().enumerate()
+    {
+        let format = match format_override(&field.attrs)?
+        {
+            Some(format) => format,
+            None => infer_format(&field.ty)?,
+        };
+        let location = location as u32;
+
+        // bytemuck's Pod derive rejects padding, so the offset is just the size of the fields before this one
+        attributes.push(quote! {
+            ::prospect::wgpu::VertexAttribute {
+                format: ::prospect::wgpu::VertexFormat::#format,
+                offset: (0 #(+ ::std::mem::size_of::<#previous>())*) as ::prospect::wgpu::BufferAddress,
+                shader_location: #location,
+            }
+        });
+
+        previous.push(&field.ty);
+    }
+
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::prospect::abstraction::vertex::VertexLayout for #name #type_generics #where_clause
+        {
+            const VERTEX_BUFFER_LAYOUT : ::prospect::wgpu::VertexBufferLayout<'static> = ::prospect::wgpu::VertexBufferLayout {
+                array_stride: ::std::mem::size_of::<#name #type_generics>() as ::prospect::wgpu::BufferAddress,
+                step_mode: ::prospect::wgpu::VertexStepMode::Vertex,
+                attributes: &[#(#attributes),*],
+            };
+        }
+    })
+}
+
+fn format_override(attrs : &[syn::Attribute]) -> Result<Option<Ident>, Error>
+{
+    let mut format = None;
+
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("vertex"))
+    {
+        attr.parse_nested_meta(|meta|
+        {
+            if meta.path.is_ident("format")
+            {
+                let value : LitStr = meta.value()?.parse()?;
+                format = Some(Ident::new(&value.value(), value.span()));
+                Ok(())
+            } else
+            {
+                Err(meta.error("expected `format = \"...\"`"))
+            }
+        })?;
+    }
+
+    Ok(format)
+}
+
+fn infer_format(ty : &Type) -> Result<Ident, Error>
+{
+    let (scalar, count) = match ty
+    {
+        Type::Path(path) => (path.path.get_ident().map(|ident| ident.to_string()), 1),
+        Type::Array(array) =>
+        {
+            let count = match &array.len
+            {
+                syn::Expr::Lit(syn::ExprLit { lit : syn::Lit::Int(int), .. }) => int.base10_parse::<usize>()?,
+                _ => 0,
+            };
+            match &*array.elem
+            {
+                Type::Path(path) => (path.path.get_ident().map(|ident| ident.to_string()), count),
+                _ => (None, 0),
+            }
+        },
+        _ => (None, 0),
+    };
+
+    let format = match (scalar.as_deref(), count)
+    {
+        (Some("f32"), 1) => "Float32",
+        (Some("f32"), 2) => "Float32x2",
+        (Some("f32"), 3) => "Float32x3",
+        (Some("f32"), 4) => "Float32x4",
+        (Some("f64"), 1) => "Float64",
+        (Some("f64"), 2) => "Float64x2",
+        (Some("f64"), 3) => "Float64x3",
+        (Some("f64"), 4) => "Float64x4",
+        (Some("u32"), 1) => "Uint32",
+        (Some("u32"), 2) => "Uint32x2",
+        (Some("u32"), 3) => "Uint32x3",
+        (Some("u32"), 4) => "Uint32x4",
+        (Some("i32"), 1) => "Sint32",
+        (Some("i32"), 2) => "Sint32x2",
+        (Some("i32"), 3) => "Sint32x3",
+        (Some("i32"), 4) => "Sint32x4",
+        (Some("u16"), 2) => "Uint16x2",
+        (Some("u16"), 4) => "Uint16x4",
+        (Some("i16"), 2) => "Sint16x2",
+        (Some("i16"), 4) => "Sint16x4",
+        (Some("u8"), 2) => "Uint8x2",
+        (Some("u8"), 4) => "Uint8x4",
+        (Some("i8"), 2) => "Sint8x2",
+        (Some("i8"), 4) => "Sint8x4",
+        _ => return Err(Error::new_spanned(ty, "no vertex format for this type, add #[vertex(format = \"...\")]")),
+    };
+
+    Ok(Ident::new(format, Span::call_site()))
+}
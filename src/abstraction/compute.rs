@@ -0,0 +1,223 @@
+use wgpu::*;
+
+use super::{graphics_context::GraphicsContext, prospect_window::ProspectWindow};
+
+/// What a compute shader expects at one binding of a bind group
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComputeBinding
+{
+    Uniform,
+    Storage { read_only : bool },
+    StorageTexture { access : StorageTextureAccess, format : TextureFormat, dimension : TextureViewDimension },
+}
+
+impl ComputeBinding
+{
+    /// A read only `var<storage, read>`
+    pub const STORAGE_READ : ComputeBinding = ComputeBinding::Storage { read_only : true };
+    /// A `var<storage, read_write>`
+    pub const STORAGE_READ_WRITE : ComputeBinding = ComputeBinding::Storage { read_only : false };
+
+    /// A `texture_storage_2d<format, write>`
+    pub fn storage_texture_2d(format : TextureFormat) -> Self
+    {
+        Self::StorageTexture { access : StorageTextureAccess::WriteOnly, format, dimension : TextureViewDimension::D2 }
+    }
+
+    fn binding_type(&self) -> BindingType
+    {
+        match *self
+        {
+            ComputeBinding::Uniform => GraphicsContext::create_uniform_binding_type(),
+            ComputeBinding::Storage { read_only } => GraphicsContext::create_storage_binding_type(read_only),
+            ComputeBinding::StorageTexture { access, format, dimension } => GraphicsContext::create_storage_texture_binding_type(access, format, dimension),
+        }
+    }
+}
+
+/// Something bound to a `ComputeBinding`, buffers for `Uniform` and `Storage`, texture views for `StorageTexture`
+#[derive(Debug, Clone, Copy)]
+pub enum ComputeResource<'a>
+{
+    Buffer(&'a Buffer),
+    TextureView(&'a TextureView),
+}
+
+impl<'a> From<&'a Buffer> for ComputeResource<'a>
+{
+    fn from(value : &'a Buffer) -> Self
+    {
+        Self::Buffer(value)
+    }
+}
+
+impl<'a> From<&'a TextureView> for ComputeResource<'a>
+{
+    fn from(value : &'a TextureView) -> Self
+    {
+        Self::TextureView(value)
+    }
+}
+
+/// A compute pipeline along with the layouts of its bind groups.
+///
+/// `workgroup_size` must match the entry point's `@workgroup_size`, it's what `dispatch_for` divides by.
+/// Native only, wasm32 builds request `Limits::downlevel_webgl2_defaults` which has no compute shaders or storage buffers
+pub struct ProspectComputeShader
+{
+    name : String,
+    module : ShaderModule,
+    bindings : Vec<Vec<ComputeBinding>>,
+    layouts : Vec<BindGroupLayout>,
+    pipeline : ComputePipeline,
+    workgroup_size : [u32; 3],
+    /// The device's `max_compute_workgroups_per_dimension`
+    max_workgroups : u32,
+}
+
+impl ProspectComputeShader
+{
+    /// `groups[n][m]` describes `@group(n) @binding(m)`
+    pub fn new(device : &Device, name : &str, src : &str, entry_point : &str, workgroup_size : [u32; 3], groups : &[&[ComputeBinding]]) -> Self
+    {
+        let module = GraphicsContext::load_shader(name, src, device);
+
+        let layouts : Vec<BindGroupLayout> = groups.iter().enumerate().map(|(group, bindings)|
+        {
+            let entries : Vec<BindGroupLayoutEntry> = bindings.iter().enumerate()
+                .map(|(binding, ty)| GraphicsContext::create_bind_group_layout_entry(binding as u32, ShaderStages::COMPUTE, ty.binding_type()))
+                .collect();
+            GraphicsContext::create_bind_group_layout(device, &format!("{} Group {} Layout", name, group), entries)
+        }).collect();
+
+        let layout = GraphicsContext::create_pipeline_layout(&format!("{} Pipeline Layout", name), device, &layouts.iter().collect());
+        let pipeline = GraphicsContext::create_compute_pipeline(name, &layout, &module, entry_point, device);
+
+        Self
+        {
+            name : name.to_string(),
+            module,
+            bindings : groups.iter().map(|bindings| bindings.to_vec()).collect(),
+            layouts,
+            pipeline,
+            workgroup_size : workgroup_size.map(|size| size.max(1)),
+            max_workgroups : device.limits().max_compute_workgroups_per_dimension,
+        }
+    }
+
+    pub fn get_name(&self) -> &str
+    {
+        &self.name
+    }
+
+    pub fn get_module(&self) -> &ShaderModule
+    {
+        &self.module
+    }
+
+    pub fn get_pipeline(&self) -> &ComputePipeline
+    {
+        &self.pipeline
+    }
+
+    pub fn get_bind_group_layout(&self, group : usize) -> Option<&BindGroupLayout>
+    {
+        self.layouts.get(group)
+    }
+
+    pub fn workgroup_size(&self) -> [u32; 3]
+    {
+        self.workgroup_size
+    }
+
+    /// Binds `resources` in order to group `group`.
+    /// Fails if there are the wrong number of them, or one doesn't suit its binding (e.g a buffer without `BufferUsages::STORAGE` for a storage binding)
+    pub fn create_bind_group(&self, device : &Device, group : usize, resources : &[ComputeResource]) -> Result<BindGroup, ()>
+    {
+        let Some(bindings) = self.bindings.get(group) else
+        {
+            log::error!("{} has no bind group {}", self.name, group);
+            return Err(());
+        };
+        if bindings.len() != resources.len()
+        {
+            log::error!("{} group {} has {} bindings but {} resources were given", self.name, group, bindings.len(), resources.len());
+            return Err(());
+        }
+
+        let mut entries = Vec::with_capacity(resources.len());
+        for (index, (binding, resource)) in bindings.iter().zip(resources).enumerate()
+        {
+            let resource = match (binding, resource)
+            {
+                (ComputeBinding::Uniform, ComputeResource::Buffer(buffer)) if buffer.usage().contains(BufferUsages::UNIFORM) => buffer.as_entire_binding(),
+                (ComputeBinding::Storage { .. }, ComputeResource::Buffer(buffer)) if buffer.usage().contains(BufferUsages::STORAGE) => buffer.as_entire_binding(),
+                (ComputeBinding::StorageTexture { .. }, ComputeResource::TextureView(view)) => BindingResource::TextureView(view),
+                _ =>
+                {
+                    log::error!("{} group {} binding {} expects {:?} but was given {:?}", self.name, group, index, binding, resource);
+                    return Err(());
+                }
+            };
+            entries.push(GraphicsContext::create_bind_group_entry(index as u32, resource));
+        }
+
+        Ok(GraphicsContext::create_bind_group(device, &format!("{} Group {}", self.name, group), &self.layouts[group], &entries))
+    }
+
+    /// Records `workgroups` workgroups with `bind_groups` set from group 0 up.
+    /// Fails if any dimension is over the device's `max_compute_workgroups_per_dimension` (65535 by default)
+    pub fn dispatch(&self, encoder : &mut CommandEncoder, bind_groups : &[&BindGroup], workgroups : [u32; 3]) -> Result<(), ()>
+    {
+        if workgroups.iter().any(|count| *count > self.max_workgroups)
+        {
+            log::error!("{} can't dispatch {:?} workgroups, the device allows at most {} per dimension", self.name, workgroups, self.max_workgroups);
+            return Err(());
+        }
+
+        let mut pass = self.begin_pass(encoder, bind_groups);
+        pass.dispatch_workgroups(workgroups[0], workgroups[1], workgroups[2]);
+        Ok(())
+    }
+
+    /// Records enough workgroups to cover `size` invocations, so edge invocations should check they're in bounds.
+    /// Fails like `dispatch` if that needs too many, split the work into several dispatches or use a bigger workgroup
+    pub fn dispatch_for(&self, encoder : &mut CommandEncoder, bind_groups : &[&BindGroup], size : [u32; 3]) -> Result<(), ()>
+    {
+        self.dispatch(encoder, bind_groups, self.workgroups_for(size))
+    }
+
+    /// Takes the workgroup counts from three `u32`s at `offset` in `indirect_buffer`, for sizes another pass worked out on the GPU.
+    /// These can't be checked here, so whatever writes them has to keep them within `max_compute_workgroups_per_dimension`
+    pub fn dispatch_indirect(&self, encoder : &mut CommandEncoder, bind_groups : &[&BindGroup], indirect_buffer : &Buffer, offset : u64)
+    {
+        let mut pass = self.begin_pass(encoder, bind_groups);
+        pass.dispatch_workgroups_indirect(indirect_buffer, offset);
+    }
+
+    /// Dispatches in its own submission, see `GraphicsContext::read_buffer` to get the results
+    pub fn run(&self, window : &ProspectWindow, bind_groups : &[&BindGroup], size : [u32; 3]) -> Result<(), ()>
+    {
+        let mut encoder = GraphicsContext::create_command_encoder(window.get_device(), &self.name);
+        self.dispatch_for(&mut encoder, bind_groups, size)?;
+        window.get_queue().submit(Some(encoder.finish()));
+        Ok(())
+    }
+
+    /// Workgroups needed to cover `size` invocations
+    pub fn workgroups_for(&self, size : [u32; 3]) -> [u32; 3]
+    {
+        [0, 1, 2].map(|axis| size[axis].div_ceil(self.workgroup_size[axis]))
+    }
+
+    fn begin_pass<'a>(&'a self, encoder : &'a mut CommandEncoder, bind_groups : &[&'a BindGroup]) -> ComputePass<'a>
+    {
+        let mut pass = GraphicsContext::begin_compute_pass(encoder, &self.name);
+        pass.set_pipeline(&self.pipeline);
+        for (index, bind_group) in bind_groups.iter().enumerate()
+        {
+            pass.set_bind_group(index as u32, bind_group, &[]);
+        }
+        pass
+    }
+}
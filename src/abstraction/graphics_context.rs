@@ -1,5 +1,12 @@
-use bytemuck::NoUninit;
-use image::RgbaImage;
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+};
+
+use bytemuck::{NoUninit, Pod};
+use image::{Rgba32FImage, RgbaImage};
 use wgpu::*;
 
 use wgpu::util::{BufferInitDescriptor, DeviceExt};
@@ -11,8 +18,40 @@ use winit::{
 
 use crate::prospect_texture::ProspectTexture;
 
+use super::mipmaps::{mip_level_count, AtlasLayout, MipmapGenerator};
+
 pub struct GraphicsContext;
 
+#[derive(Default)]
+struct BufferMapState {
+    result: Option<Result<(), BufferAsyncError>>,
+    waker: Option<Waker>,
+}
+
+/// Resolves once `map_async` has called back, which wakes it. Checks the device without blocking each time it's polled
+struct BufferMapFuture<'a> {
+    device: &'a Device,
+    state: Arc<Mutex<BufferMapState>>,
+}
+
+impl Future for BufferMapFuture<'_> {
+    type Output = Result<(), BufferAsyncError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if let Some(result) = self.state.lock().unwrap().result.take() {
+            return Poll::Ready(result);
+        }
+        self.state.lock().unwrap().waker = Some(cx.waker().clone());
+
+        // Native backends only run the callback while the device is polled, on the web this does nothing and the browser calls it
+        self.device.poll(Maintain::Poll);
+        match self.state.lock().unwrap().result.take() {
+            Some(result) => Poll::Ready(result),
+            None => Poll::Pending,
+        }
+    }
+}
+
 impl GraphicsContext {
     pub fn init() {
         env_logger::init();
@@ -189,6 +228,28 @@ impl GraphicsContext {
         })
     }
 
+    pub fn create_compute_pipeline(
+        name: &str,
+        layout: &PipelineLayout,
+        module: &ShaderModule,
+        entry_point: &str,
+        device: &Device,
+    ) -> ComputePipeline {
+        device.create_compute_pipeline(&ComputePipelineDescriptor {
+            label: Some(name),
+            layout: Some(layout),
+            module,
+            entry_point,
+        })
+    }
+
+    pub fn begin_compute_pass<'pass>(encoder: &'pass mut CommandEncoder, label: &str) -> ComputePass<'pass> {
+        encoder.begin_compute_pass(&ComputePassDescriptor {
+            label: Some(label),
+            timestamp_writes: None,
+        })
+    }
+
     pub fn create_render_pipeline(
         name: &str,
         layout: &PipelineLayout,
@@ -266,6 +327,88 @@ impl GraphicsContext {
         })
     }
 
+    /// For passes that need a different depth test, e.g `CompareFunction::LessEqual` without depth writes for a skybox
+    pub fn create_render_pipeline_with_depth_stencil(
+        name: &str,
+        layout: &PipelineLayout,
+        fragment_state: FragmentState,
+        vertex_state: VertexState,
+        device: &Device,
+        primitive_state: PrimitiveState,
+        depth_stencil: DepthStencilState,
+    ) -> RenderPipeline {
+        device.create_render_pipeline(&RenderPipelineDescriptor {
+            label: Some(name),
+            layout: Some(layout),
+            vertex: vertex_state,
+            fragment: Some(fragment_state),
+            primitive: primitive_state,
+            depth_stencil: Some(depth_stencil),
+            multisample: MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        })
+    }
+
+    pub fn create_render_pipeline_without_depth(
+        name: &str,
+        layout: &PipelineLayout,
+        fragment_state: FragmentState,
+        vertex_state: VertexState,
+        device: &Device,
+        primitive_state: PrimitiveState,
+    ) -> RenderPipeline {
+        device.create_render_pipeline(&RenderPipelineDescriptor {
+            label: Some(name),
+            layout: Some(layout),
+            vertex: vertex_state,
+            fragment: Some(fragment_state),
+            primitive: primitive_state,
+            depth_stencil: None,
+            multisample: MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        })
+    }
+
+    pub fn begin_render_pass_colour_only<'pass>(
+        clear_color: Option<(f64, f64, f64, f64)>,
+        label: &str,
+        view: &'pass TextureView,
+        command_encoder: &'pass mut CommandEncoder,
+    ) -> RenderPass<'pass> {
+        let load = match clear_color {
+            Some(clear_color) => LoadOp::Clear(Color {
+                r: clear_color.0,
+                g: clear_color.1,
+                b: clear_color.2,
+                a: clear_color.3,
+            }),
+            None => LoadOp::Load,
+        };
+
+        command_encoder.begin_render_pass(&RenderPassDescriptor {
+            label: Some(label),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: &view,
+                resolve_target: None,
+                ops: Operations {
+                    load,
+                    store: StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        })
+    }
+
     pub fn create_buffer<A: NoUninit>(
         device: &Device,
         name: &str,
@@ -283,6 +426,98 @@ impl GraphicsContext {
         queue.write_buffer(buffer, offset, bytemuck::cast_slice(data))
     }
 
+    /// `size` bytes of zeros
+    pub fn create_empty_buffer(device: &Device, name: &str, size: u64, usage: BufferUsages) -> Buffer {
+        device.create_buffer(&BufferDescriptor {
+            label: Some(name),
+            size,
+            usage,
+            mapped_at_creation: false,
+        })
+    }
+
+    /// A buffer compute shaders can read and write, which can also be written from the CPU and read back with `read_buffer`
+    pub fn create_storage_buffer<A: NoUninit>(device: &Device, name: &str, contents: &[A]) -> Buffer {
+        Self::create_buffer(
+            device,
+            name,
+            contents,
+            BufferUsages::STORAGE | BufferUsages::COPY_DST | BufferUsages::COPY_SRC,
+        )
+    }
+
+    /// Copies the whole of `buffer` back to the CPU, any bytes past the last whole `T` are dropped.
+    /// The buffer needs `BufferUsages::COPY_SRC`.
+    ///
+    /// On native the future only resolves once the device is polled after the copy finishes, which every `Queue::submit` and
+    /// `Device::poll` does, so it completes within a frame or two while something is rendering. Otherwise the caller has to poll
+    /// the device itself, e.g `device.poll(Maintain::Wait)` on another thread, `pollster::block_on` on its own can wait forever
+    pub async fn read_buffer<T: Pod>(device: &Device, queue: &Queue, buffer: &Buffer) -> Result<Vec<T>, ()> {
+        Self::read_buffer_range(device, queue, buffer, 0, buffer.size()).await
+    }
+
+    /// Copies `size` bytes from `offset` back to the CPU, both must be multiples of 4
+    pub async fn read_buffer_range<T: Pod>(
+        device: &Device,
+        queue: &Queue,
+        buffer: &Buffer,
+        offset: u64,
+        size: u64,
+    ) -> Result<Vec<T>, ()> {
+        if !buffer.usage().contains(BufferUsages::COPY_SRC) {
+            log::error!("Can't read back a buffer without BufferUsages::COPY_SRC");
+            return Err(());
+        }
+        if offset % COPY_BUFFER_ALIGNMENT != 0 || size % COPY_BUFFER_ALIGNMENT != 0 || offset + size > buffer.size() {
+            log::error!(
+                "Can't read {} bytes at {} from a buffer of {}, both must be multiples of {}",
+                size,
+                offset,
+                buffer.size(),
+                COPY_BUFFER_ALIGNMENT
+            );
+            return Err(());
+        }
+        let element_size = std::mem::size_of::<T>() as u64;
+        if size == 0 || element_size == 0 {
+            return Ok(Vec::new());
+        }
+
+        let staging = Self::create_empty_buffer(
+            device,
+            "Readback Buffer",
+            size,
+            BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+        );
+        let mut encoder = Self::create_command_encoder(device, "Readback Encoder");
+        encoder.copy_buffer_to_buffer(buffer, offset, &staging, 0, size);
+        queue.submit(Some(encoder.finish()));
+
+        let slice = staging.slice(..);
+        let state = Arc::new(Mutex::new(BufferMapState::default()));
+        let callback_state = state.clone();
+        slice.map_async(MapMode::Read, move |result| {
+            let mut state = callback_state.lock().unwrap();
+            state.result = Some(result);
+            if let Some(waker) = state.waker.take() {
+                waker.wake();
+            }
+        });
+
+        if let Err(err) = (BufferMapFuture { device, state }).await {
+            log::error!("Failed to map readback buffer: {}", err);
+            return Err(());
+        }
+
+        let data = {
+            let bytes = slice.get_mapped_range();
+            let whole = (size - size % element_size) as usize;
+            bytemuck::pod_collect_to_vec(&bytes[..whole])
+        };
+        staging.unmap();
+        Ok(data)
+    }
+
     pub fn create_texture_raw(
         label: &str,
         width: u32,
@@ -291,21 +526,37 @@ impl GraphicsContext {
         device: &Device,
         queue: &Queue,
     ) -> Result<Texture, ()> {
-        let img = RgbaImage::from_vec(width, height, bytes);
-        if img.is_none() {
+        let expected = width as usize * height as usize * 4;
+        if bytes.len() != expected {
+            log::error!("Texture \"{}\" is {}x{} so needs {} bytes of RGBA, got {}", label, width, height, expected, bytes.len());
             return Err(());
         }
-        let img = img.unwrap();
+        let img = RgbaImage::from_vec(width, height, bytes).ok_or(())?;
         Ok(GraphicsContext::create_texture_from_image(
             label, img, device, queue,
         ))
     }
 
-    pub fn create_texture(label: &str, bytes: &[u8], device: &Device, queue: &Queue) -> Texture {
-        let img = image::load_from_memory(bytes).unwrap();
+    /// Fails if `bytes` isn't an image in a format `image` was built with
+    pub fn create_texture(label: &str, bytes: &[u8], device: &Device, queue: &Queue) -> Result<Texture, image::ImageError> {
+        let img = image::load_from_memory(bytes)?;
+        let raw = img.to_rgba8();
+
+        Ok(GraphicsContext::create_texture_from_image(label, raw, device, queue))
+    }
+
+    /// Use `TextureFormat::Rgba8Unorm` for data such as normal maps that shouldn't be treated as sRGB
+    pub fn create_texture_with_format(
+        label: &str,
+        bytes: &[u8],
+        device: &Device,
+        queue: &Queue,
+        format: TextureFormat,
+    ) -> Result<Texture, image::ImageError> {
+        let img = image::load_from_memory(bytes)?;
         let raw = img.to_rgba8();
 
-        GraphicsContext::create_texture_from_image(label, raw, device, queue)
+        Ok(GraphicsContext::create_texture_from_image_with_format(label, raw, device, queue, format))
     }
 
     pub fn create_texture_from_image(
@@ -313,6 +564,37 @@ impl GraphicsContext {
         raw: RgbaImage,
         device: &Device,
         queue: &Queue,
+    ) -> Texture {
+        GraphicsContext::create_texture_from_image_with_format(
+            label,
+            raw,
+            device,
+            queue,
+            TextureFormat::Rgba8UnormSrgb,
+        )
+    }
+
+    /// A single mip level, see `create_texture_from_image_with_mipmaps` for a full chain
+    pub fn create_texture_from_image_with_format(
+        label: &str,
+        raw: RgbaImage,
+        device: &Device,
+        queue: &Queue,
+        format: TextureFormat,
+    ) -> Texture {
+        GraphicsContext::create_texture_from_image_with_mipmaps(label, raw, device, queue, format, None, None)
+    }
+
+    /// Mipmaps are generated on the GPU after uploading when given a generator (see `ProspectWindow::get_mipmap_generator`),
+    /// pass an `AtlasLayout` so tiles of an atlas don't bleed into each other
+    pub fn create_texture_from_image_with_mipmaps(
+        label: &str,
+        raw: RgbaImage,
+        device: &Device,
+        queue: &Queue,
+        format: TextureFormat,
+        mipmaps: Option<&MipmapGenerator>,
+        atlas: Option<&AtlasLayout>,
     ) -> Texture {
         let dimensions = raw.dimensions();
 
@@ -322,14 +604,25 @@ impl GraphicsContext {
             depth_or_array_layers: 1,
         };
 
+        let mip_level_count = if mipmaps.is_some() {
+            mip_level_count(dimensions.0, dimensions.1, atlas)
+        } else {
+            1
+        };
+
+        let mut usage = TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST;
+        if mip_level_count > 1 {
+            usage |= TextureUsages::RENDER_ATTACHMENT;
+        }
+
         let texture = device.create_texture(&TextureDescriptor {
             label: Some(label),
             size,
-            mip_level_count: 1,
+            mip_level_count,
             sample_count: 1,
             dimension: TextureDimension::D2,
-            format: TextureFormat::Rgba8UnormSrgb,
-            usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+            format,
+            usage,
             view_formats: &[],
         });
 
@@ -349,13 +642,278 @@ impl GraphicsContext {
             size,
         );
 
+        if let Some(mipmaps) = mipmaps {
+            mipmaps.generate(device, queue, &texture, atlas);
+        }
+
         texture
     }
 
+    /// Copies the `width` x `height` rectangle at `x`, `y` of `image` into the same place in mip level 0 of `texture`,
+    /// which must be the same size as `image`
+    pub fn update_texture_region(
+        queue: &Queue,
+        texture: &Texture,
+        image: &RgbaImage,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) {
+        queue.write_texture(
+            ImageCopyTexture {
+                texture,
+                mip_level: 0,
+                origin: Origin3d { x, y, z: 0 },
+                aspect: TextureAspect::All,
+            },
+            image,
+            ImageDataLayout {
+                offset: 4 * (y as u64 * image.width() as u64 + x as u64),
+                bytes_per_row: Some(4 * image.width()),
+                rows_per_image: Some(image.height()),
+            },
+            Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
+    /// Copies all of `image` into mip level 0 of `texture` with its top left at `x`, `y`
+    pub fn write_image_to_texture(queue: &Queue, texture: &Texture, image: &RgbaImage, x: u32, y: u32) {
+        queue.write_texture(
+            ImageCopyTexture {
+                texture,
+                mip_level: 0,
+                origin: Origin3d { x, y, z: 0 },
+                aspect: TextureAspect::All,
+            },
+            image,
+            ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * image.width()),
+                rows_per_image: Some(image.height()),
+            },
+            Extent3d {
+                width: image.width(),
+                height: image.height(),
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
     pub fn create_texture_view(texture: &Texture) -> TextureView {
         texture.create_view(&TextureViewDescriptor::default())
     }
 
+    /// Six square faces in the order +X, -X, +Y, -Y, +Z, -Z, with a full mip chain when given a generator
+    pub fn create_cubemap_from_images(
+        label: &str,
+        faces: &[RgbaImage; 6],
+        device: &Device,
+        queue: &Queue,
+        format: TextureFormat,
+        mipmaps: Option<&MipmapGenerator>,
+    ) -> Result<Texture, ()> {
+        let dimensions = faces[0].dimensions();
+        if dimensions.0 != dimensions.1 {
+            log::error!("Cubemap \"{}\" needs six square faces of the same size", label);
+            return Err(());
+        }
+
+        GraphicsContext::create_texture_array_from_images(label, faces, device, queue, format, mipmaps)
+    }
+
+    /// One layer per image, every image must be the same size. Each layer gets a full mip chain when given a generator
+    pub fn create_texture_array_from_images(
+        label: &str,
+        layers: &[RgbaImage],
+        device: &Device,
+        queue: &Queue,
+        format: TextureFormat,
+        mipmaps: Option<&MipmapGenerator>,
+    ) -> Result<Texture, ()> {
+        let dimensions = match layers.first() {
+            Some(layer) => layer.dimensions(),
+            None => {
+                log::error!("Texture array \"{}\" needs at least one layer", label);
+                return Err(());
+            }
+        };
+        if layers.iter().any(|layer| layer.dimensions() != dimensions) {
+            log::error!("Every layer of texture array \"{}\" needs to be the same size", label);
+            return Err(());
+        }
+
+        let size = Extent3d {
+            width: dimensions.0,
+            height: dimensions.1,
+            depth_or_array_layers: layers.len() as u32,
+        };
+
+        let mip_level_count = if mipmaps.is_some() {
+            mip_level_count(dimensions.0, dimensions.1, None)
+        } else {
+            1
+        };
+
+        let mut usage = TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST;
+        if mip_level_count > 1 {
+            usage |= TextureUsages::RENDER_ATTACHMENT;
+        }
+
+        let texture = device.create_texture(&TextureDescriptor {
+            label: Some(label),
+            size,
+            mip_level_count,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format,
+            usage,
+            view_formats: &[],
+        });
+
+        for (index, layer) in layers.iter().enumerate() {
+            queue.write_texture(
+                ImageCopyTexture {
+                    texture: &texture,
+                    mip_level: 0,
+                    origin: Origin3d {
+                        x: 0,
+                        y: 0,
+                        z: index as u32,
+                    },
+                    aspect: TextureAspect::All,
+                },
+                layer,
+                ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(4 * dimensions.0),
+                    rows_per_image: Some(dimensions.1),
+                },
+                Extent3d {
+                    depth_or_array_layers: 1,
+                    ..size
+                },
+            );
+        }
+
+        if let Some(mipmaps) = mipmaps {
+            mipmaps.generate(device, queue, &texture, None);
+        }
+
+        Ok(texture)
+    }
+
+    pub fn create_cube_texture_view(texture: &Texture) -> TextureView {
+        texture.create_view(&TextureViewDescriptor {
+            dimension: Some(TextureViewDimension::Cube),
+            ..Default::default()
+        })
+    }
+
+    pub fn create_array_texture_view(texture: &Texture) -> TextureView {
+        texture.create_view(&TextureViewDescriptor {
+            dimension: Some(TextureViewDimension::D2Array),
+            ..Default::default()
+        })
+    }
+
+    /// An empty cubemap to render into, one face at a time through `TextureViewDimension::D2` views
+    pub fn create_render_target_cubemap(
+        label: &str,
+        size: u32,
+        mip_level_count: u32,
+        format: TextureFormat,
+        device: &Device,
+    ) -> Texture {
+        device.create_texture(&TextureDescriptor {
+            label: Some(label),
+            size: Extent3d {
+                width: size,
+                height: size,
+                depth_or_array_layers: 6,
+            },
+            mip_level_count,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format,
+            usage: TextureUsages::TEXTURE_BINDING | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        })
+    }
+
+    /// A texture compute shaders can write to and everything else can sample, `format` must support storage (e.g `Rgba8Unorm` or `Rgba32Float`, not sRGB)
+    pub fn create_storage_texture(
+        label: &str,
+        width: u32,
+        height: u32,
+        format: TextureFormat,
+        device: &Device,
+    ) -> Texture {
+        device.create_texture(&TextureDescriptor {
+            label: Some(label),
+            size: Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format,
+            usage: TextureUsages::STORAGE_BINDING | TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_SRC | TextureUsages::COPY_DST,
+            view_formats: &[],
+        })
+    }
+
+    /// Uploads a floating point image as `Rgba32Float`, which can't be filtered so it has no mipmaps
+    pub fn create_texture_from_hdr_image(
+        label: &str,
+        raw: &Rgba32FImage,
+        device: &Device,
+        queue: &Queue,
+    ) -> Texture {
+        let dimensions = raw.dimensions();
+
+        let size = Extent3d {
+            width: dimensions.0,
+            height: dimensions.1,
+            depth_or_array_layers: 1,
+        };
+
+        let texture = device.create_texture(&TextureDescriptor {
+            label: Some(label),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Rgba32Float,
+            usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+
+        queue.write_texture(
+            ImageCopyTexture {
+                texture: &texture,
+                mip_level: 0,
+                origin: Origin3d::ZERO,
+                aspect: TextureAspect::All,
+            },
+            bytemuck::cast_slice(raw.as_raw()),
+            ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(16 * dimensions.0),
+                rows_per_image: Some(dimensions.1),
+            },
+            size,
+        );
+
+        texture
+    }
+
     pub fn create_sampler(
         label: &str,
         device: &Device,
@@ -425,6 +983,26 @@ impl GraphicsContext {
         }
     }
 
+    pub fn create_storage_binding_type(read_only: bool) -> BindingType {
+        BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Storage { read_only },
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        }
+    }
+
+    pub fn create_storage_texture_binding_type(
+        access: StorageTextureAccess,
+        format: TextureFormat,
+        view_dimension: TextureViewDimension,
+    ) -> BindingType {
+        BindingType::StorageTexture {
+            access,
+            format,
+            view_dimension,
+        }
+    }
+
     pub const fn create_bind_group_layout_entry(
         binding: u32,
         shader_stage: ShaderStages,
@@ -528,6 +1106,22 @@ impl GraphicsContext {
         label: &str,
         width: u32,
         height: u32,
+    ) -> (Texture, TextureView, Sampler) {
+        GraphicsContext::create_framebuffer_with_format(
+            device,
+            label,
+            width,
+            height,
+            TextureFormat::Bgra8UnormSrgb,
+        )
+    }
+
+    pub fn create_framebuffer_with_format(
+        device: &Device,
+        label: &str,
+        width: u32,
+        height: u32,
+        format: TextureFormat,
     ) -> (Texture, TextureView, Sampler) {
         let size = Extent3d {
             width,
@@ -541,7 +1135,7 @@ impl GraphicsContext {
             mip_level_count: 1,
             sample_count: 1,
             dimension: TextureDimension::D2,
-            format: TextureFormat::Bgra8UnormSrgb,
+            format,
             usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
             view_formats: &[],
         };
@@ -14,6 +14,7 @@ impl HighLevelGraphicsContext {
         EventLoop<()>,
         Window,
         Surface,
+        Adapter,
         Device,
         Queue,
         SurfaceConfiguration,
@@ -41,7 +42,7 @@ impl HighLevelGraphicsContext {
             (size.width, size.height),
         );
 
-        (event_loop, window, surface, device, queue, config)
+        (event_loop, window, surface, adapter, device, queue, config)
     }
 
     pub fn init_view(window: &ProspectWindow) -> (SurfaceTexture, TextureView, CommandEncoder) {
@@ -93,20 +94,24 @@ impl HighLevelGraphicsContext {
     }
 
 
-    pub fn create_texture_from_bytes(name: &str, width: u32, height: u32, bytes : Vec<u8>, window: &ProspectWindow) -> Option<TextureView>
+    /// Fails if `bytes` isn't exactly `width` x `height` RGBA texels
+    pub fn create_texture_from_bytes(name: &str, width: u32, height: u32, bytes : Vec<u8>, window: &ProspectWindow) -> Result<TextureView, ()>
     {
-        let texture = GraphicsContext::create_texture_raw(name, width, height, bytes, window.get_device(), window.get_queue());
-        if texture.is_err()
-        {
-            
-        }
-        Some(GraphicsContext::create_texture_view(&texture.unwrap()))
+        let texture = GraphicsContext::create_texture_raw(name, width, height, bytes, window.get_device(), window.get_queue())?;
+        Ok(GraphicsContext::create_texture_view(&texture))
     }
 
-    pub fn create_texture_from_file(name: &str, bytes : &[u8], window: &ProspectWindow) -> TextureView
+    /// With a full mip chain
+    pub fn create_texture_from_file(name: &str, bytes : &[u8], window: &ProspectWindow) -> Result<TextureView, image::ImageError>
     {
-        let texture = GraphicsContext::create_texture(name, bytes, window.get_device(), window.get_queue());
-        GraphicsContext::create_texture_view(&texture)
+        HighLevelGraphicsContext::create_texture_from_file_with_format(name, bytes, TextureFormat::Rgba8UnormSrgb, window)
+    }
+
+    pub fn create_texture_from_file_with_format(name: &str, bytes : &[u8], format : TextureFormat, window: &ProspectWindow) -> Result<TextureView, image::ImageError>
+    {
+        let image = image::load_from_memory(bytes)?.to_rgba8();
+        let texture = GraphicsContext::create_texture_from_image_with_mipmaps(name, image, window.get_device(), window.get_queue(), format, Some(window.get_mipmap_generator()), None);
+        Ok(GraphicsContext::create_texture_view(&texture))
     }
 
     pub fn create_uniform_with_bind_group(device : &Device, label : &str, buffer : &Buffer, bind_group_layout : &BindGroupLayout) -> BindGroup
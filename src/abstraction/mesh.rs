@@ -1,13 +1,10 @@
-use std::collections::HashMap;
-
-
-
-use wgpu::{Buffer, BufferUsages, RenderPass, Device, BindGroup};
+use wgpu::{Buffer, BufferUsages, RenderPass, Device};
 
 
+use crate::prospect_material::Material;
 use crate::smart::{SmartRenderPipeline, SmartBindGroup};
 use crate::{prospect_shape::ProspectShape, prospect_camera::ProspectCamera};
-use super::{vertex::Vertex, graphics_context::GraphicsContext};
+use super::{vertex::{Vertex, VertexLayout}, graphics_context::GraphicsContext};
 
 pub trait Meshable
 {
@@ -20,76 +17,117 @@ pub struct Mesh
     vertex_buffer : Buffer,
     index_buffer : Buffer,
     index_count : u32,
-    render_pipeline : SmartRenderPipeline,
-    bind_groups : HashMap<u32, SmartBindGroup>
+    material : Material
 }
 
 impl Mesh
 {
-    pub fn from_shape<T, U>(shape : &ProspectShape<T, U>, device : &Device, pipeline : &SmartRenderPipeline) -> Self
-        where   T : Into<Vec<Vertex>> + Clone,
+    pub fn from_shape<V, T, U>(shape : &ProspectShape<T, U>, device : &Device, pipeline : &SmartRenderPipeline) -> Self
+        where   V : VertexLayout,
+                T : Into<Vec<V>> + Clone,
                 U : Into<Vec<u32>> + Clone
     {
-        let vertices = shape.vertices.clone().into();
+        let vertices = shape.get_vertices();
+        let indices = shape.get_indices::<V>();
 
-        let indices = if shape.indices.is_none()
-        {
-            // Auto Generate indices
-            let mut v = vec![];
-            let l = vertices.len() as u32;
-            for i in 0..l
-            {
-                v.push(l - i - 1);
-            }
-            v
-        } else
-        {
-            shape.indices.clone().unwrap().into()
-        };
+        Self::new(vertices, indices, device, pipeline)
+    }
+
+    /// Generates tangents for the shape, use with a normal mapped shader such as `Default3D::new_normal_mapped`
+    pub fn from_shape_with_tangents<T, U>(shape : &ProspectShape<T, U>, device : &Device, pipeline : &SmartRenderPipeline) -> Self
+        where   T : Into<Vec<Vertex>> + Clone,
+                U : Into<Vec<u32>> + Clone
+    {
+        let (vertices, indices) = shape.with_tangents();
 
         Self::new(vertices, indices, device, pipeline)
     }
 
-    pub fn new<T, U>(vertices : T, indices : U, device : &Device, pipeline : &SmartRenderPipeline) -> Self 
-        where   T : Into<Vec<Vertex>>,
+    /// Works with any vertex type, as long as `pipeline` was built with the same `VertexLayout`
+    pub fn new<V, T, U>(vertices : T, indices : U, device : &Device, pipeline : &SmartRenderPipeline) -> Self 
+        where   V : VertexLayout,
+                T : Into<Vec<V>>,
                 U : Into<Vec<u32>>
     {
-        let vertices = vertices.into();
-        let indices = indices.into();
+        Self::from_buffers(&vertices.into(), &indices.into(), device, pipeline)
+    }
+
+    fn from_buffers<V : VertexLayout>(vertices : &[V], indices : &[u32], device : &Device, pipeline : &SmartRenderPipeline) -> Self
+    {
         let count = indices.len();
 
-        let vertex_buffer = GraphicsContext::create_buffer(device, "Vertex Buffer: MeshIndexed", &vertices, BufferUsages::VERTEX);
-        let index_buffer = GraphicsContext::create_buffer(device, "Index Buffer: MeshIndexed", &indices, BufferUsages::INDEX);
+        let vertex_buffer = GraphicsContext::create_buffer(device, "Vertex Buffer: MeshIndexed", vertices, BufferUsages::VERTEX);
+        let index_buffer = GraphicsContext::create_buffer(device, "Index Buffer: MeshIndexed", indices, BufferUsages::INDEX);
 
         Self
         {
             vertex_buffer,
             index_buffer,
             index_count: count as u32,
-            render_pipeline : pipeline.clone(),
-            bind_groups : HashMap::new()
+            material : Material::new("Mesh", pipeline)
         }
     }
 
+    /// Replaces the vertex and index buffers, keeping the material and its bind groups
+    pub fn set_geometry<V, T, U>(&mut self, vertices : T, indices : U, device : &Device)
+        where   V : VertexLayout,
+                T : Into<Vec<V>>,
+                U : Into<Vec<u32>>
+    {
+        let indices = indices.into();
+        self.vertex_buffer = GraphicsContext::create_buffer(device, "Vertex Buffer: MeshIndexed", &vertices.into(), BufferUsages::VERTEX);
+        self.index_buffer = GraphicsContext::create_buffer(device, "Index Buffer: MeshIndexed", &indices, BufferUsages::INDEX);
+        self.index_count = indices.len() as u32;
+    }
+
+    /// Changes the mesh's material, so every mesh sharing it too
     pub fn set_bind_group(&mut self, loc : u32, bind_group : &SmartBindGroup)
     {
-        self.bind_groups.insert(loc, bind_group.clone());
+        self.material.set_bind_group(loc, bind_group);
     }
-}
 
-impl Meshable for Mesh
-{
-    fn draw<'life>(&'life self, render_pass : &mut RenderPass<'life>, cam : &'life ProspectCamera) {
-        self.render_pipeline.apply(render_pass);
-        
-        for bind_group in &self.bind_groups
-        {
-            bind_group.1.set_bind_group(render_pass, *bind_group.0, &[]);
-        }
+    /// Draws with `material` from now on, without touching the vertex or index buffers. Later changes to it reach this mesh too
+    pub fn set_material(&mut self, material : &Material)
+    {
+        self.material = material.clone();
+    }
+
+    pub fn get_material(&self) -> &Material
+    {
+        &self.material
+    }
+
+    pub fn get_material_mut(&mut self) -> &mut Material
+    {
+        &mut self.material
+    }
+
+    /// See `Material::trim`
+    pub fn trim(&mut self)
+    {
+        self.material.trim();
+    }
+
+    /// Draws using `material` instead of the mesh's own one
+    pub fn draw_with_material<'life>(&'life self, render_pass : &mut RenderPass<'life>, cam : &'life ProspectCamera, material : &'life Material)
+    {
+        material.apply(render_pass);
+        self.draw_buffers(render_pass, cam);
+    }
 
+    fn draw_buffers<'life>(&'life self, render_pass : &mut RenderPass<'life>, cam : &'life ProspectCamera)
+    {
         cam.bind(render_pass, 0);
         render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
         render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint32);  
         render_pass.draw_indexed(0..self.index_count, 0, 0..1); 
     }
+}
+
+impl Meshable for Mesh
+{
+    fn draw<'life>(&'life self, render_pass : &mut RenderPass<'life>, cam : &'life ProspectCamera) {
+        self.material.apply(render_pass);
+        self.draw_buffers(render_pass, cam);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,817 @@
+use std::{cmp::Ordering, collections::{BinaryHeap, HashMap, HashSet}};
+
+use crate::utils::math::{add, cross, dot, normalize, scale, sub};
+
+use super::vertex::Vertex;
+
+fn position_key(position : [f32; 3]) -> [u32; 3]
+{
+    // -0 and 0 are the same place
+    position.map(|value| (value + 0.).to_bits())
+}
+
+/// The unnormalised normal of a triangle wound the way `Mesh` draws them, its length is twice the area
+fn face_normal(a : [f32; 3], b : [f32; 3], c : [f32; 3]) -> [f32; 3]
+{
+    cross(sub(c, a), sub(b, a))
+}
+
+/// Triangles whose indices are all in range
+fn triangles(vertices : &[Vertex], indices : &[u32]) -> Vec<[u32; 3]>
+{
+    indices.chunks_exact(3)
+        .map(|triangle| [triangle[0], triangle[1], triangle[2]])
+        .filter(|triangle| triangle.iter().all(|index| (*index as usize) < vertices.len()))
+        .collect()
+}
+
+/// Keeps only the vertices the indices use, in the order they're first used
+fn compact(vertices : &[Vertex], indices : &[u32]) -> (Vec<Vertex>, Vec<u32>)
+{
+    let mut remap = vec![u32::MAX; vertices.len()];
+    let mut compacted = Vec::new();
+    let indices = indices.iter().map(|index|
+    {
+        let new = &mut remap[*index as usize];
+        if *new == u32::MAX
+        {
+            *new = compacted.len() as u32;
+            compacted.push(vertices[*index as usize]);
+        }
+        *new
+    }).collect();
+    (compacted, indices)
+}
+
+/// Gives every triangle its own normal, splitting vertices shared by faces that point different ways
+pub fn generate_flat_normals(vertices : &[Vertex], indices : &[u32]) -> (Vec<Vertex>, Vec<u32>)
+{
+    let mut lookup : HashMap<(u32, [u32; 3]), u32> = HashMap::new();
+    let mut output = Vec::new();
+    let mut output_indices = Vec::new();
+
+    for triangle in triangles(vertices, indices)
+    {
+        let [a, b, c] = triangle.map(|index| vertices[index as usize].position);
+        let normal = normalize(face_normal(a, b, c));
+
+        for index in triangle
+        {
+            let mut vertex = vertices[index as usize];
+            vertex.normal = normal.unwrap_or(vertex.normal);
+            let next = output.len() as u32;
+            let new = *lookup.entry((index, vertex.normal.map(f32::to_bits))).or_insert_with(||
+            {
+                output.push(vertex);
+                next
+            });
+            output_indices.push(new);
+        }
+    }
+
+    (output, output_indices)
+}
+
+/// Averages the normals of faces that meet at the same position, weighted by the angle they make there.
+///
+/// Faces more than `angle_threshold` degrees apart don't smooth into each other, so 180 smooths everything and a value around 30-60 keeps hard edges.
+/// Vertices are split where a position ends up with more than one normal. Degenerate faces keep the normals they had
+pub fn generate_smooth_normals(vertices : &[Vertex], indices : &[u32], angle_threshold : f32) -> (Vec<Vertex>, Vec<u32>)
+{
+    let triangles = triangles(vertices, indices);
+    let threshold = angle_threshold.to_radians().cos();
+
+    let mut face_normals = Vec::with_capacity(triangles.len());
+    let mut corner_angles = Vec::with_capacity(triangles.len());
+    let mut corners_at : HashMap<[u32; 3], Vec<(usize, usize)>> = HashMap::new();
+    for (face, triangle) in triangles.iter().enumerate()
+    {
+        let positions = triangle.map(|index| vertices[index as usize].position);
+        face_normals.push(normalize(face_normal(positions[0], positions[1], positions[2])));
+        corner_angles.push([0, 1, 2].map(|corner|
+        {
+            let position = positions[corner];
+            match (normalize(sub(positions[(corner + 1) % 3], position)), normalize(sub(positions[(corner + 2) % 3], position)))
+            {
+                (Some(a), Some(b)) => dot(a, b).clamp(-1., 1.).acos(),
+                _ => 0.,
+            }
+        }));
+
+        for (corner, index) in triangle.iter().enumerate()
+        {
+            corners_at.entry(position_key(vertices[*index as usize].position)).or_default().push((face, corner));
+        }
+    }
+
+    let mut lookup : HashMap<(u32, [u32; 3]), u32> = HashMap::new();
+    let mut output = Vec::new();
+    let mut output_indices = Vec::with_capacity(triangles.len() * 3);
+    for (face, triangle) in triangles.iter().enumerate()
+    {
+        for index in triangle
+        {
+            let mut vertex = vertices[*index as usize];
+            if let Some(normal) = face_normals[face]
+            {
+                let mut sum = [0.; 3];
+                for (other, corner) in &corners_at[&position_key(vertex.position)]
+                {
+                    if let Some(other_normal) = face_normals[*other]
+                    {
+                        // The face itself always passes, even with a threshold of 0
+                        if *other == face || dot(normal, other_normal) >= threshold
+                        {
+                            sum = add(sum, scale(other_normal, corner_angles[*other][*corner]));
+                        }
+                    }
+                }
+                vertex.normal = normalize(sum).unwrap_or(normal);
+            }
+
+            let next = output.len() as u32;
+            let new = *lookup.entry((*index, vertex.normal.map(f32::to_bits))).or_insert_with(||
+            {
+                output.push(vertex);
+                next
+            });
+            output_indices.push(new);
+        }
+    }
+
+    (output, output_indices)
+}
+
+/// Merges vertices whose position, uv and normal all round to the same multiple of `tolerance`, keeping the first of each.
+/// A `tolerance` of 0 only merges exact copies. Triangles that collapse are removed and unused vertices dropped
+pub fn weld_vertices(vertices : &[Vertex], indices : &[u32], tolerance : f32) -> (Vec<Vertex>, Vec<u32>)
+{
+    let quantise = |value : f32| -> u32
+    {
+        if tolerance > 0. {((value / tolerance).round() as i32) as u32} else {(value + 0.).to_bits()}
+    };
+
+    let mut lookup : HashMap<[u32; 8], u32> = HashMap::new();
+    let remap : Vec<u32> = vertices.iter().enumerate().map(|(index, vertex)|
+    {
+        let key = [
+            quantise(vertex.position[0]), quantise(vertex.position[1]), quantise(vertex.position[2]),
+            quantise(vertex.uv[0]), quantise(vertex.uv[1]),
+            quantise(vertex.normal[0]), quantise(vertex.normal[1]), quantise(vertex.normal[2]),
+        ];
+        *lookup.entry(key).or_insert(index as u32)
+    }).collect();
+
+    let welded : Vec<u32> = triangles(vertices, indices).into_iter()
+        .map(|triangle| triangle.map(|index| remap[index as usize]))
+        .filter(|[a, b, c]| a != b && b != c && a != c)
+        .flatten()
+        .collect();
+
+    compact(vertices, &welded)
+}
+
+const CACHE_SIZE : usize = 32;
+
+/// Tom Forsyth's vertex score, how much drawing a triangle using this vertex next would help the post transform cache
+fn cache_score(cache_position : Option<usize>, remaining : usize) -> f32
+{
+    if remaining == 0
+    {
+        return -1.;
+    }
+
+    let cache = match cache_position
+    {
+        None => 0.,
+        // The triangle just drawn, any order of its vertices is as good
+        Some(position) if position < 3 => 0.75,
+        Some(position) => (1. - (position - 3) as f32 / (CACHE_SIZE - 3) as f32).powf(1.5),
+    };
+    // Finishing off vertices with few triangles left avoids coming back to them later
+    cache + 2. / (remaining as f32).sqrt()
+}
+
+/// Reorders triangles so vertices are reused while they're still in the GPU's post transform cache, then reorders the vertices by
+/// first use so they're fetched in order. Unused vertices are dropped. The triangles drawn don't change
+pub fn optimise_vertex_cache(vertices : &[Vertex], indices : &[u32]) -> (Vec<Vertex>, Vec<u32>)
+{
+    let triangles = triangles(vertices, indices);
+
+    let mut vertex_triangles : Vec<Vec<usize>> = vec![Vec::new(); vertices.len()];
+    for (face, triangle) in triangles.iter().enumerate()
+    {
+        for index in triangle
+        {
+            vertex_triangles[*index as usize].push(face);
+        }
+    }
+
+    let mut vertex_scores : Vec<f32> = vertex_triangles.iter().map(|faces| cache_score(None, faces.len())).collect();
+    let mut triangle_scores : Vec<f32> = triangles.iter().map(|triangle| triangle.iter().map(|index| vertex_scores[*index as usize]).sum()).collect();
+    let mut drawn = vec![false; triangles.len()];
+    let mut cache : Vec<u32> = Vec::with_capacity(CACHE_SIZE + 3);
+    let mut ordered = Vec::with_capacity(triangles.len() * 3);
+    let mut next_undrawn = 0;
+    let mut best = triangle_scores.iter().enumerate().max_by(|a, b| a.1.total_cmp(b.1)).map(|(face, _)| face);
+
+    for _ in 0..triangles.len()
+    {
+        // Nothing in the cache has triangles left, start again from the first undrawn one
+        let face = best.unwrap_or_else(||
+        {
+            while drawn[next_undrawn]
+            {
+                next_undrawn += 1;
+            }
+            next_undrawn
+        });
+
+        drawn[face] = true;
+        ordered.extend_from_slice(&triangles[face]);
+        for index in triangles[face]
+        {
+            let faces = &mut vertex_triangles[index as usize];
+            if let Some(position) = faces.iter().position(|other| *other == face)
+            {
+                faces.swap_remove(position);
+            }
+        }
+
+        let mut updated : Vec<u32> = triangles[face].to_vec();
+        updated.extend(cache.iter().filter(|index| !triangles[face].contains(index)));
+        let evicted = if updated.len() > CACHE_SIZE {updated.split_off(CACHE_SIZE)} else {Vec::new()};
+        cache = updated;
+
+        for index in &evicted
+        {
+            vertex_scores[*index as usize] = cache_score(None, vertex_triangles[*index as usize].len());
+        }
+        for (position, index) in cache.iter().enumerate()
+        {
+            vertex_scores[*index as usize] = cache_score(Some(position), vertex_triangles[*index as usize].len());
+        }
+
+        best = None;
+        let mut best_score = f32::MIN;
+        for index in cache.iter().chain(&evicted)
+        {
+            for other in &vertex_triangles[*index as usize]
+            {
+                let score = triangles[*other].iter().map(|index| vertex_scores[*index as usize]).sum();
+                triangle_scores[*other] = score;
+                if score > best_score
+                {
+                    best_score = score;
+                    best = Some(*other);
+                }
+            }
+        }
+    }
+
+    compact(vertices, &ordered)
+}
+
+/// A symmetric 4x4 matrix measuring the squared distance to a set of planes, from Garland and Heckbert
+#[derive(Clone, Copy, Default)]
+struct Quadric([f64; 10]);
+
+impl Quadric
+{
+    fn from_plane(normal : [f32; 3], point : [f32; 3], weight : f32) -> Self
+    {
+        let [a, b, c] = normal.map(f64::from);
+        let d = -(a * point[0] as f64 + b * point[1] as f64 + c * point[2] as f64);
+        let w = weight as f64;
+        Self([a * a * w, a * b * w, a * c * w, a * d * w, b * b * w, b * c * w, b * d * w, c * c * w, c * d * w, d * d * w])
+    }
+
+    fn add(&self, other : &Self) -> Self
+    {
+        let mut sum = self.0;
+        for (value, other) in sum.iter_mut().zip(other.0)
+        {
+            *value += other;
+        }
+        Self(sum)
+    }
+
+    fn error(&self, point : [f32; 3]) -> f64
+    {
+        let [x, y, z] = point.map(f64::from);
+        let q = &self.0;
+        q[0] * x * x + 2. * q[1] * x * y + 2. * q[2] * x * z + 2. * q[3] * x
+            + q[4] * y * y + 2. * q[5] * y * z + 2. * q[6] * y
+            + q[7] * z * z + 2. * q[8] * z
+            + q[9]
+    }
+}
+
+/// Open edges, including uv and normal seams, are held in place this much more strongly than surfaces
+const BOUNDARY_WEIGHT : f32 = 10.;
+
+struct Collapse
+{
+    cost : f64,
+    from : usize,
+    to : usize,
+    stamps : (u32, u32),
+}
+
+impl PartialEq for Collapse
+{
+    fn eq(&self, other : &Self) -> bool
+    {
+        self.cost == other.cost
+    }
+}
+
+impl Eq for Collapse {}
+
+impl PartialOrd for Collapse
+{
+    fn partial_cmp(&self, other : &Self) -> Option<Ordering>
+    {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Collapse
+{
+    // Reversed so the `BinaryHeap` gives the cheapest first
+    fn cmp(&self, other : &Self) -> Ordering
+    {
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+struct Simplifier
+{
+    vertices : Vec<Vertex>,
+    triangles : Vec<[u32; 3]>,
+    alive : Vec<bool>,
+    /// Vertices at the same position share a group, groups are what get collapsed so seams move together
+    group_of : Vec<usize>,
+    positions : Vec<[f32; 3]>,
+    quadrics : Vec<Quadric>,
+    group_triangles : Vec<Vec<usize>>,
+    stamps : Vec<u32>,
+}
+
+impl Simplifier
+{
+    fn new(vertices : &[Vertex], indices : &[u32]) -> Self
+    {
+        let triangles = triangles(vertices, indices);
+
+        let mut lookup : HashMap<[u32; 3], usize> = HashMap::new();
+        let mut positions = Vec::new();
+        let group_of : Vec<usize> = vertices.iter().map(|vertex|
+        {
+            let next = positions.len();
+            *lookup.entry(position_key(vertex.position)).or_insert_with(||
+            {
+                positions.push(vertex.position);
+                next
+            })
+        }).collect();
+
+        let mut quadrics = vec![Quadric::default(); positions.len()];
+        let mut group_triangles = vec![Vec::new(); positions.len()];
+        let mut edge_counts : HashMap<(u32, u32), usize> = HashMap::new();
+        for (face, triangle) in triangles.iter().enumerate()
+        {
+            let [a, b, c] = triangle.map(|index| vertices[index as usize].position);
+            let normal = face_normal(a, b, c);
+            if let Some(unit) = normalize(normal)
+            {
+                let plane = Quadric::from_plane(unit, a, dot(normal, normal).sqrt() / 2.);
+                for index in triangle
+                {
+                    let group = group_of[*index as usize];
+                    quadrics[group] = quadrics[group].add(&plane);
+                }
+            }
+
+            for corner in 0..3
+            {
+                let group = group_of[triangle[corner] as usize];
+                if !group_triangles[group].contains(&face)
+                {
+                    group_triangles[group].push(face);
+                }
+
+                let (start, end) = (triangle[corner], triangle[(corner + 1) % 3]);
+                *edge_counts.entry((start.min(end), start.max(end))).or_default() += 1;
+            }
+        }
+
+        // A plane through each open edge, perpendicular to its face, stops borders and seams shrinking
+        for triangle in &triangles
+        {
+            let [a, b, c] = triangle.map(|index| vertices[index as usize].position);
+            let Some(normal) = normalize(face_normal(a, b, c)) else { continue; };
+            for corner in 0..3
+            {
+                let (start, end) = (triangle[corner], triangle[(corner + 1) % 3]);
+                if edge_counts[&(start.min(end), start.max(end))] != 1
+                {
+                    continue;
+                }
+
+                let (p0, p1) = (vertices[start as usize].position, vertices[end as usize].position);
+                let edge = sub(p1, p0);
+                let Some(perpendicular) = normalize(cross(edge, normal)) else { continue; };
+                let plane = Quadric::from_plane(perpendicular, p0, dot(edge, edge) * BOUNDARY_WEIGHT);
+                for index in [start, end]
+                {
+                    let group = group_of[index as usize];
+                    quadrics[group] = quadrics[group].add(&plane);
+                }
+            }
+        }
+
+        Self
+        {
+            vertices : vertices.to_vec(),
+            alive : vec![true; triangles.len()],
+            triangles,
+            stamps : vec![0; positions.len()],
+            group_of,
+            positions,
+            quadrics,
+            group_triangles,
+        }
+    }
+
+    /// Groups sharing a live triangle with `group`
+    fn neighbours(&self, group : usize) -> HashSet<usize>
+    {
+        self.group_triangles[group].iter()
+            .filter(|face| self.alive[**face])
+            .flat_map(|face| self.triangles[*face])
+            .map(|index| self.group_of[index as usize])
+            .filter(|other| *other != group)
+            .collect()
+    }
+
+    /// Which vertex of `to` each vertex of `from` becomes and what it costs, `None` if it would flip a triangle or tear a seam
+    fn plan(&self, from : usize, to : usize) -> Option<(f64, HashMap<u32, u32>)>
+    {
+        let faces : Vec<usize> = self.group_triangles[from].iter().copied().filter(|face| self.alive[*face]).collect();
+
+        // Each vertex follows the edge it's on, so vertices either side of a seam stay on their side
+        let mut targets : HashMap<u32, u32> = HashMap::new();
+        for face in &faces
+        {
+            let triangle = self.triangles[*face];
+            let Some(target) = triangle.iter().find(|index| self.group_of[**index as usize] == to) else { continue; };
+            for index in triangle.iter().filter(|index| self.group_of[**index as usize] == from)
+            {
+                if *targets.entry(*index).or_insert(*target) != *target
+                {
+                    return None;
+                }
+            }
+        }
+
+        let destination = self.positions[to];
+        for face in &faces
+        {
+            let triangle = self.triangles[*face];
+            if triangle.iter().any(|index| self.group_of[*index as usize] == to)
+            {
+                continue;
+            }
+
+            let before = triangle.map(|index| self.positions[self.group_of[index as usize]]);
+            let after = triangle.map(|index| if self.group_of[index as usize] == from {destination} else {self.positions[self.group_of[index as usize]]});
+            let before = normalize(face_normal(before[0], before[1], before[2]));
+            let after = normalize(face_normal(after[0], after[1], after[2]));
+            match (before, after)
+            {
+                (Some(before), Some(after)) if dot(before, after) > 0.2 => {}
+                (None, Some(_)) => {}
+                _ => return None,
+            }
+        }
+
+        Some((self.quadrics[from].add(&self.quadrics[to]).error(destination), targets))
+    }
+
+    fn push_collapses(&self, group : usize, heap : &mut BinaryHeap<Collapse>)
+    {
+        for neighbour in self.neighbours(group)
+        {
+            for (from, to) in [(group, neighbour), (neighbour, group)]
+            {
+                if let Some((cost, _)) = self.plan(from, to)
+                {
+                    heap.push(Collapse { cost, from, to, stamps : (self.stamps[from], self.stamps[to]) });
+                }
+            }
+        }
+    }
+
+    /// The vertex `index` becomes in `to`. Vertices with no edge to `to` keep their uv and normal in a copy at its position
+    fn moved_vertex(&mut self, index : u32, to : usize, targets : &mut HashMap<u32, u32>) -> u32
+    {
+        *targets.entry(index).or_insert_with(||
+        {
+            let mut vertex = self.vertices[index as usize];
+            vertex.position = self.positions[to];
+            self.vertices.push(vertex);
+            self.group_of.push(to);
+            (self.vertices.len() - 1) as u32
+        })
+    }
+
+    fn collapse(&mut self, from : usize, to : usize, mut targets : HashMap<u32, u32>) -> usize
+    {
+        let mut removed = 0;
+        for face in std::mem::take(&mut self.group_triangles[from])
+        {
+            if !self.alive[face]
+            {
+                continue;
+            }
+
+            if self.triangles[face].iter().any(|index| self.group_of[*index as usize] == to)
+            {
+                self.alive[face] = false;
+                removed += 1;
+                continue;
+            }
+
+            for corner in 0..3
+            {
+                let index = self.triangles[face][corner];
+                if self.group_of[index as usize] == from
+                {
+                    self.triangles[face][corner] = self.moved_vertex(index, to, &mut targets);
+                }
+            }
+            self.group_triangles[to].push(face);
+        }
+
+        self.quadrics[to] = self.quadrics[to].add(&self.quadrics[from]);
+        self.stamps[from] += 1;
+        self.stamps[to] += 1;
+        removed
+    }
+
+    fn run(mut self, target_index_count : usize) -> (Vec<Vertex>, Vec<u32>)
+    {
+        let mut heap = BinaryHeap::new();
+        for group in 0..self.positions.len()
+        {
+            for neighbour in self.neighbours(group)
+            {
+                if let Some((cost, _)) = self.plan(group, neighbour)
+                {
+                    heap.push(Collapse { cost, from : group, to : neighbour, stamps : (self.stamps[group], self.stamps[neighbour]) });
+                }
+            }
+        }
+
+        let mut triangle_count = self.triangles.len();
+        while triangle_count * 3 > target_index_count
+        {
+            let Some(collapse) = heap.pop() else { break; };
+            if collapse.stamps != (self.stamps[collapse.from], self.stamps[collapse.to])
+            {
+                continue;
+            }
+
+            // Neighbours may have moved since this was queued
+            let Some((cost, targets)) = self.plan(collapse.from, collapse.to) else { continue; };
+            if cost > collapse.cost * (1. + 1e-6) + 1e-12
+            {
+                heap.push(Collapse { cost, ..collapse });
+                continue;
+            }
+
+            triangle_count -= self.collapse(collapse.from, collapse.to, targets);
+            self.push_collapses(collapse.to, &mut heap);
+        }
+
+        let indices : Vec<u32> = self.triangles.iter().zip(&self.alive).filter(|(_, alive)| **alive).flat_map(|(triangle, _)| *triangle).collect();
+        compact(&self.vertices, &indices)
+    }
+}
+
+/// Quadric error simplification, collapsing the edges that change the shape least until there are at most `target_index_count` indices
+/// or nothing more can go without flipping a face.
+///
+/// Vertices at the same position collapse together so uv and normal seams don't crack, and open edges are kept where possible
+pub fn simplify(vertices : &[Vertex], indices : &[u32], target_index_count : usize) -> (Vec<Vertex>, Vec<u32>)
+{
+    Simplifier::new(vertices, indices).run(target_index_count)
+}
+
+/// Applies a column major matrix (see `matrix_from_trs`) to each mesh and joins them into one.
+/// Normals use the inverse transpose so non uniform scales keep them perpendicular, and mirroring matrices flip the winding back
+pub fn merge_meshes<'a, I>(parts : I) -> (Vec<Vertex>, Vec<u32>)
+    where   I : IntoIterator<Item = (&'a [Vertex], &'a [u32], [[f32; 4]; 4])>
+{
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+
+    for (part_vertices, part_indices, matrix) in parts
+    {
+        let column = |i : usize| [matrix[i][0], matrix[i][1], matrix[i][2]];
+        let (x, y, z) = (column(0), column(1), column(2));
+        let determinant = dot(x, cross(y, z));
+        // The cofactor matrix is the inverse transpose scaled by the determinant, its columns are these
+        let cofactors = [cross(y, z), cross(z, x), cross(x, y)].map(|column| scale(column, determinant.signum()));
+
+        let offset = vertices.len() as u32;
+        vertices.extend(part_vertices.iter().map(|vertex|
+        {
+            let [px, py, pz] = vertex.position;
+            let [nx, ny, nz] = vertex.normal;
+            let position = add(add(add(scale(x, px), scale(y, py)), scale(z, pz)), column(3));
+            let normal = add(add(scale(cofactors[0], nx), scale(cofactors[1], ny)), scale(cofactors[2], nz));
+            Vertex
+            {
+                position,
+                uv : vertex.uv,
+                normal : normalize(normal).unwrap_or(vertex.normal),
+            }
+        }));
+
+        for triangle in triangles(part_vertices, part_indices)
+        {
+            let [a, b, c] = triangle.map(|index| index + offset);
+            if determinant < 0.
+            {
+                indices.extend([a, c, b]);
+            } else
+            {
+                indices.extend([a, b, c]);
+            }
+        }
+    }
+
+    (vertices, indices)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    /// A cube from -1 to 1 with `divisions` quads along each edge of each face, faces don't share vertices
+    fn cube(divisions : u32) -> (Vec<Vertex>, Vec<u32>)
+    {
+        let axes : [[f32; 3]; 3] = [[1., 0., 0.], [0., 1., 0.], [0., 0., 1.]];
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        for axis in 0..3
+        {
+            for sign in [1., -1.]
+            {
+                let normal = scale(axes[axis], sign);
+                // Picked so u x v points along the normal
+                let (u, v) = if sign > 0. {(axes[(axis + 1) % 3], axes[(axis + 2) % 3])} else {(axes[(axis + 2) % 3], axes[(axis + 1) % 3])};
+
+                let start = vertices.len() as u32;
+                for s in 0..=divisions
+                {
+                    for t in 0..=divisions
+                    {
+                        let (fs, ft) = (s as f32 / divisions as f32, t as f32 / divisions as f32);
+                        vertices.push(Vertex
+                        {
+                            position : add(add(normal, scale(u, fs * 2. - 1.)), scale(v, ft * 2. - 1.)),
+                            uv : [fs, ft],
+                            normal,
+                        });
+                    }
+                }
+
+                let corner = |s : u32, t : u32| start + s * (divisions + 1) + t;
+                for s in 0..divisions
+                {
+                    for t in 0..divisions
+                    {
+                        indices.extend([corner(s, t), corner(s, t + 1), corner(s + 1, t + 1)]);
+                        indices.extend([corner(s, t), corner(s + 1, t + 1), corner(s + 1, t)]);
+                    }
+                }
+            }
+        }
+        (vertices, indices)
+    }
+
+    fn centroid(vertices : &[Vertex], triangle : &[u32]) -> [f32; 3]
+    {
+        let sum = triangle.iter().fold([0.; 3], |sum, index| add(sum, vertices[*index as usize].position));
+        scale(sum, 1. / 3.)
+    }
+
+    /// Every triangle as its vertices' bits, rotated to start at the smallest so winding is kept but the starting corner doesn't matter
+    fn triangle_set(vertices : &[Vertex], indices : &[u32]) -> Vec<[[u32; 8]; 3]>
+    {
+        let mut set : Vec<[[u32; 8]; 3]> = indices.chunks_exact(3).map(|triangle|
+        {
+            let bits = [0, 1, 2].map(|corner|
+            {
+                let vertex = vertices[triangle[corner] as usize];
+                [vertex.position[0], vertex.position[1], vertex.position[2], vertex.uv[0], vertex.uv[1], vertex.normal[0], vertex.normal[1], vertex.normal[2]].map(f32::to_bits)
+            });
+            let first = (0..3).min_by_key(|corner| bits[*corner]).unwrap();
+            [0, 1, 2].map(|corner| bits[(first + corner) % 3])
+        }).collect();
+        set.sort();
+        set
+    }
+
+    #[test]
+    fn test_cube_faces_outwards()
+    {
+        let (vertices, indices) = cube(2);
+        for triangle in indices.chunks_exact(3)
+        {
+            let [a, b, c] = [0, 1, 2].map(|corner| vertices[triangle[corner] as usize].position);
+            assert!(dot(face_normal(a, b, c), centroid(&vertices, triangle)) > 0.);
+        }
+    }
+
+    #[test]
+    fn test_weld_duplicated_cube()
+    {
+        let (vertices, indices) = cube(1);
+        assert_eq!(vertices.len(), 24);
+
+        // Every triangle with its own copies of its vertices
+        let duplicated : Vec<Vertex> = indices.iter().map(|index| vertices[*index as usize]).collect();
+        let sequential : Vec<u32> = (0..duplicated.len() as u32).collect();
+
+        let (welded, welded_indices) = weld_vertices(&duplicated, &sequential, 0.);
+        assert_eq!(welded.len(), 24);
+        assert_eq!(welded_indices.len(), 36);
+        assert_eq!(triangle_set(&welded, &welded_indices), triangle_set(&vertices, &indices));
+
+        // Nudges under the tolerance still weld, the normals keep the faces apart
+        let nudged : Vec<Vertex> = duplicated.iter().enumerate().map(|(index, vertex)|
+        {
+            let mut vertex = *vertex;
+            vertex.position[0] += if index % 2 == 0 {1e-5} else {-1e-5};
+            vertex
+        }).collect();
+        let (welded, welded_indices) = weld_vertices(&nudged, &sequential, 1e-3);
+        assert_eq!(welded.len(), 24);
+        assert_eq!(welded_indices.len(), 36);
+    }
+
+    #[test]
+    fn test_optimise_vertex_cache_keeps_triangles()
+    {
+        let (vertices, indices) = cube(6);
+        let (optimised, optimised_indices) = optimise_vertex_cache(&vertices, &indices);
+
+        assert_eq!(optimised.len(), vertices.len());
+        assert_eq!(triangle_set(&optimised, &optimised_indices), triangle_set(&vertices, &indices));
+    }
+
+    #[test]
+    fn test_simplify_reaches_target_without_flipping()
+    {
+        let (vertices, indices) = cube(4);
+        let target = 36;
+        let (simplified, simplified_indices) = simplify(&vertices, &indices, target);
+
+        assert!(!simplified_indices.is_empty());
+        assert!(simplified_indices.len() <= target, "{} indices left", simplified_indices.len());
+        for triangle in simplified_indices.chunks_exact(3)
+        {
+            let [a, b, c] = [0, 1, 2].map(|corner| simplified[triangle[corner] as usize].position);
+            assert!(dot(face_normal(a, b, c), centroid(&simplified, triangle)) > 0.);
+        }
+    }
+
+    #[test]
+    fn test_merge_meshes_flips_mirrored_winding()
+    {
+        let (vertices, indices) = cube(1);
+        let identity = [[1., 0., 0., 0.], [0., 1., 0., 0.], [0., 0., 1., 0.], [0., 0., 0., 1.]];
+        let mirror = [[-1., 0., 0., 0.], [0., 1., 0., 0.], [0., 0., 1., 0.], [0., 0., 0., 1.]];
+
+        let (merged, merged_indices) = merge_meshes([(vertices.as_slice(), indices.as_slice(), identity), (vertices.as_slice(), indices.as_slice(), mirror)]);
+        assert_eq!(merged.len(), 48);
+        assert_eq!(&merged_indices[..36], indices.as_slice());
+
+        let mirrored = &merged_indices[36..];
+        for (original, flipped) in indices.chunks_exact(3).zip(mirrored.chunks_exact(3))
+        {
+            assert_eq!([flipped[0], flipped[2], flipped[1]], [original[0] + 24, original[1] + 24, original[2] + 24]);
+
+            // Still facing out, and the normals agree
+            let [a, b, c] = [0, 1, 2].map(|corner| merged[flipped[corner] as usize].position);
+            let normal = face_normal(a, b, c);
+            assert!(dot(normal, centroid(&merged, flipped)) > 0.);
+            assert!(dot(normal, merged[flipped[0] as usize].normal) > 0.);
+        }
+    }
+}
@@ -0,0 +1,226 @@
+use std::{cell::RefCell, collections::HashMap};
+
+use image::RgbaImage;
+use wgpu::*;
+
+use super::graphics_context::GraphicsContext;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable, Default)]
+struct MipmapParams
+{
+    tile_size : [u32; 2],
+    padding : [u32; 2],
+}
+
+/// Describes a texture made of equally sized tiles, so mipmaps are built per tile instead of blending neighbours together.
+///
+/// `tile_size` includes `padding`, which is the gutter of extruded edge texels around every tile (see `AtlasLayout::pad_image`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AtlasLayout
+{
+    pub tile_size : (u32, u32),
+    pub padding : u32,
+}
+
+impl AtlasLayout
+{
+    pub fn new(tile_size : (u32, u32)) -> Self
+    {
+        Self
+        {
+            tile_size,
+            padding : 0,
+        }
+    }
+
+    /// Copies every `tile_size` tile of `image` into a new atlas with `padding` texels of its edges repeated around it,
+    /// which stops linear filtering and lower mip levels picking up the neighbouring tiles
+    pub fn pad_image(image : &RgbaImage, tile_size : (u32, u32), padding : u32) -> (RgbaImage, AtlasLayout)
+    {
+        let columns = image.width() / tile_size.0.max(1);
+        let rows = image.height() / tile_size.1.max(1);
+        let padded = (tile_size.0 + padding * 2, tile_size.1 + padding * 2);
+
+        let mut output = RgbaImage::new(columns * padded.0, rows * padded.1);
+        for row in 0..rows
+        {
+            for column in 0..columns
+            {
+                for y in 0..padded.1
+                {
+                    for x in 0..padded.0
+                    {
+                        let source_x = column * tile_size.0 + x.saturating_sub(padding).min(tile_size.0 - 1);
+                        let source_y = row * tile_size.1 + y.saturating_sub(padding).min(tile_size.1 - 1);
+                        output.put_pixel(column * padded.0 + x, row * padded.1 + y, *image.get_pixel(source_x, source_y));
+                    }
+                }
+            }
+        }
+
+        (output, AtlasLayout { tile_size : padded, padding })
+    }
+
+    /// The UV rectangle (min u, min v, max u, max v) of a tile without its padding
+    pub fn get_tile_uv(&self, atlas_size : (u32, u32), column : u32, row : u32) -> [f32; 4]
+    {
+        let width = atlas_size.0 as f32;
+        let height = atlas_size.1 as f32;
+        let x = (column * self.tile_size.0 + self.padding) as f32;
+        let y = (row * self.tile_size.1 + self.padding) as f32;
+        let inner = ((self.tile_size.0 - self.padding * 2) as f32, (self.tile_size.1 - self.padding * 2) as f32);
+
+        [x / width, y / height, (x + inner.0) / width, (y + inner.1) / height]
+    }
+}
+
+/// Number of levels in a full mip chain. Atlases stop at the last level their tiles still halve evenly at, so every tile
+/// keeps lining up with the texels of the smaller levels, which means power of two tiles get the most levels
+pub fn mip_level_count(width : u32, height : u32, atlas : Option<&AtlasLayout>) -> u32
+{
+    let levels = 32 - width.max(height).max(1).leading_zeros();
+
+    match atlas
+    {
+        Some(atlas) => levels.min(atlas.tile_size.0.trailing_zeros().min(atlas.tile_size.1.trailing_zeros()) + 1),
+        None => levels,
+    }
+}
+
+/// The blit shader and its layouts, with a pipeline for each format it has been used with. `ProspectWindow` keeps one for its device
+pub struct MipmapGenerator
+{
+    module : ShaderModule,
+    layout : BindGroupLayout,
+    pipeline_layout : PipelineLayout,
+    pipelines : RefCell<HashMap<TextureFormat, RenderPipeline>>,
+}
+
+impl MipmapGenerator
+{
+    pub fn new(device : &Device) -> Self
+    {
+        let module = GraphicsContext::load_shader("Mipmap Shader", include_str!("../shaders/mipmap.wgsl"), device);
+
+        let entries = vec![
+            GraphicsContext::create_bind_group_layout_entry(0, ShaderStages::FRAGMENT, GraphicsContext::create_texture_binding_type(false, TextureViewDimension::D2, TextureSampleType::Float { filterable: false })),
+            GraphicsContext::create_bind_group_layout_entry(1, ShaderStages::FRAGMENT, GraphicsContext::create_uniform_binding_type()),
+        ];
+        let layout = GraphicsContext::create_bind_group_layout(device, "Mipmap Bind Group Layout", &entries);
+        let pipeline_layout = GraphicsContext::create_pipeline_layout("Mipmap Pipeline Layout", device, &vec![&layout]);
+
+        Self { module, layout, pipeline_layout, pipelines : RefCell::new(HashMap::new()) }
+    }
+
+    fn create_pipeline(&self, device : &Device, format : TextureFormat) -> RenderPipeline
+    {
+        let targets = [Some(ColorTargetState {
+            format,
+            blend: Some(BlendState::REPLACE),
+            write_mask: ColorWrites::ALL,
+        })];
+
+        let fragment_state = FragmentState {
+            module: &self.module,
+            entry_point: "fs_main",
+            targets: &targets,
+        };
+
+        let vertex_state = VertexState {
+            module: &self.module,
+            entry_point: "vs_main",
+            buffers: &[],
+        };
+
+        let mut state = GraphicsContext::DEFAULT_PRIMITIVE_STATE;
+        state.cull_mode = None;
+
+        GraphicsContext::create_render_pipeline_without_depth("Mipmap Pipeline", &self.pipeline_layout, fragment_state, vertex_state, device, state)
+    }
+
+    /// Fills mip levels 1.. of every layer of a 2D, 2D array or cube `texture` by repeatedly downsampling level 0.
+    ///
+    /// The texture needs `RENDER_ATTACHMENT` and `TEXTURE_BINDING` usage and a renderable format, and `device` must be the one
+    /// this was made with. Atlases need a mip count from `mip_level_count` so their tiles divide evenly at every level
+    pub fn generate(&self, device : &Device, queue : &Queue, texture : &Texture, atlas : Option<&AtlasLayout>)
+    {
+        if texture.mip_level_count() <= 1 || texture.dimension() != TextureDimension::D2
+        {
+            return;
+        }
+
+        if let Some(atlas) = atlas
+        {
+            debug_assert!(texture.mip_level_count() <= mip_level_count(texture.width(), texture.height(), Some(atlas)), "Atlas tiles don't halve evenly at every mip level of {:?}", texture);
+        }
+
+        let mut pipelines = self.pipelines.borrow_mut();
+        let pipeline = pipelines.entry(texture.format()).or_insert_with(|| self.create_pipeline(device, texture.format()));
+
+        let level_view = |level : u32, layer : u32| texture.create_view(&TextureViewDescriptor {
+            label: Some("Mipmap Level"),
+            dimension: Some(TextureViewDimension::D2),
+            base_mip_level: level,
+            mip_level_count: Some(1),
+            base_array_layer: layer,
+            array_layer_count: Some(1),
+            ..Default::default()
+        });
+
+        let mut encoder = GraphicsContext::create_command_encoder(device, "Mipmap Encoder");
+
+        for level in 1..texture.mip_level_count()
+        {
+            // The tiles halve evenly at every level (see `mip_level_count`) so this matches how the level sizes round down
+            let shift = level - 1;
+            let params = match atlas
+            {
+                Some(atlas) => MipmapParams
+                {
+                    tile_size : [atlas.tile_size.0 >> shift, atlas.tile_size.1 >> shift],
+                    padding : [atlas.padding >> shift; 2],
+                },
+                None => MipmapParams::default(),
+            };
+            let buffer = GraphicsContext::create_buffer(device, "Mipmap Params", &[params], BufferUsages::UNIFORM);
+
+            // Each layer of an array or cubemap is downsampled on its own
+            for layer in 0..texture.depth_or_array_layers()
+            {
+                let source = level_view(level - 1, layer);
+                let destination = level_view(level, layer);
+
+                let entries = vec![
+                    GraphicsContext::create_texture_view_resource(0, &source),
+                    GraphicsContext::create_bind_group_entry(1, buffer.as_entire_binding()),
+                ];
+                let bind_group = GraphicsContext::create_bind_group(device, "Mipmap Bind Group", &self.layout, &entries);
+
+                let mut render_pass = GraphicsContext::begin_render_pass_colour_only(None, "Mipmap Pass", &destination, &mut encoder);
+                render_pass.set_pipeline(pipeline);
+                render_pass.set_bind_group(0, &bind_group, &[]);
+                render_pass.draw(0..3, 0..1);
+            }
+        }
+
+        queue.submit(std::iter::once(encoder.finish()));
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn atlas_mips_stop_once_tiles_stop_halving_evenly()
+    {
+        assert_eq!(mip_level_count(256, 256, None), 9);
+        assert_eq!(mip_level_count(256, 256, Some(&AtlasLayout::new((16, 16)))), 5);
+        // 24 -> 12 -> 6 -> 3, a fifth level would need 1.5 texel tiles
+        assert_eq!(mip_level_count(384, 384, Some(&AtlasLayout::new((24, 24)))), 4);
+        assert_eq!(mip_level_count(384, 384, Some(&AtlasLayout::new((24, 8)))), 4);
+        assert_eq!(mip_level_count(8, 8, Some(&AtlasLayout::new((64, 64)))), 4);
+    }
+}
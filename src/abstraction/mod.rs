@@ -3,4 +3,9 @@ pub mod prospect_window;
 pub mod high_level_abstraction;
 pub mod shader;
 pub mod vertex;
-pub mod mesh;
\ No newline at end of file
+pub mod mesh;
+pub mod tangents;
+pub mod mipmaps;
+pub mod shader_registry;
+pub mod mesh_processing;
+pub mod compute;
\ No newline at end of file
@@ -16,6 +16,7 @@ use winit::{
 };
 
 use super::graphics_context::GraphicsContext;
+use super::mipmaps::MipmapGenerator;
 use super::{
     high_level_abstraction::HighLevelGraphicsContext,
     shader::ProspectShader,
@@ -25,10 +26,12 @@ pub struct ProspectWindow {
     event_loop: Option<EventLoop<()>>,
     window: Window,
     surface: Surface,
+    adapter: Adapter,
     device: Device,
     queue: Queue,
     config: SurfaceConfiguration,
     depth_texture: (Texture, TextureView, Sampler),
+    mipmaps: MipmapGenerator,
     pub size: (u32, u32),
     delta : f64,
     last_frame : SystemTime,
@@ -40,20 +43,23 @@ pub struct ProspectWindow {
         width: u32,
         height: u32,
     ) -> Self {
-        let (event_loop, window, surface, device, queue, config) =
+        let (event_loop, window, surface, adapter, device, queue, config) =
             pollster::block_on(HighLevelGraphicsContext::init_window(title, width, height));
 
         let depth_texture = GraphicsContext::create_depth_texture(&device, &config, "Depth Texture");
+        let mipmaps = MipmapGenerator::new(&device);
 
         Self {
             event_loop: Some(event_loop),
             window,
             surface,
+            adapter,
             device,
             queue,
             config,
             size: (width, height),
             depth_texture,
+            mipmaps,
             delta : 0.,
             last_frame : SystemTime::now(),
         }
@@ -98,6 +104,11 @@ pub struct ProspectWindow {
         &self.surface
     }
 
+    /// For checking what the device can do, e.g `get_downlevel_capabilities`
+    pub fn get_adapter(&self) -> &Adapter {
+        &self.adapter
+    }
+
     pub fn get_device(&self) -> &Device {
         &self.device
     }
@@ -106,6 +117,17 @@ pub struct ProspectWindow {
         &self.queue
     }
 
+    /// Builds the mip chains of textures made with this window's device, see `MipmapGenerator::generate`
+    pub fn get_mipmap_generator(&self) -> &MipmapGenerator {
+        &self.mipmaps
+    }
+
+    /// Physical pixels per logical pixel, e.g 2 on a high DPI display
+    pub fn get_scale_factor(&self) -> f64
+    {
+        self.window.scale_factor()
+    }
+
     pub fn get_window(&mut self) -> &mut Window
     {
         &mut self.window
@@ -235,6 +257,30 @@ pub struct ProspectWindow {
                         *control_flow = flow;
                     }
                 }
+                WindowEvent::ReceivedCharacter(character) => {
+                    if let Some(flow) = self.process_input(
+                        ProspectEvent::ReceivedCharacter(*character),
+                        &mut app,
+                    ) {
+                        *control_flow = flow;
+                    }
+                }
+                WindowEvent::MouseWheel { delta, .. } => {
+                    if let Some(flow) = self.process_input(
+                        ProspectEvent::MouseWheel(*delta),
+                        &mut app,
+                    ) {
+                        *control_flow = flow;
+                    }
+                }
+                WindowEvent::ModifiersChanged(modifiers) => {
+                    if let Some(flow) = self.process_input(
+                        ProspectEvent::ModifiersChanged(*modifiers),
+                        &mut app,
+                    ) {
+                        *control_flow = flow;
+                    }
+                }
                 WindowEvent::Resized(size) => {
                     self.resize(size);
                 }
@@ -246,4 +292,4 @@ pub struct ProspectWindow {
             _ => {}
         })
     }
-}
\ No newline at end of file
+}
@@ -2,7 +2,7 @@ use wgpu::{
     BlendState, ColorTargetState, ColorWrites, Device, FragmentState, ShaderModule, VertexState, RenderPipeline, BindGroupLayout,
 };
 
-use super::{graphics_context::GraphicsContext, vertex::Vertex, high_level_abstraction::HighLevelGraphicsContext, prospect_window::ProspectWindow};
+use super::{graphics_context::GraphicsContext, vertex::{Vertex, VertexLayout}, high_level_abstraction::HighLevelGraphicsContext, prospect_window::ProspectWindow};
 
 pub trait ProspectShader : Sized {
     fn get_name(&self) -> &str;
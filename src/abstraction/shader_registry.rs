@@ -0,0 +1,152 @@
+use std::{collections::HashSet, path::{Path, PathBuf}};
+
+use wgpu::*;
+
+use crate::{smart::SmartRenderPipeline, utils::prospect_fs::FileWatcher};
+
+use super::{graphics_context::GraphicsContext, prospect_window::ProspectWindow};
+
+/// A WGSL file loaded by a `ShaderRegistry`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ShaderHandle(usize);
+
+type PipelineBuilder = Box<dyn Fn(&Device, &ShaderModule) -> RenderPipeline>;
+
+struct DependentPipeline
+{
+    pipeline : SmartRenderPipeline,
+    build : PipelineBuilder,
+}
+
+struct ShaderSource
+{
+    path : PathBuf,
+    module : ShaderModule,
+    pipelines : Vec<DependentPipeline>,
+}
+
+/// Loads WGSL from disk and recompiles it when the file changes, rebuilding every `SmartRenderPipeline` made from it in place.
+///
+/// Pipelines are created with `create_pipeline` from a closure that's called again on every reload, so it should own
+/// whatever it needs (e.g an `Rc` of the shader and its `PipelineLayout`). If the new source doesn't compile the error is
+/// logged and the previous version stays in use. Call `process_frame` once a frame to pick up changes
+pub struct ShaderRegistry
+{
+    sources : Vec<ShaderSource>,
+    watcher : FileWatcher,
+}
+
+impl ShaderRegistry
+{
+    /// Shaders still load if the file watcher can't be started, they just won't reload
+    pub fn new() -> Self
+    {
+        Self
+        {
+            sources : Vec::new(),
+            watcher : FileWatcher::new("shaders"),
+        }
+    }
+
+    /// Compiles `source` and runs `build` with the result, returning `None` and logging if either fails
+    fn compile<T, F : FnOnce(&ShaderModule) -> T>(path : &Path, source : &str, device : &Device, build : F) -> Option<(ShaderModule, T)>
+    {
+        device.push_error_scope(ErrorFilter::Validation);
+        let module = GraphicsContext::load_shader(&path.to_string_lossy(), source, device);
+        let built = build(&module);
+
+        match pollster::block_on(device.pop_error_scope())
+        {
+            Some(error) =>
+            {
+                log::error!("Failed to compile {}: {}", path.display(), error);
+                None
+            }
+            None => Some((module, built)),
+        }
+    }
+
+    /// Reads and compiles the WGSL at `path` then watches it for changes
+    pub fn load<P : AsRef<Path>>(&mut self, path : P, window : &ProspectWindow) -> Result<ShaderHandle, ()>
+    {
+        let path = path.as_ref().canonicalize().map_err(|error| log::error!("Unable to find shader {}: {}", path.as_ref().display(), error))?;
+        let source = std::fs::read_to_string(&path).map_err(|error| log::error!("Unable to read shader {}: {}", path.display(), error))?;
+        let (module, _) = Self::compile(&path, &source, window.get_device(), |_| ()).ok_or(())?;
+
+        self.watcher.watch(&path);
+        self.sources.push(ShaderSource { path, module, pipelines : Vec::new() });
+        Ok(ShaderHandle(self.sources.len() - 1))
+    }
+
+    /// The latest version that compiled
+    pub fn get_module(&self, handle : ShaderHandle) -> &ShaderModule
+    {
+        &self.sources[handle.0].module
+    }
+
+    pub fn get_path(&self, handle : ShaderHandle) -> &Path
+    {
+        &self.sources[handle.0].path
+    }
+
+    /// Builds a pipeline with `build` now and again every time the shader reloads
+    pub fn create_pipeline<F>(&mut self, handle : ShaderHandle, window : &ProspectWindow, build : F) -> SmartRenderPipeline
+    where
+        F : Fn(&Device, &ShaderModule) -> RenderPipeline + 'static
+    {
+        let source = &mut self.sources[handle.0];
+        let pipeline : SmartRenderPipeline = build(window.get_device(), &source.module).into();
+        source.pipelines.push(DependentPipeline { pipeline : pipeline.clone(), build : Box::new(build) });
+        pipeline
+    }
+
+    /// Recompiles the shaders whose files changed since the last call, returning the ones that reloaded
+    pub fn process_frame(&mut self, window : &ProspectWindow) -> Vec<ShaderHandle>
+    {
+        let changed : HashSet<usize> = self.watcher.changed_paths().iter()
+            .filter_map(|path| self.sources.iter().position(|source| &source.path == path))
+            .collect();
+
+        let mut reloaded = Vec::new();
+        for index in changed
+        {
+            if self.reload(index, window.get_device())
+            {
+                reloaded.push(ShaderHandle(index));
+            }
+        }
+        reloaded
+    }
+
+    fn reload(&mut self, index : usize, device : &Device) -> bool
+    {
+        let source = &mut self.sources[index];
+        let text = match std::fs::read_to_string(&source.path)
+        {
+            Ok(text) => text,
+            Err(error) =>
+            {
+                log::error!("Unable to read shader {}: {}", source.path.display(), error);
+                return false;
+            }
+        };
+
+        // Every pipeline has to build before any are swapped, so they never end up on different versions
+        let pipelines = &source.pipelines;
+        let compiled = Self::compile(&source.path, &text, device, |module| pipelines.iter().map(|dependent| (dependent.build)(device, module)).collect::<Vec<_>>());
+        let (module, built) = match compiled
+        {
+            Some(compiled) => compiled,
+            None => return false,
+        };
+
+        for (dependent, pipeline) in source.pipelines.iter_mut().zip(built)
+        {
+            dependent.pipeline.replace(pipeline);
+        }
+        source.module = module;
+
+        log::info!("Reloaded {}", source.path.display());
+        true
+    }
+}
@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+
+use bevy_mikktspace::Geometry;
+
+use super::vertex::{Vertex, TangentVertex};
+
+/// A triangle list as `bevy_mikktspace` sees it, collecting a tangent for every corner
+struct Corners<'a>
+{
+    vertices : &'a [Vertex],
+    triangles : Vec<[usize; 3]>,
+    tangents : Vec<[f32; 4]>,
+}
+
+impl<'a> Corners<'a>
+{
+    fn vertex(&self, face : usize, vert : usize) -> &Vertex
+    {
+        &self.vertices[self.triangles[face][vert]]
+    }
+}
+
+impl<'a> Geometry for Corners<'a>
+{
+    fn num_faces(&self) -> usize
+    {
+        self.triangles.len()
+    }
+
+    fn num_vertices_of_face(&self, _face : usize) -> usize
+    {
+        3
+    }
+
+    fn position(&self, face : usize, vert : usize) -> [f32; 3]
+    {
+        self.vertex(face, vert).position
+    }
+
+    fn normal(&self, face : usize, vert : usize) -> [f32; 3]
+    {
+        self.vertex(face, vert).normal
+    }
+
+    fn tex_coord(&self, face : usize, vert : usize) -> [f32; 2]
+    {
+        self.vertex(face, vert).uv
+    }
+
+    fn set_tangent_encoded(&mut self, tangent : [f32; 4], face : usize, vert : usize)
+    {
+        self.tangents[face * 3 + vert] = tangent;
+    }
+}
+
+/// Generates MikkTSpace tangents for a triangle list, so normal maps baked in Blender, Substance or xNormal line up.
+/// Handedness goes in `w` and the bitangent is rebuilt as `cross(normal, tangent) * w`.
+///
+/// MikkTSpace gives each corner its own tangent, so vertices are split where the faces sharing them disagree and welded back
+/// together where they don't, which is why new indices are returned alongside the vertices.
+/// Triangles with out of range indices are dropped. Normals should already be normalised
+pub fn generate_tangents(vertices : &[Vertex], indices : &[u32]) -> (Vec<TangentVertex>, Vec<u32>)
+{
+    let triangles : Vec<[usize; 3]> = indices.chunks_exact(3)
+        .map(|triangle| [triangle[0] as usize, triangle[1] as usize, triangle[2] as usize])
+        .filter(|corners| corners.iter().all(|i| *i < vertices.len()))
+        .collect();
+
+    let mut corners = Corners { vertices, tangents : vec![[1., 0., 0., 1.]; triangles.len() * 3], triangles };
+    if !bevy_mikktspace::generate_tangents(&mut corners)
+    {
+        log::warn!("Unable to generate tangents for a mesh with {} triangles", corners.triangles.len());
+    }
+
+    let mut welded : HashMap<[u32; 12], u32> = HashMap::new();
+    let mut tangent_vertices = Vec::new();
+    let mut tangent_indices = Vec::with_capacity(corners.tangents.len());
+    for (corner, index) in corners.triangles.iter().flatten().enumerate()
+    {
+        let vertex = &vertices[*index];
+        let tangent = corners.tangents[corner];
+        let key = [
+            vertex.position[0].to_bits(), vertex.position[1].to_bits(), vertex.position[2].to_bits(),
+            vertex.uv[0].to_bits(), vertex.uv[1].to_bits(),
+            vertex.normal[0].to_bits(), vertex.normal[1].to_bits(), vertex.normal[2].to_bits(),
+            tangent[0].to_bits(), tangent[1].to_bits(), tangent[2].to_bits(), tangent[3].to_bits(),
+        ];
+        let index = *welded.entry(key).or_insert_with(||
+        {
+            tangent_vertices.push(TangentVertex { position : vertex.position, uv : vertex.uv, normal : vertex.normal, tangent });
+            tangent_vertices.len() as u32 - 1
+        });
+        tangent_indices.push(index);
+    }
+
+    (tangent_vertices, tangent_indices)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    /// The +x side of the cube from MikkTSpace's reference tests, four triangles around a centre vertex
+    fn reference_side() -> (Vec<Vertex>, Vec<u32>)
+    {
+        let points : [([f32; 2], [f32; 3]); 5] = [
+            ([0., 0.], [1., -1., 1.]),
+            ([0., 1.], [1., -1., -1.]),
+            ([1., 1.], [1., 1., -1.]),
+            ([1., 0.], [1., 1., 1.]),
+            ([0.5, 0.5], [1., 0., 0.]),
+        ];
+        let vertices = points.iter().map(|(uv, direction)|
+        {
+            let length = (direction[0] * direction[0] + direction[1] * direction[1] + direction[2] * direction[2]).sqrt();
+            Vertex
+            {
+                position : direction.map(|x| x / 2.),
+                uv : *uv,
+                normal : direction.map(|x| x / length),
+            }
+        }).collect();
+        (vertices, vec![0, 1, 4, 1, 2, 4, 2, 3, 4, 3, 0, 4])
+    }
+
+    fn assert_close(a : [f32; 4], b : [f32; 4])
+    {
+        assert!(a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-5), "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn matches_mikktspace_reference_values()
+    {
+        let (vertices, indices) = reference_side();
+        let (tangent_vertices, tangent_indices) = generate_tangents(&vertices, &indices);
+        let tangent = |corner : usize| tangent_vertices[tangent_indices[corner] as usize].tangent;
+
+        assert_eq!(tangent_indices.len(), indices.len());
+        assert_close(tangent(0), [0.40824825, 0.81649655, 0.40824825, -1.]);
+        assert_close(tangent(1), [0.40824825, 0.81649655, -0.40824825, -1.]);
+        assert_close(tangent(2), [0., 1., 0., -1.]);
+        assert_close(tangent(4), [-0.40824825, 0.81649655, 0.40824825, -1.]);
+        assert_close(tangent(7), [-0.40824825, 0.81649655, -0.40824825, -1.]);
+        // The corners that share a vertex agree, so they're welded back together
+        assert_eq!(tangent_indices[1], tangent_indices[3]);
+        assert_eq!(tangent_vertices.len(), vertices.len());
+    }
+
+    #[test]
+    fn drops_out_of_range_triangles()
+    {
+        let (vertices, mut indices) = reference_side();
+        indices.extend([0, 1, 99]);
+        let (_, tangent_indices) = generate_tangents(&vertices, &indices);
+        assert_eq!(tangent_indices.len(), 12);
+    }
+}
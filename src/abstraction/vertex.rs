@@ -1,7 +1,17 @@
 use bytemuck::{Pod, Zeroable};
-use wgpu::{VertexBufferLayout, VertexStepMode, VertexAttribute, VertexFormat, BufferAddress};
+use wgpu::VertexBufferLayout;
 
-#[derive(Clone, Copy, Pod, Zeroable)]
+pub use prospect_derive::VertexLayout;
+
+/// How a vertex struct is laid out in a vertex buffer, anything implementing this can be used with `Mesh`.
+///
+/// Usually derived, see `prospect_derive::VertexLayout`
+pub trait VertexLayout : Pod
+{
+    const VERTEX_BUFFER_LAYOUT : VertexBufferLayout<'static>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable, VertexLayout)]
 #[repr(C)]
 pub struct Vertex
 {
@@ -10,29 +20,113 @@ pub struct Vertex
     pub normal : [f32; 3]
 }
 
-impl Vertex
+/// A `Vertex` with a tangent, `w` holds the handedness (1 or -1) so the bitangent is `cross(normal, tangent.xyz) * tangent.w`
+///
+/// Use `generate_tangents` to build these from an ordinary mesh
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable, VertexLayout)]
+#[repr(C)]
+pub struct TangentVertex
+{
+    pub position : [f32; 3],
+    pub uv : [f32; 2],
+    pub normal : [f32; 3],
+    pub tangent : [f32; 4]
+}
+
+impl From<Vertex> for TangentVertex
+{
+    fn from(value : Vertex) -> Self
+    {
+        Self
+        {
+            position : value.position,
+            uv : value.uv,
+            normal : value.normal,
+            tangent : [1., 0., 0., 1.]
+        }
+    }
+}
+
+/// A `Vertex` moved by up to 4 joints of a `Skeleton`, `weights` should add up to 1. Draw with `Default3D::new_skinned`
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable, VertexLayout)]
+#[repr(C)]
+pub struct SkinnedVertex
+{
+    pub position : [f32; 3],
+    pub uv : [f32; 2],
+    pub normal : [f32; 3],
+    /// Indices into `Skeleton::joints`
+    pub joints : [u32; 4],
+    pub weights : [f32; 4]
+}
+
+impl From<Vertex> for SkinnedVertex
+{
+    /// Follows the first joint only
+    fn from(value : Vertex) -> Self
+    {
+        Self
+        {
+            position : value.position,
+            uv : value.uv,
+            normal : value.normal,
+            joints : [0; 4],
+            weights : [1., 0., 0., 0.]
+        }
+    }
+}
+
+/// For meshes coloured per vertex instead of textured, such as CAD exports and point clouds
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable, VertexLayout)]
+#[repr(C)]
+pub struct ColourVertex
+{
+    pub position : [f32; 3],
+    #[vertex(format = "Unorm8x4")]
+    pub colour : [u8; 4],
+    pub normal : [f32; 3]
+}
+
+/// A `Vertex` with its normal squeezed into 4 bytes, 24 bytes instead of 32
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable, VertexLayout)]
+#[repr(C)]
+pub struct PackedNormalVertex
+{
+    pub position : [f32; 3],
+    pub uv : [f32; 2],
+    /// xyz in -127..=127, the last byte is unused, see `pack_normal`
+    #[vertex(format = "Snorm8x4")]
+    pub normal : [i8; 4]
+}
+
+impl PackedNormalVertex
+{
+    pub fn pack_normal(normal : [f32; 3]) -> [i8; 4]
+    {
+        let pack = |n : f32| (n.clamp(-1., 1.) * 127.).round() as i8;
+        [pack(normal[0]), pack(normal[1]), pack(normal[2]), 0]
+    }
+}
+
+impl From<Vertex> for PackedNormalVertex
+{
+    fn from(value : Vertex) -> Self
+    {
+        Self
+        {
+            position : value.position,
+            uv : value.uv,
+            normal : Self::pack_normal(value.normal)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable, VertexLayout)]
+#[repr(C)]
+pub struct Vertex2D
 {
-    pub const VERTEX_BUFFER_LAYOUT : VertexBufferLayout<'static> =         VertexBufferLayout {
-        array_stride: std::mem::size_of::<Vertex>() as BufferAddress,
-        step_mode: VertexStepMode::Vertex,
-        attributes: &[
-            VertexAttribute {
-                format: VertexFormat::Float32x3,
-                offset: 0,
-                shader_location: 0,
-            },
-            VertexAttribute {
-                format: VertexFormat::Float32x2,
-                offset: std::mem::size_of::<[f32; 3]>() as BufferAddress,
-                shader_location: 1,
-            },
-            VertexAttribute {
-                format: VertexFormat::Float32x3,
-                offset: std::mem::size_of::<[f32; 5]>() as BufferAddress,
-                shader_location: 2,
-            },
-        ],
-    };
+    pub position : [f32; 2],
+    pub uv : [f32; 2]
 }
 
 pub fn vertpos(x : f32, y : f32, z : f32) -> Vertex
@@ -0,0 +1,209 @@
+use super::{skeleton::{lerp, normalize_quaternion, slerp}, Pose, Skeleton};
+
+/// How values between two keyframes are found, matching glTF's samplers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interpolation
+{
+    /// Holds each key's value until the next
+    Step,
+    /// Straight lines, and slerp for rotations
+    Linear,
+    /// Hermite curves, each key has an in tangent, value and out tangent
+    CubicSpline,
+}
+
+/// The keyframe values of a channel, one per time or three (in tangent, value, out tangent) with `CubicSpline`
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChannelValues
+{
+    Translation(Vec<[f32; 3]>),
+    /// `[x, y, z, w]` quaternions
+    Rotation(Vec<[f32; 4]>),
+    Scale(Vec<[f32; 3]>),
+}
+
+impl ChannelValues
+{
+    fn len(&self) -> usize
+    {
+        match self
+        {
+            ChannelValues::Translation(values) | ChannelValues::Scale(values) => values.len(),
+            ChannelValues::Rotation(values) => values.len(),
+        }
+    }
+}
+
+/// Keyframes for one property of one joint
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationChannel
+{
+    /// An index into `Skeleton::joints`
+    pub joint : usize,
+    pub interpolation : Interpolation,
+    /// In seconds, ascending
+    pub times : Vec<f32>,
+    pub values : ChannelValues,
+}
+
+/// Where `time` falls among a channel's keys
+enum Segment
+{
+    /// Before the first key, after the last, or exactly on one
+    Key(usize),
+    Between { previous : usize, next : usize, t : f32, duration : f32 },
+}
+
+fn find_segment(times : &[f32], time : f32) -> Segment
+{
+    let next = times.partition_point(|key_time| *key_time <= time);
+    if next == 0
+    {
+        return Segment::Key(0);
+    }
+    if next == times.len()
+    {
+        return Segment::Key(times.len() - 1);
+    }
+
+    let previous = next - 1;
+    let duration = times[next] - times[previous];
+    if duration <= 0.
+    {
+        return Segment::Key(next);
+    }
+    Segment::Between { previous, next, t : (time - times[previous]) / duration, duration }
+}
+
+/// The key's value, skipping the tangents around it with `CubicSpline`
+fn key_value<const N : usize>(interpolation : Interpolation, values : &[[f32; N]], key : usize) -> [f32; N]
+{
+    if interpolation == Interpolation::CubicSpline {values[key * 3 + 1]} else {values[key]}
+}
+
+fn sample_values<const N : usize>(interpolation : Interpolation, times : &[f32], values : &[[f32; N]], time : f32) -> [f32; N]
+{
+    let (previous, next, t, duration) = match find_segment(times, time)
+    {
+        Segment::Key(key) => return key_value(interpolation, values, key),
+        Segment::Between { previous, next, t, duration } => (previous, next, t, duration),
+    };
+
+    match interpolation
+    {
+        Interpolation::Step => values[previous],
+        Interpolation::Linear => lerp(values[previous], values[next], t),
+        Interpolation::CubicSpline =>
+        {
+            let (t2, t3) = (t * t, t * t * t);
+            let (p0, m0) = (values[previous * 3 + 1], values[previous * 3 + 2]);
+            let (m1, p1) = (values[next * 3], values[next * 3 + 1]);
+            let mut result = [0.; N];
+            for (i, value) in result.iter_mut().enumerate()
+            {
+                // Tangents are per second, so they're scaled to the gap between keys
+                *value = (2. * t3 - 3. * t2 + 1.) * p0[i]
+                    + (t3 - 2. * t2 + t) * m0[i] * duration
+                    + (-2. * t3 + 3. * t2) * p1[i]
+                    + (t3 - t2) * m1[i] * duration;
+            }
+            result
+        }
+    }
+}
+
+fn sample_rotation(interpolation : Interpolation, times : &[f32], values : &[[f32; 4]], time : f32) -> [f32; 4]
+{
+    match (interpolation, find_segment(times, time))
+    {
+        (Interpolation::Linear, Segment::Between { previous, next, t, .. }) => slerp(values[previous], values[next], t),
+        _ => normalize_quaternion(sample_values(interpolation, times, values, time)),
+    }
+}
+
+impl AnimationChannel
+{
+    /// Writes this channel's value at `time` into its joint of `pose`, if the pose has it
+    pub fn apply(&self, time : f32, pose : &mut Pose)
+    {
+        let Some(joint) = pose.joints.get_mut(self.joint) else { return; };
+        match &self.values
+        {
+            ChannelValues::Translation(values) => joint.translation = sample_values(self.interpolation, &self.times, values, time),
+            ChannelValues::Rotation(values) => joint.rotation = sample_rotation(self.interpolation, &self.times, values, time),
+            ChannelValues::Scale(values) => joint.scale = sample_values(self.interpolation, &self.times, values, time),
+        }
+    }
+}
+
+/// A named set of channels animating a `Skeleton`, such as a walk cycle
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationClip
+{
+    pub name : String,
+    channels : Vec<AnimationChannel>,
+    duration : f32,
+}
+
+impl AnimationClip
+{
+    /// Fails if a channel has no keys, times that go backwards, or the wrong number of values for its interpolation
+    pub fn new(name : &str, channels : Vec<AnimationChannel>) -> Result<Self, ()>
+    {
+        for (index, channel) in channels.iter().enumerate()
+        {
+            let stride = if channel.interpolation == Interpolation::CubicSpline {3} else {1};
+            if channel.times.is_empty()
+            {
+                log::error!("Channel {} of animation \"{}\" has no keyframes", index, name);
+                return Err(());
+            }
+            if channel.times.windows(2).any(|pair| pair[1] < pair[0])
+            {
+                log::error!("Channel {} of animation \"{}\" has keyframe times that go backwards", index, name);
+                return Err(());
+            }
+            if channel.values.len() != channel.times.len() * stride
+            {
+                log::error!("Channel {} of animation \"{}\" has {} values for {} keyframes, expected {}", index, name, channel.values.len(), channel.times.len(), channel.times.len() * stride);
+                return Err(());
+            }
+        }
+
+        let duration = channels.iter().filter_map(|channel| channel.times.last().copied()).fold(0., f32::max);
+        Ok(Self
+        {
+            name : name.to_string(),
+            channels,
+            duration,
+        })
+    }
+
+    pub fn channels(&self) -> &[AnimationChannel]
+    {
+        &self.channels
+    }
+
+    /// The time of the last keyframe, in seconds
+    pub fn duration(&self) -> f32
+    {
+        self.duration
+    }
+
+    /// Writes every channel's value at `time` into `pose`, joints this clip doesn't animate are left alone
+    pub fn sample_into(&self, time : f32, pose : &mut Pose)
+    {
+        for channel in &self.channels
+        {
+            channel.apply(time, pose);
+        }
+    }
+
+    /// The skeleton's rest pose with this clip applied at `time`
+    pub fn sample(&self, skeleton : &Skeleton, time : f32) -> Pose
+    {
+        let mut pose = skeleton.rest_pose();
+        self.sample_into(time, &mut pose);
+        pose
+    }
+}
@@ -0,0 +1,158 @@
+use std::f32::consts::PI;
+
+/// Shapes how a tween moves between two keyframes, `In` curves start slow, `Out` curves end slow and `InOut` do both
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Easing
+{
+    #[default]
+    Linear,
+    /// Jumps to the next value at the end
+    Step,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    SineIn,
+    SineOut,
+    SineInOut,
+    ExpoIn,
+    ExpoOut,
+    ExpoInOut,
+    /// Pulls back a little before moving
+    BackIn,
+    /// Overshoots a little then settles
+    BackOut,
+    BackInOut,
+    /// Springs past the end a few times
+    ElasticOut,
+    /// Bounces on the end like a dropped ball
+    BounceOut,
+    /// A CSS style `cubic-bezier(x1, y1, x2, y2)`
+    CubicBezier(f32, f32, f32, f32),
+}
+
+fn bounce_out(t : f32) -> f32
+{
+    const N : f32 = 7.5625;
+    const D : f32 = 2.75;
+    if t < 1. / D
+    {
+        N * t * t
+    } else if t < 2. / D
+    {
+        let t = t - 1.5 / D;
+        N * t * t + 0.75
+    } else if t < 2.5 / D
+    {
+        let t = t - 2.25 / D;
+        N * t * t + 0.9375
+    } else
+    {
+        let t = t - 2.625 / D;
+        N * t * t + 0.984375
+    }
+}
+
+/// Solves the curve's x for `t` with Newton's method, falling back to bisection where the slope is too flat
+fn cubic_bezier(x1 : f32, y1 : f32, x2 : f32, y2 : f32, t : f32) -> f32
+{
+    let curve = |a : f32, b : f32, s : f32| 3. * a * s * (1. - s) * (1. - s) + 3. * b * s * s * (1. - s) + s * s * s;
+    let slope = |a : f32, b : f32, s : f32| 3. * a * (1. - s) * (1. - s) + 6. * (b - a) * s * (1. - s) + 3. * (1. - b) * s * s;
+
+    let mut s = t;
+    for _ in 0..8
+    {
+        let error = curve(x1, x2, s) - t;
+        let gradient = slope(x1, x2, s);
+        if error.abs() < 1e-6 || gradient.abs() < 1e-6
+        {
+            break;
+        }
+        s = (s - error / gradient).clamp(0., 1.);
+    }
+
+    if (curve(x1, x2, s) - t).abs() > 1e-4
+    {
+        let (mut low, mut high) = (0., 1.);
+        for _ in 0..32
+        {
+            s = (low + high) / 2.;
+            if curve(x1, x2, s) < t {low = s} else {high = s}
+        }
+    }
+
+    curve(y1, y2, s)
+}
+
+impl Easing
+{
+    /// Maps progress `t` from 0 to 1 onto the curve, which also starts at 0 and ends at 1 but may go outside them in between
+    pub fn apply(&self, t : f32) -> f32
+    {
+        let t = t.clamp(0., 1.);
+        match *self
+        {
+            Easing::Linear => t,
+            Easing::Step => if t < 1. {0.} else {1.},
+            Easing::QuadIn => t * t,
+            Easing::QuadOut => 1. - (1. - t) * (1. - t),
+            Easing::QuadInOut => if t < 0.5 {2. * t * t} else {1. - (-2. * t + 2.).powi(2) / 2.},
+            Easing::CubicIn => t * t * t,
+            Easing::CubicOut => 1. - (1. - t).powi(3),
+            Easing::CubicInOut => if t < 0.5 {4. * t * t * t} else {1. - (-2. * t + 2.).powi(3) / 2.},
+            Easing::SineIn => 1. - (t * PI / 2.).cos(),
+            Easing::SineOut => (t * PI / 2.).sin(),
+            Easing::SineInOut => -((PI * t).cos() - 1.) / 2.,
+            Easing::ExpoIn => if t == 0. {0.} else {2f32.powf(10. * t - 10.)},
+            Easing::ExpoOut => if t == 1. {1.} else {1. - 2f32.powf(-10. * t)},
+            Easing::ExpoInOut =>
+            {
+                if t == 0. || t == 1.
+                {
+                    t
+                } else if t < 0.5
+                {
+                    2f32.powf(20. * t - 10.) / 2.
+                } else
+                {
+                    (2. - 2f32.powf(-20. * t + 10.)) / 2.
+                }
+            }
+            Easing::BackIn =>
+            {
+                const C1 : f32 = 1.70158;
+                (C1 + 1.) * t * t * t - C1 * t * t
+            }
+            Easing::BackOut =>
+            {
+                const C1 : f32 = 1.70158;
+                1. + (C1 + 1.) * (t - 1.).powi(3) + C1 * (t - 1.).powi(2)
+            }
+            Easing::BackInOut =>
+            {
+                const C2 : f32 = 1.70158 * 1.525;
+                if t < 0.5
+                {
+                    (2. * t).powi(2) * ((C2 + 1.) * 2. * t - C2) / 2.
+                } else
+                {
+                    ((2. * t - 2.).powi(2) * ((C2 + 1.) * (t * 2. - 2.) + C2) + 2.) / 2.
+                }
+            }
+            Easing::ElasticOut =>
+            {
+                if t == 0. || t == 1.
+                {
+                    t
+                } else
+                {
+                    2f32.powf(-10. * t) * ((t * 10. - 0.75) * (2. * PI / 3.)).sin() + 1.
+                }
+            }
+            Easing::BounceOut => bounce_out(t),
+            Easing::CubicBezier(x1, y1, x2, y2) => cubic_bezier(x1.clamp(0., 1.), y1, x2.clamp(0., 1.), y2, t),
+        }
+    }
+}
@@ -0,0 +1,15 @@
+mod skeleton;
+mod clip;
+mod player;
+mod skinned_model;
+mod easing;
+mod tween;
+mod targets;
+
+pub use skeleton::{Joint, JointTransform, Pose, Skeleton};
+pub use clip::*;
+pub use player::*;
+pub use skinned_model::*;
+pub use easing::*;
+pub use tween::*;
+pub use targets::*;
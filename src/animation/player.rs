@@ -0,0 +1,190 @@
+use std::rc::Rc;
+
+use super::{AnimationClip, Pose, Skeleton};
+
+/// Identifies a track in an `AnimationPlayer`, stays valid until the track is stopped or fades out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TrackId(u64);
+
+#[derive(Debug, Clone, Copy)]
+struct Fade
+{
+    from : f32,
+    to : f32,
+    elapsed : f32,
+    duration : f32,
+    /// Cross fades remove the tracks they fade out
+    stop_when_done : bool,
+}
+
+/// A clip being played, with its own time, speed and blend weight
+#[derive(Debug, Clone)]
+pub struct AnimationTrack
+{
+    id : TrackId,
+    pub clip : Rc<AnimationClip>,
+    /// In seconds from the start of the clip
+    pub time : f32,
+    pub speed : f32,
+    /// Wraps back to the start at the end, otherwise holds the last frame
+    pub looping : bool,
+    /// Relative to the other tracks, see `AnimationPlayer::sample`
+    pub weight : f32,
+    fade : Option<Fade>,
+}
+
+impl AnimationTrack
+{
+    pub fn id(&self) -> TrackId
+    {
+        self.id
+    }
+
+    pub fn is_fading(&self) -> bool
+    {
+        self.fade.is_some()
+    }
+
+    /// Non looping tracks that have reached their last frame
+    pub fn is_finished(&self) -> bool
+    {
+        !self.looping && self.time >= self.clip.duration()
+    }
+}
+
+/// Plays and blends `AnimationClip`s into a `Pose`, call `update` every frame then `sample` for the pose to draw
+#[derive(Debug, Clone, Default)]
+pub struct AnimationPlayer
+{
+    tracks : Vec<AnimationTrack>,
+    next_id : u64,
+}
+
+impl AnimationPlayer
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    /// Adds `clip` without touching the other tracks, for blending several at once (e.g walking and running by speed)
+    pub fn add_track(&mut self, clip : &Rc<AnimationClip>, weight : f32, looping : bool) -> TrackId
+    {
+        let id = TrackId(self.next_id);
+        self.next_id += 1;
+        self.tracks.push(AnimationTrack
+        {
+            id,
+            clip : clip.clone(),
+            time : 0.,
+            speed : 1.,
+            looping,
+            weight,
+            fade : None,
+        });
+        id
+    }
+
+    /// Stops everything else and plays `clip` on its own
+    pub fn play(&mut self, clip : &Rc<AnimationClip>, looping : bool) -> TrackId
+    {
+        self.tracks.clear();
+        self.add_track(clip, 1., looping)
+    }
+
+    /// Fades `clip` in over `duration` seconds while every other track fades out and is then stopped
+    pub fn cross_fade(&mut self, clip : &Rc<AnimationClip>, duration : f32, looping : bool) -> TrackId
+    {
+        for track in &mut self.tracks
+        {
+            track.fade = Some(Fade { from : track.weight, to : 0., elapsed : 0., duration, stop_when_done : true });
+        }
+
+        let id = self.add_track(clip, 0., looping);
+        self.fade_track(id, 1., duration);
+        id
+    }
+
+    /// Moves a track's weight to `weight` over `duration` seconds, it keeps playing at 0
+    pub fn fade_track(&mut self, id : TrackId, weight : f32, duration : f32)
+    {
+        if let Some(track) = self.get_track_mut(id)
+        {
+            track.fade = Some(Fade { from : track.weight, to : weight, elapsed : 0., duration, stop_when_done : false });
+        }
+    }
+
+    pub fn stop(&mut self, id : TrackId)
+    {
+        self.tracks.retain(|track| track.id != id);
+    }
+
+    pub fn stop_all(&mut self)
+    {
+        self.tracks.clear();
+    }
+
+    pub fn get_track(&self, id : TrackId) -> Option<&AnimationTrack>
+    {
+        self.tracks.iter().find(|track| track.id == id)
+    }
+
+    pub fn get_track_mut(&mut self, id : TrackId) -> Option<&mut AnimationTrack>
+    {
+        self.tracks.iter_mut().find(|track| track.id == id)
+    }
+
+    pub fn tracks(&self) -> &[AnimationTrack]
+    {
+        &self.tracks
+    }
+
+    /// Moves every track and fade on by `delta` seconds
+    pub fn update(&mut self, delta : f32)
+    {
+        for track in &mut self.tracks
+        {
+            let duration = track.clip.duration();
+            track.time += delta * track.speed;
+            if track.looping && duration > 0.
+            {
+                track.time = track.time.rem_euclid(duration);
+            } else
+            {
+                track.time = track.time.clamp(0., duration);
+            }
+
+            if let Some(fade) = &mut track.fade
+            {
+                fade.elapsed += delta;
+                let progress = if fade.duration > 0. {(fade.elapsed / fade.duration).min(1.)} else {1.};
+                track.weight = fade.from + (fade.to - fade.from) * progress;
+            }
+        }
+
+        self.tracks.retain(|track| !track.fade.is_some_and(|fade| fade.stop_when_done && fade.elapsed >= fade.duration));
+        for track in &mut self.tracks
+        {
+            if track.fade.is_some_and(|fade| fade.elapsed >= fade.duration)
+            {
+                track.fade = None;
+            }
+        }
+    }
+
+    /// Every track blended by weight, weights are relative so two tracks at 1 are mixed evenly.
+    /// With nothing playing (or every weight at 0) this is the rest pose
+    pub fn sample(&self, skeleton : &Skeleton) -> Pose
+    {
+        let mut pose = skeleton.rest_pose();
+        let mut total = 0.;
+        for track in self.tracks.iter().filter(|track| track.weight > 0.)
+        {
+            total += track.weight;
+            let sampled = track.clip.sample(skeleton, track.time);
+            // Each pose takes its share of everything blended so far, which works out to a weighted average
+            pose = pose.blend(&sampled, track.weight / total);
+        }
+        pose
+    }
+}
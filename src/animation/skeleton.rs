@@ -0,0 +1,189 @@
+use crate::prospect_transform::{matrix_from_trs, multiply_matrices};
+
+pub(crate) const IDENTITY_MATRIX : [[f32; 4]; 4] = [[1., 0., 0., 0.], [0., 1., 0., 0.], [0., 0., 1., 0.], [0., 0., 0., 1.]];
+
+pub(crate) fn lerp<const N : usize>(a : [f32; N], b : [f32; N], t : f32) -> [f32; N]
+{
+    let mut result = a;
+    for (value, other) in result.iter_mut().zip(b)
+    {
+        *value += (other - *value) * t;
+    }
+    result
+}
+
+pub(crate) fn normalize_quaternion(q : [f32; 4]) -> [f32; 4]
+{
+    let length = q.iter().map(|value| value * value).sum::<f32>().sqrt();
+    if length > f32::EPSILON {q.map(|value| value / length)} else {[0., 0., 0., 1.]}
+}
+
+/// Spherical interpolation between `[x, y, z, w]` quaternions, the short way round
+pub(crate) fn slerp(a : [f32; 4], b : [f32; 4], t : f32) -> [f32; 4]
+{
+    let mut cos = a.iter().zip(b).map(|(a, b)| a * b).sum::<f32>();
+    let b = if cos < 0. { cos = -cos; b.map(|value| -value) } else { b };
+
+    // Close enough that the angle can't be found accurately, and a straight line is just as good
+    if cos > 0.9995
+    {
+        return normalize_quaternion(lerp(a, b, t));
+    }
+
+    let angle = cos.acos();
+    let sin = angle.sin();
+    let (wa, wb) = (((1. - t) * angle).sin() / sin, (t * angle).sin() / sin);
+    [0, 1, 2, 3].map(|i| a[i] * wa + b[i] * wb)
+}
+
+/// A joint's translation, `[x, y, z, w]` rotation and scale relative to its parent
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JointTransform
+{
+    pub translation : [f32; 3],
+    pub rotation : [f32; 4],
+    pub scale : [f32; 3],
+}
+
+impl Default for JointTransform
+{
+    fn default() -> Self
+    {
+        Self
+        {
+            translation : [0.; 3],
+            rotation : [0., 0., 0., 1.],
+            scale : [1.; 3],
+        }
+    }
+}
+
+impl JointTransform
+{
+    pub fn to_matrix(&self) -> [[f32; 4]; 4]
+    {
+        matrix_from_trs(self.translation, self.rotation, self.scale)
+    }
+
+    /// `weight` of the way from this to `other`, rotations are slerped
+    pub fn blend(&self, other : &Self, weight : f32) -> Self
+    {
+        Self
+        {
+            translation : lerp(self.translation, other.translation, weight),
+            rotation : slerp(self.rotation, other.rotation, weight),
+            scale : lerp(self.scale, other.scale, weight),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Joint
+{
+    pub name : String,
+    /// Always before this joint in `Skeleton::joints`
+    pub parent : Option<usize>,
+    /// Where the joint sits when nothing animates it
+    pub rest : JointTransform,
+    /// Takes a vertex from the mesh's space into the joint's, as it was when the mesh was bound to the skeleton
+    pub inverse_bind_matrix : [[f32; 4]; 4],
+}
+
+impl Joint
+{
+    pub fn new(name : &str, parent : Option<usize>, rest : JointTransform, inverse_bind_matrix : [[f32; 4]; 4]) -> Self
+    {
+        Self
+        {
+            name : name.to_string(),
+            parent,
+            rest,
+            inverse_bind_matrix,
+        }
+    }
+}
+
+/// A local transform for every joint of a `Skeleton`, in the same order
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pose
+{
+    pub joints : Vec<JointTransform>,
+}
+
+impl Pose
+{
+    /// `weight` of the way from this pose to `other`, joints only one of them has are kept as they are
+    pub fn blend(&self, other : &Pose, weight : f32) -> Pose
+    {
+        let mut joints = self.joints.clone();
+        for (joint, other) in joints.iter_mut().zip(&other.joints)
+        {
+            *joint = joint.blend(other, weight);
+        }
+        Pose { joints }
+    }
+}
+
+/// A hierarchy of joints that a `SkinnedVertex` mesh is bound to
+#[derive(Debug, Clone, PartialEq)]
+pub struct Skeleton
+{
+    joints : Vec<Joint>,
+}
+
+impl Skeleton
+{
+    /// Fails if a joint's parent isn't earlier in `joints`, which also rules out cycles
+    pub fn new(joints : Vec<Joint>) -> Result<Self, ()>
+    {
+        for (index, joint) in joints.iter().enumerate()
+        {
+            if joint.parent.is_some_and(|parent| parent >= index)
+            {
+                log::error!("Joint \"{}\" ({}) comes before its parent {:?}, parents must be listed first", joint.name, index, joint.parent);
+                return Err(());
+            }
+        }
+
+        Ok(Self { joints })
+    }
+
+    pub fn joints(&self) -> &[Joint]
+    {
+        &self.joints
+    }
+
+    pub fn joint_count(&self) -> usize
+    {
+        self.joints.len()
+    }
+
+    pub fn find_joint(&self, name : &str) -> Option<usize>
+    {
+        self.joints.iter().position(|joint| joint.name == name)
+    }
+
+    pub fn rest_pose(&self) -> Pose
+    {
+        Pose { joints : self.joints.iter().map(|joint| joint.rest).collect() }
+    }
+
+    /// Each joint's transform relative to the skeleton's root, joints missing from `pose` use their rest transform
+    pub fn world_matrices(&self, pose : &Pose) -> Vec<[[f32; 4]; 4]>
+    {
+        let mut matrices : Vec<[[f32; 4]; 4]> = Vec::with_capacity(self.joints.len());
+        for (index, joint) in self.joints.iter().enumerate()
+        {
+            let local = pose.joints.get(index).unwrap_or(&joint.rest).to_matrix();
+            let parent = joint.parent.map_or(IDENTITY_MATRIX, |parent| matrices[parent]);
+            matrices.push(multiply_matrices(&parent, &local));
+        }
+        matrices
+    }
+
+    /// The matrices `vs_skinned` moves vertices by, each joint's world matrix after its inverse bind matrix
+    pub fn skinning_matrices(&self, pose : &Pose) -> Vec<[[f32; 4]; 4]>
+    {
+        self.world_matrices(pose).iter().zip(&self.joints).map(|(world, joint)| multiply_matrices(world, &joint.inverse_bind_matrix)).collect()
+    }
+}
@@ -0,0 +1,97 @@
+use wgpu::{Buffer, BufferUsages, RenderPass};
+
+use crate::{abstraction::{graphics_context::GraphicsContext, mesh::{Mesh, Meshable}, prospect_window::ProspectWindow, shader::ProspectShader}, prospect_camera::ProspectCamera, prospect_material::Material, prospect_transform::{Transform, TransformUniform}, shaders::default_3d::Default3D, smart::SmartBindGroup};
+
+use super::{skeleton::IDENTITY_MATRIX, Pose, Skeleton};
+
+/// A `Model3D` for `Default3D::new_skinned`, with a buffer of joint matrices alongside the model matrix at group 3
+pub struct SkinnedModel3D
+{
+    pub transform : Transform,
+    matrix_buffer : Buffer,
+    joint_buffer : Buffer,
+    joint_count : usize,
+    bind_group : SmartBindGroup
+}
+
+impl SkinnedModel3D
+{
+    /// Room for `joint_count` joints, all starting at the bind pose.
+    /// Fails if `shader` wasn't made with `new_skinned`, or has fallen back to uniforms and `joint_count` is over `Default3D::max_joints`
+    pub fn new(shader : &Default3D, joint_count : usize, window : &mut ProspectWindow) -> Result<Self, ()>
+    {
+        if !shader.is_skinned()
+        {
+            log::error!("SkinnedModel3D needs a shader made with Default3D::new_skinned");
+            return Err(());
+        }
+        let layout = shader.get_model_matrix_bind_layout().ok_or(())?;
+        let device = window.get_device();
+
+        let matrix_buffer = GraphicsContext::create_buffer(device, "Transform Buffer", &[TransformUniform::default()], BufferUsages::COPY_DST | BufferUsages::UNIFORM);
+        let joint_buffer = match shader.max_joints()
+        {
+            Some(max) if joint_count > max =>
+            {
+                log::error!("{} joints is more than the {} this device supports without storage buffers", joint_count, max);
+                return Err(());
+            }
+            // The uniform array always has its full length
+            Some(max) => GraphicsContext::create_buffer(device, "Joint Matrix Buffer", &vec![IDENTITY_MATRIX; max], BufferUsages::COPY_DST | BufferUsages::UNIFORM),
+            // Storage buffers can't be empty
+            None => GraphicsContext::create_buffer(device, "Joint Matrix Buffer", &vec![IDENTITY_MATRIX; joint_count.max(1)], BufferUsages::COPY_DST | BufferUsages::STORAGE),
+        };
+
+        let entries = vec![
+            GraphicsContext::create_bind_group_entry(0, matrix_buffer.as_entire_binding()),
+            GraphicsContext::create_bind_group_entry(1, joint_buffer.as_entire_binding()),
+        ];
+        let bind_group = GraphicsContext::create_bind_group(device, "Skinned Model Bind Group", layout, &entries);
+
+        Ok(Self
+        {
+            transform : Transform::new(),
+            matrix_buffer,
+            joint_buffer,
+            joint_count,
+            bind_group : bind_group.into()
+        })
+    }
+
+    pub fn joint_count(&self) -> usize
+    {
+        self.joint_count
+    }
+
+    /// Uploads matrices from `Skeleton::skinning_matrices`, anything past `joint_count` is ignored
+    pub fn set_joint_matrices(&self, matrices : &[[[f32; 4]; 4]], window : &ProspectWindow)
+    {
+        if matrices.len() > self.joint_count
+        {
+            log::warn!("{} joint matrices given to a SkinnedModel3D with room for {}", matrices.len(), self.joint_count);
+        }
+        let count = matrices.len().min(self.joint_count);
+        GraphicsContext::update_buffer(window.get_queue(), &self.joint_buffer, 0, &matrices[..count]);
+    }
+
+    pub fn set_pose(&self, skeleton : &Skeleton, pose : &Pose, window : &ProspectWindow)
+    {
+        self.set_joint_matrices(&skeleton.skinning_matrices(pose), window);
+    }
+
+    pub fn draw<'a>(&'a self, render_pass : &mut RenderPass<'a>, window : &'a ProspectWindow, cam : &'a ProspectCamera, mesh : &'a impl Meshable)
+    {
+        let data = self.transform.generate_matrix();
+        GraphicsContext::update_buffer(window.get_queue(), &self.matrix_buffer, 0, &[data]);
+        self.bind_group.set_bind_group(render_pass, 3, &[]);
+        mesh.draw(render_pass, cam);
+    }
+
+    pub fn draw_with_material<'a>(&'a self, render_pass : &mut RenderPass<'a>, window : &'a ProspectWindow, cam : &'a ProspectCamera, mesh : &'a Mesh, material : &'a Material)
+    {
+        let data = self.transform.generate_matrix();
+        GraphicsContext::update_buffer(window.get_queue(), &self.matrix_buffer, 0, &[data]);
+        self.bind_group.set_bind_group(render_pass, 3, &[]);
+        mesh.draw_with_material(render_pass, cam, material);
+    }
+}
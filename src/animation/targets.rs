@@ -0,0 +1,99 @@
+use vecto_rs::linear::Vector;
+
+use crate::{prospect_camera::ProspectCamera, prospect_light::ProspectPointLight, prospect_transform::Transform};
+
+use super::{Animatable, KeyframeTrack};
+
+/// Which field of a `Transform` a track moves
+#[derive(Debug, Clone, PartialEq)]
+pub enum TransformTrack
+{
+    Position(KeyframeTrack<Vector>),
+    /// In degrees, like `Transform::rotation`
+    Rotation(KeyframeTrack<Vector>),
+    Scale(KeyframeTrack<f32>),
+}
+
+impl Animatable for Transform
+{
+    type Track = TransformTrack;
+
+    fn track_duration(track : &TransformTrack) -> f32
+    {
+        match track
+        {
+            TransformTrack::Position(track) | TransformTrack::Rotation(track) => track.duration(),
+            TransformTrack::Scale(track) => track.duration(),
+        }
+    }
+
+    fn apply_track(&mut self, track : &TransformTrack, time : f32)
+    {
+        match track
+        {
+            TransformTrack::Position(track) => if let Some(position) = track.sample(time) { self.position = position; },
+            TransformTrack::Rotation(track) => if let Some(rotation) = track.sample(time) { self.rotation = rotation; },
+            TransformTrack::Scale(track) => if let Some(scale) = track.sample(time) { self.scale = scale; },
+        }
+    }
+}
+
+/// Which field of a `ProspectCamera` a track moves, the change shows after its next `process_frame`
+#[derive(Debug, Clone, PartialEq)]
+pub enum CameraTrack
+{
+    Eye(KeyframeTrack<Vector>),
+    Rotation(KeyframeTrack<Vector>),
+}
+
+impl Animatable for ProspectCamera
+{
+    type Track = CameraTrack;
+
+    fn track_duration(track : &CameraTrack) -> f32
+    {
+        match track
+        {
+            CameraTrack::Eye(track) | CameraTrack::Rotation(track) => track.duration(),
+        }
+    }
+
+    fn apply_track(&mut self, track : &CameraTrack, time : f32)
+    {
+        match track
+        {
+            CameraTrack::Eye(track) => if let Some(eye) = track.sample(time) { self.eye = eye; },
+            CameraTrack::Rotation(track) => if let Some(rotation) = track.sample(time) { self.rotation = rotation; },
+        }
+    }
+}
+
+/// Which field of a `ProspectPointLight` a track moves, the change shows after its next `process_frame`
+#[derive(Debug, Clone, PartialEq)]
+pub enum LightTrack
+{
+    Colour(KeyframeTrack<Vector>),
+    Position(KeyframeTrack<Vector>),
+}
+
+impl Animatable for ProspectPointLight
+{
+    type Track = LightTrack;
+
+    fn track_duration(track : &LightTrack) -> f32
+    {
+        match track
+        {
+            LightTrack::Colour(track) | LightTrack::Position(track) => track.duration(),
+        }
+    }
+
+    fn apply_track(&mut self, track : &LightTrack, time : f32)
+    {
+        match track
+        {
+            LightTrack::Colour(track) => if let Some(colour) = track.sample(time) { self.colour = colour; },
+            LightTrack::Position(track) => if let Some(position) = track.sample(time) { self.position = position; },
+        }
+    }
+}
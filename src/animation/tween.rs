@@ -0,0 +1,335 @@
+use vecto_rs::linear::{Vector, VectorTrait};
+
+use super::Easing;
+
+/// A value a `KeyframeTrack` can move between
+pub trait Tweenable : Copy
+{
+    fn interpolate(&self, other : &Self, t : f32) -> Self;
+}
+
+impl Tweenable for f32
+{
+    fn interpolate(&self, other : &Self, t : f32) -> Self
+    {
+        self + (other - self) * t
+    }
+}
+
+impl<const N : usize> Tweenable for [f32; N]
+{
+    fn interpolate(&self, other : &Self, t : f32) -> Self
+    {
+        let mut result = *self;
+        for (value, other) in result.iter_mut().zip(other)
+        {
+            *value += (other - *value) * t;
+        }
+        result
+    }
+}
+
+impl Tweenable for Vector
+{
+    fn interpolate(&self, other : &Self, t : f32) -> Self
+    {
+        Vector::new3(self.x.interpolate(&other.x, t), self.y.interpolate(&other.y, t), self.z.interpolate(&other.z, t))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Keyframe<T>
+{
+    /// In seconds from the start of the animation
+    pub time : f32,
+    pub value : T,
+    /// How the value moves from this keyframe to the next
+    pub easing : Easing,
+}
+
+impl<T> Keyframe<T>
+{
+    pub fn new(time : f32, value : T, easing : Easing) -> Self
+    {
+        Self { time, value, easing }
+    }
+}
+
+/// Keyframes for a single value, held at the first before it starts and at the last after it ends
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyframeTrack<T>
+{
+    keyframes : Vec<Keyframe<T>>,
+}
+
+impl<T : Tweenable> KeyframeTrack<T>
+{
+    /// Keyframes are sorted by time, ones at the same time keep their order so a value can jump
+    pub fn new(mut keyframes : Vec<Keyframe<T>>) -> Self
+    {
+        keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+        Self { keyframes }
+    }
+
+    /// From `from` to `to` over `duration` seconds
+    pub fn tween(from : T, to : T, duration : f32, easing : Easing) -> Self
+    {
+        Self::new(vec![Keyframe::new(0., from, easing), Keyframe::new(duration, to, easing)])
+    }
+
+    pub fn keyframes(&self) -> &[Keyframe<T>]
+    {
+        &self.keyframes
+    }
+
+    /// The time of the last keyframe
+    pub fn duration(&self) -> f32
+    {
+        self.keyframes.last().map_or(0., |keyframe| keyframe.time)
+    }
+
+    /// `None` if there are no keyframes
+    pub fn sample(&self, time : f32) -> Option<T>
+    {
+        let next = self.keyframes.partition_point(|keyframe| keyframe.time <= time);
+        let previous = next.checked_sub(1).and_then(|previous| self.keyframes.get(previous));
+        match (previous, self.keyframes.get(next))
+        {
+            (Some(previous), Some(next)) =>
+            {
+                let t = (time - previous.time) / (next.time - previous.time);
+                Some(previous.value.interpolate(&next.value, previous.easing.apply(t)))
+            }
+            (Some(only), None) | (None, Some(only)) => Some(only.value),
+            (None, None) => None,
+        }
+    }
+}
+
+/// Something a `PropertyAnimation` can drive, `Track` says which of its properties a `KeyframeTrack` moves
+pub trait Animatable
+{
+    type Track;
+
+    fn track_duration(track : &Self::Track) -> f32;
+    fn apply_track(&mut self, track : &Self::Track, time : f32);
+}
+
+/// What happens when a `PropertyAnimation` reaches its end
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaybackMode
+{
+    /// Stops on the last frame
+    Once,
+    /// Starts again from the beginning
+    Loop,
+    /// Plays backwards to the start, then forwards again
+    PingPong,
+}
+
+type CompletionCallback<T> = Box<dyn FnMut(&mut T)>;
+
+/// Keyframe tracks playing together on one `Animatable`, such as a `Transform` or `ProspectCamera`.
+/// Call `update` every frame with the target to move it on
+pub struct PropertyAnimation<T : Animatable>
+{
+    tracks : Vec<T::Track>,
+    duration : f32,
+    time : f32,
+    pub speed : f32,
+    pub mode : PlaybackMode,
+    /// -1 while a ping pong is on its way back
+    direction : f32,
+    playing : bool,
+    finished : bool,
+    on_complete : Option<CompletionCallback<T>>,
+}
+
+impl<T : Animatable> Default for PropertyAnimation<T>
+{
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+impl<T : Animatable> PropertyAnimation<T>
+{
+    /// Plays once at normal speed, starting straight away
+    pub fn new() -> Self
+    {
+        Self
+        {
+            tracks : Vec::new(),
+            duration : 0.,
+            time : 0.,
+            speed : 1.,
+            mode : PlaybackMode::Once,
+            direction : 1.,
+            playing : true,
+            finished : false,
+            on_complete : None,
+        }
+    }
+
+    pub fn with_track(mut self, track : T::Track) -> Self
+    {
+        self.add_track(track);
+        self
+    }
+
+    pub fn with_mode(mut self, mode : PlaybackMode) -> Self
+    {
+        self.mode = mode;
+        self
+    }
+
+    pub fn with_speed(mut self, speed : f32) -> Self
+    {
+        self.speed = speed;
+        self
+    }
+
+    /// Called with the target whenever the end is reached, every cycle when looping and each time a ping pong gets back to the start
+    pub fn on_complete<F : FnMut(&mut T) + 'static>(mut self, callback : F) -> Self
+    {
+        self.on_complete = Some(Box::new(callback));
+        self
+    }
+
+    pub fn add_track(&mut self, track : T::Track)
+    {
+        self.duration = self.duration.max(T::track_duration(&track));
+        self.tracks.push(track);
+    }
+
+    /// The end of the longest track, in seconds
+    pub fn duration(&self) -> f32
+    {
+        self.duration
+    }
+
+    pub fn time(&self) -> f32
+    {
+        self.time
+    }
+
+    pub fn is_playing(&self) -> bool
+    {
+        self.playing
+    }
+
+    /// A `Once` animation that has reached its end
+    pub fn is_finished(&self) -> bool
+    {
+        self.finished
+    }
+
+    /// Resumes, or starts again if it had finished
+    pub fn play(&mut self)
+    {
+        if self.finished
+        {
+            self.restart();
+        }
+        self.playing = true;
+    }
+
+    pub fn pause(&mut self)
+    {
+        self.playing = false;
+    }
+
+    /// Back to the start, playing forwards
+    pub fn restart(&mut self)
+    {
+        self.time = 0.;
+        self.direction = 1.;
+        self.playing = true;
+        self.finished = false;
+    }
+
+    pub fn seek(&mut self, time : f32)
+    {
+        self.time = time.clamp(0., self.duration);
+        self.finished = false;
+    }
+
+    /// Moves the animation on by `delta` seconds (scaled by `speed`) and applies it to `target`
+    pub fn update(&mut self, delta : f32, target : &mut T)
+    {
+        if self.playing
+        {
+            self.advance(delta * self.speed, target);
+        }
+        self.apply(target);
+    }
+
+    /// Sets every property to its value at the current time without moving on
+    pub fn apply(&self, target : &mut T)
+    {
+        for track in &self.tracks
+        {
+            target.apply_track(track, self.time);
+        }
+    }
+
+    fn advance(&mut self, delta : f32, target : &mut T)
+    {
+        let duration = self.duration;
+        if duration <= 0.
+        {
+            self.time = 0.;
+        } else
+        {
+            self.time += delta * self.direction;
+        }
+
+        // A long frame can pass the end more than once, the callback runs for each
+        let completions = match self.mode
+        {
+            _ if duration <= 0. => usize::from(self.mode == PlaybackMode::Once),
+            PlaybackMode::Once if self.time >= duration || (self.time <= 0. && delta < 0.) =>
+            {
+                self.time = self.time.clamp(0., duration);
+                1
+            }
+            PlaybackMode::Once => 0,
+            PlaybackMode::Loop =>
+            {
+                let cycles = (self.time / duration).floor();
+                self.time -= cycles * duration;
+                cycles.abs() as usize
+            }
+            PlaybackMode::PingPong =>
+            {
+                // How far through a there and back cycle it is
+                let cycle = 2. * duration;
+                let along = if self.direction > 0. {self.time} else {cycle - self.time};
+                let cycles = (along / cycle).floor();
+                let phase = along - cycles * cycle;
+                (self.time, self.direction) = if phase <= duration {(phase, 1.)} else {(cycle - phase, -1.)};
+                cycles.abs() as usize
+            }
+        };
+
+        if self.mode == PlaybackMode::Once && completions > 0
+        {
+            self.playing = false;
+            self.finished = true;
+        }
+        for _ in 0..completions
+        {
+            self.apply(target);
+            self.complete(target);
+        }
+    }
+
+    fn complete(&mut self, target : &mut T)
+    {
+        if let Some(callback) = &mut self.on_complete
+        {
+            callback(target);
+        }
+    }
+}
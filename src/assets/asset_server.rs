@@ -0,0 +1,503 @@
+use std::{collections::{HashMap, HashSet}, panic::{catch_unwind, AssertUnwindSafe}, path::{Path, PathBuf}, rc::Weak, sync::{mpsc::{channel, Receiver, Sender}, Arc, Mutex}, thread};
+
+use image::RgbaImage;
+use wgpu::BindGroupLayout;
+
+use crate::{abstraction::{mesh::Mesh, prospect_window::ProspectWindow, vertex::Vertex}, formats::read_obj, prospect_material::Material, prospect_texture::{ProspectTexture, TextureBindLayout, TextureOptions}, smart::{SmartBindGroup, SmartRenderPipeline}, utils::prospect_fs::FileWatcher};
+
+use super::Handle;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoadState
+{
+    /// Still being read and decoded, or waiting for `AssetServer::process_frame` to upload it. Reloads don't go back to this
+    Loading,
+    Loaded,
+    /// The file couldn't be read or decoded, or the asset was already freed
+    Failed,
+}
+
+struct AssetEntry<T>
+{
+    asset : Option<T>,
+    state : LoadState,
+    path : Option<PathBuf>,
+    handle : Weak<()>,
+}
+
+/// Every asset of one type held by an `AssetServer`
+pub struct Assets<T>
+{
+    entries : HashMap<u64, AssetEntry<T>>,
+    paths : HashMap<PathBuf, u64>,
+}
+
+impl<T> Assets<T>
+{
+    fn new() -> Self
+    {
+        Self
+        {
+            entries : HashMap::new(),
+            paths : HashMap::new(),
+        }
+    }
+
+    pub fn get(&self, handle : &Handle<T>) -> Option<&T>
+    {
+        self.entries.get(&handle.id())?.asset.as_ref()
+    }
+
+    pub fn get_mut(&mut self, handle : &Handle<T>) -> Option<&mut T>
+    {
+        self.entries.get_mut(&handle.id())?.asset.as_mut()
+    }
+
+    pub fn get_load_state(&self, handle : &Handle<T>) -> LoadState
+    {
+        self.entries.get(&handle.id()).map_or(LoadState::Failed, |entry| entry.state)
+    }
+
+    pub fn get_path(&self, handle : &Handle<T>) -> Option<&Path>
+    {
+        self.entries.get(&handle.id())?.path.as_deref()
+    }
+
+    /// Includes assets that are still loading or failed to
+    pub fn len(&self) -> usize
+    {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.entries.is_empty()
+    }
+
+    /// A new handle to the asset already loaded from `path`, as long as something still holds one
+    fn cached(&self, path : &Path) -> Option<Handle<T>>
+    {
+        let id = *self.paths.get(path)?;
+        Handle::upgrade(id, &self.entries.get(&id)?.handle)
+    }
+
+    fn insert(&mut self, handle : &Handle<T>, asset : Option<T>, path : Option<PathBuf>)
+    {
+        let state = if asset.is_some() {LoadState::Loaded} else {LoadState::Loading};
+        if let Some(path) = &path
+        {
+            self.paths.insert(path.clone(), handle.id());
+        }
+        self.entries.insert(handle.id(), AssetEntry { asset, state, path, handle : handle.downgrade() });
+    }
+
+    fn finish(&mut self, id : u64, result : Result<T, String>)
+    {
+        let Some(entry) = self.entries.get_mut(&id) else { return; };
+        match result
+        {
+            Ok(asset) =>
+            {
+                entry.asset = Some(asset);
+                entry.state = LoadState::Loaded;
+            }
+            Err(error) if entry.asset.is_some() =>
+            {
+                log::error!("Failed to reload {}, keeping the previous version: {}", entry.path.as_deref().unwrap_or(Path::new("asset")).display(), error);
+            }
+            Err(error) =>
+            {
+                log::error!("Failed to load {}: {}", entry.path.as_deref().unwrap_or(Path::new("asset")).display(), error);
+                entry.state = LoadState::Failed;
+            }
+        }
+    }
+
+    /// Drops every asset no handle refers to anymore, returning their ids
+    fn free_unused(&mut self) -> Vec<u64>
+    {
+        let unused : Vec<u64> = self.entries.iter().filter(|(_, entry)| entry.handle.strong_count() == 0).map(|(id, _)| *id).collect();
+        for id in &unused
+        {
+            if let Some(path) = self.entries.remove(id).and_then(|entry| entry.path)
+            {
+                // The path may already point at a newer load of the same file
+                if self.paths.get(&path) == Some(id)
+                {
+                    self.paths.remove(&path);
+                }
+            }
+        }
+        unused
+    }
+}
+
+/// Anything an `AssetServer` can hand out a `Handle` to
+pub trait Asset : Sized + 'static
+{
+    fn assets(server : &AssetServer) -> &Assets<Self>;
+    fn assets_mut(server : &mut AssetServer) -> &mut Assets<Self>;
+}
+
+impl Asset for ProspectTexture
+{
+    fn assets(server : &AssetServer) -> &Assets<Self> { &server.textures }
+    fn assets_mut(server : &mut AssetServer) -> &mut Assets<Self> { &mut server.textures }
+}
+
+impl Asset for Mesh
+{
+    fn assets(server : &AssetServer) -> &Assets<Self> { &server.meshes }
+    fn assets_mut(server : &mut AssetServer) -> &mut Assets<Self> { &mut server.meshes }
+}
+
+impl Asset for Material
+{
+    fn assets(server : &AssetServer) -> &Assets<Self> { &server.materials }
+    fn assets_mut(server : &mut AssetServer) -> &mut Assets<Self> { &mut server.materials }
+}
+
+/// What a worker thread hands back, ready to upload
+enum Decoded
+{
+    Image(RgbaImage),
+    Mesh(Vec<Vertex>, Vec<u32>),
+}
+
+/// What the main thread needs to upload a decoded asset, kept here since it isn't `Send`
+#[derive(Clone)]
+enum Upload
+{
+    Texture(TextureOptions),
+    Mesh(SmartRenderPipeline),
+}
+
+type Job = Box<dyn FnOnce() + Send>;
+
+fn decode_image(path : &Path) -> Result<Decoded, String>
+{
+    let bytes = std::fs::read(path).map_err(|error| error.to_string())?;
+    let image = image::load_from_memory(&bytes).map_err(|error| error.to_string())?;
+    Ok(Decoded::Image(image.to_rgba8()))
+}
+
+fn decode_obj(path : &Path) -> Result<Decoded, String>
+{
+    let source = std::fs::read_to_string(path).map_err(|error| error.to_string())?;
+    let shape = read_obj(&source).map_err(|error| error.to_string())?.to_shape();
+    Ok(Decoded::Mesh(shape.vertices, shape.indices.unwrap_or_default()))
+}
+
+/// Loads textures and meshes from disk, decoding them on worker threads and uploading them to the GPU in `process_frame`.
+///
+/// Loading the same path again returns another handle to the asset that's already loaded (or loading) as long as a handle to it
+/// is still alive, the options or pipeline it was first loaded with are kept. Assets are freed in `process_frame` once every
+/// handle to them has been dropped. Materials, or anything built at runtime, can be stored with `add`.
+///
+/// Files that were loaded are watched and decoded again when they change. Meshes keep their material and get new buffers, textures are
+/// swapped and every `SmartBindGroup` made by `bind_texture` is rebuilt to point at the new one. If the new file fails to decode the
+/// previous version is kept
+pub struct AssetServer
+{
+    textures : Assets<ProspectTexture>,
+    meshes : Assets<Mesh>,
+    materials : Assets<Material>,
+    next_id : u64,
+    uploads : HashMap<u64, Upload>,
+    /// Decodes still in flight for each asset, a reload can start before the last one finishes
+    loading : HashMap<u64, usize>,
+    /// One bind group per texture and layout, the layout is kept so its address can't be reused
+    texture_bindings : HashMap<(u64, *const BindGroupLayout), (SmartBindGroup, TextureBindLayout)>,
+    watcher : FileWatcher,
+    jobs : Sender<Job>,
+    decoded : Receiver<(u64, Result<Decoded, String>)>,
+    decoded_sender : Sender<(u64, Result<Decoded, String>)>,
+}
+
+impl AssetServer
+{
+    pub fn new() -> Self
+    {
+        let worker_count = thread::available_parallelism().map_or(2, |count| count.get().clamp(1, 4));
+        Self::with_worker_count(worker_count)
+    }
+
+    /// Decodes on `worker_count` threads, which exit when the server is dropped
+    pub fn with_worker_count(worker_count : usize) -> Self
+    {
+        let (jobs, receiver) = channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        for index in 0..worker_count.max(1)
+        {
+            let receiver = receiver.clone();
+            let spawned = thread::Builder::new().name(format!("Prospect Asset Worker {}", index)).spawn(move ||
+            {
+                loop
+                {
+                    // The lock is released before the job runs so the other workers can pick up the next one
+                    let job = match receiver.lock()
+                    {
+                        Ok(receiver) => receiver.recv(),
+                        Err(_) => return,
+                    };
+                    match job
+                    {
+                        Ok(job) => job(),
+                        Err(_) => return,
+                    }
+                }
+            });
+
+            if let Err(error) = spawned
+            {
+                log::error!("Unable to start asset worker: {}", error);
+            }
+        }
+
+        let (decoded_sender, decoded) = channel();
+
+        Self
+        {
+            textures : Assets::new(),
+            meshes : Assets::new(),
+            materials : Assets::new(),
+            next_id : 0,
+            uploads : HashMap::new(),
+            loading : HashMap::new(),
+            texture_bindings : HashMap::new(),
+            watcher : FileWatcher::new("assets"),
+            jobs,
+            decoded,
+            decoded_sender,
+        }
+    }
+
+    fn new_handle<T>(&mut self) -> Handle<T>
+    {
+        self.next_id += 1;
+        Handle::new(self.next_id)
+    }
+
+    /// Paths are cached by their canonical form when the file exists, so `a/../b.png` and `b.png` share an asset
+    fn resolve(path : &Path) -> PathBuf
+    {
+        path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+    }
+
+    /// Runs `decode` on a worker thread, or right away if none are running
+    fn decode<F : FnOnce() -> Result<Decoded, String> + Send + 'static>(&mut self, id : u64, decode : F)
+    {
+        *self.loading.entry(id).or_default() += 1;
+
+        let sender = self.decoded_sender.clone();
+        let job : Job = Box::new(move ||
+        {
+            // A bad file shouldn't take a worker down with it, or leave the asset loading forever
+            let result = catch_unwind(AssertUnwindSafe(decode)).unwrap_or_else(|_| Err("decoder panicked".to_string()));
+            let _ = sender.send((id, result));
+        });
+
+        if let Err(job) = self.jobs.send(job)
+        {
+            (job.0)();
+        }
+    }
+
+    /// Starts loading the image at `path`, it can be drawn once `get_load_state` is `Loaded`
+    pub fn load_texture<P : AsRef<Path>>(&mut self, path : P, options : &TextureOptions) -> Handle<ProspectTexture>
+    {
+        let path = Self::resolve(path.as_ref());
+        if let Some(handle) = self.textures.cached(&path)
+        {
+            return handle;
+        }
+
+        let handle = self.new_handle();
+        self.watcher.watch(&path);
+        self.textures.insert(&handle, None, Some(path.clone()));
+        self.uploads.insert(handle.id(), Upload::Texture(*options));
+        self.decode(handle.id(), move || decode_image(&path));
+        handle
+    }
+
+    /// Starts loading the OBJ at `path` as a `Mesh` drawn with `pipeline`, which should take `Vertex`. Every material is merged into one mesh,
+    /// use `formats::load_obj` to keep them apart
+    pub fn load_mesh<P : AsRef<Path>>(&mut self, path : P, pipeline : &SmartRenderPipeline) -> Handle<Mesh>
+    {
+        let path = Self::resolve(path.as_ref());
+        if let Some(handle) = self.meshes.cached(&path)
+        {
+            return handle;
+        }
+
+        let handle = self.new_handle();
+        self.watcher.watch(&path);
+        self.meshes.insert(&handle, None, Some(path.clone()));
+        self.uploads.insert(handle.id(), Upload::Mesh(pipeline.clone()));
+        self.decode(handle.id(), move || decode_obj(&path));
+        handle
+    }
+
+    /// Stores an asset that's already loaded, it isn't cached by path
+    pub fn add<T : Asset>(&mut self, asset : T) -> Handle<T>
+    {
+        let handle = self.new_handle();
+        T::assets_mut(self).insert(&handle, Some(asset), None);
+        handle
+    }
+
+    /// `None` until the asset has loaded
+    pub fn get<T : Asset>(&self, handle : &Handle<T>) -> Option<&T>
+    {
+        T::assets(self).get(handle)
+    }
+
+    pub fn get_mut<T : Asset>(&mut self, handle : &Handle<T>) -> Option<&mut T>
+    {
+        T::assets_mut(self).get_mut(handle)
+    }
+
+    pub fn get_load_state<T : Asset>(&self, handle : &Handle<T>) -> LoadState
+    {
+        T::assets(self).get_load_state(handle)
+    }
+
+    pub fn is_loaded<T : Asset>(&self, handle : &Handle<T>) -> bool
+    {
+        self.get_load_state(handle) == LoadState::Loaded
+    }
+
+    /// Where the asset was loaded from, `None` for ones stored with `add`
+    pub fn get_path<T : Asset>(&self, handle : &Handle<T>) -> Option<&Path>
+    {
+        T::assets(self).get_path(handle)
+    }
+
+    pub fn get_assets<T : Asset>(&self) -> &Assets<T>
+    {
+        T::assets(self)
+    }
+
+    /// How many assets are still being read, decoded or waiting to upload, including reloads
+    pub fn get_loading_count(&self) -> usize
+    {
+        self.loading.len()
+    }
+
+    /// Binds a loaded texture through `layout`, the bind group is rebuilt in place whenever the texture reloads.
+    /// Binding the same texture through the same layout again returns the same bind group. `None` if it hasn't loaded yet
+    pub fn bind_texture(&mut self, handle : &Handle<ProspectTexture>, layout : &TextureBindLayout, window : &ProspectWindow) -> Option<SmartBindGroup>
+    {
+        let texture = self.textures.get(handle)?;
+        let (bind_group, _) = self.texture_bindings.entry((handle.id(), layout.get_layout() as *const BindGroupLayout))
+            .or_insert_with(|| (layout.create_prospect_texture(texture, window).into(), layout.clone()));
+        Some(bind_group.clone())
+    }
+
+    /// Queues a reload for every loaded file that changed since the last call
+    fn process_file_events(&mut self)
+    {
+        for path in self.watcher.changed_paths()
+        {
+            if let Some(&id) = self.textures.paths.get(&path)
+            {
+                log::info!("Reloading {}", path.display());
+                self.decode(id, move || decode_image(&path));
+            }
+            else if let Some(&id) = self.meshes.paths.get(&path)
+            {
+                log::info!("Reloading {}", path.display());
+                self.decode(id, move || decode_obj(&path));
+            }
+        }
+    }
+
+    fn free_unused(&mut self)
+    {
+        let freed : HashSet<u64> = [self.textures.free_unused(), self.meshes.free_unused(), self.materials.free_unused()].into_iter().flatten().collect();
+        for id in &freed
+        {
+            self.uploads.remove(id);
+            self.loading.remove(id);
+        }
+        self.texture_bindings.retain(|(texture, _), _| !freed.contains(texture));
+    }
+
+    /// Reloads changed files, uploads everything the workers finished decoding since the last call and frees assets without any handles left
+    pub fn process_frame(&mut self, window : &mut ProspectWindow)
+    {
+        self.process_file_events();
+
+        let decoded : Vec<_> = self.decoded.try_iter().collect();
+        for (id, result) in decoded
+        {
+            // Freed while it was loading
+            let Some(upload) = self.uploads.get(&id).cloned() else { continue; };
+            if let Some(count) = self.loading.get_mut(&id)
+            {
+                *count -= 1;
+                if *count == 0
+                {
+                    self.loading.remove(&id);
+                }
+            }
+
+            match (upload, result)
+            {
+                (Upload::Texture(options), Ok(Decoded::Image(image))) =>
+                {
+                    let name = self.textures.entries.get(&id).and_then(|entry| entry.path.as_ref()).map_or(String::from("Asset Texture"), |path| path.display().to_string());
+                    let texture = ProspectTexture::from_image_with_options(&name, image, &options, window);
+                    for (_, (bind_group, layout)) in self.texture_bindings.iter_mut().filter(|((texture, _), _)| *texture == id)
+                    {
+                        bind_group.replace(layout.create_prospect_texture(&texture, window));
+                    }
+                    self.textures.finish(id, Ok(texture));
+                }
+                (Upload::Mesh(pipeline), Ok(Decoded::Mesh(vertices, indices))) =>
+                {
+                    // Reloads keep the mesh so its material and bind groups stay put
+                    match self.meshes.entries.get_mut(&id).and_then(|entry| entry.asset.as_mut())
+                    {
+                        Some(mesh) => mesh.set_geometry(vertices, indices, window.get_device()),
+                        None => self.meshes.finish(id, Ok(Mesh::new(vertices, indices, window.get_device(), &pipeline))),
+                    }
+                }
+                (Upload::Texture(_), result) => self.textures.finish(id, result.and(Err("decoded as the wrong type".to_string()))),
+                (Upload::Mesh(_), result) => self.meshes.finish(id, result.and(Err("decoded as the wrong type".to_string()))),
+            }
+        }
+
+        self.free_unused();
+
+        // Between frames nothing can be drawing with them, so replaced pipelines and bind groups can go
+        for mesh in self.meshes.entries.values_mut().filter_map(|entry| entry.asset.as_mut())
+        {
+            mesh.trim();
+        }
+        for material in self.materials.entries.values_mut().filter_map(|entry| entry.asset.as_mut())
+        {
+            material.trim();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn freeing_an_old_load_keeps_a_newer_load_of_the_same_path()
+    {
+        let mut server = AssetServer::with_worker_count(1);
+        let options = TextureOptions::default();
+        drop(server.load_texture("missing.png", &options));
+        let handle = server.load_texture("missing.png", &options);
+
+        server.free_unused();
+
+        assert_eq!(server.get_assets::<ProspectTexture>().len(), 1);
+        assert_eq!(server.load_texture("missing.png", &options).id(), handle.id());
+    }
+}
@@ -0,0 +1,77 @@
+use std::{fmt::Debug, hash::Hash, marker::PhantomData, rc::{Rc, Weak}};
+
+/// A reference counted handle to an asset owned by an `AssetServer`.
+///
+/// Clones share the same asset, which is freed by `AssetServer::process_frame` once every clone has been dropped
+pub struct Handle<T>
+{
+    id : u64,
+    strong : Rc<()>,
+    // `fn() -> T` so a handle doesn't need `T` to be `Send` or `Sync` to say anything about it
+    marker : PhantomData<fn() -> T>,
+}
+
+impl<T> Handle<T>
+{
+    pub(crate) fn new(id : u64) -> Self
+    {
+        Self
+        {
+            id,
+            strong : Rc::new(()),
+            marker : PhantomData,
+        }
+    }
+
+    /// Another handle to `id` if any are still alive
+    pub(crate) fn upgrade(id : u64, weak : &Weak<()>) -> Option<Self>
+    {
+        Some(Self { id, strong : weak.upgrade()?, marker : PhantomData })
+    }
+
+    pub(crate) fn downgrade(&self) -> Weak<()>
+    {
+        Rc::downgrade(&self.strong)
+    }
+
+    pub fn id(&self) -> u64
+    {
+        self.id
+    }
+
+    /// How many handles share this asset, including this one
+    pub fn handle_count(&self) -> usize
+    {
+        Rc::strong_count(&self.strong)
+    }
+}
+
+impl<T> Clone for Handle<T>
+{
+    fn clone(&self) -> Self {
+        Self { id : self.id, strong : self.strong.clone(), marker : PhantomData }
+    }
+}
+
+impl<T> PartialEq for Handle<T>
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> Hash for Handle<T>
+{
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.id.hash(state)
+    }
+}
+
+impl<T> Debug for Handle<T>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Handle").field("type", &std::any::type_name::<T>()).field("id", &self.id).finish()
+    }
+}
@@ -0,0 +1,5 @@
+mod handle;
+mod asset_server;
+
+pub use handle::*;
+pub use asset_server::*;
@@ -0,0 +1,442 @@
+use std::{collections::HashMap, fmt::Write as _, io::Cursor, path::Path};
+
+use image::{ImageOutputFormat, RgbaImage};
+
+use crate::{abstraction::vertex::Vertex, prospect_shape::ProspectShape};
+
+use super::ObjSubmesh;
+
+/// A material written alongside an `ExportMesh`, meshes with the same material name share it
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportMaterial
+{
+    pub name : String,
+    /// Linear RGBA, multiplied with the texture by most tools
+    pub colour : [f32; 4],
+    /// Saved as a PNG, embedded in GLB files or next to OBJ ones
+    pub texture : Option<RgbaImage>,
+}
+
+impl ExportMaterial
+{
+    pub fn new(name : &str, colour : [f32; 4]) -> Self
+    {
+        Self
+        {
+            name : name.to_string(),
+            colour,
+            texture : None,
+        }
+    }
+
+    pub fn with_texture(mut self, texture : RgbaImage) -> Self
+    {
+        self.texture = Some(texture);
+        self
+    }
+}
+
+/// Geometry on the CPU ready to be written out, wound the way `Mesh` expects (see `Mesh::from_shape`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportMesh
+{
+    pub name : String,
+    pub vertices : Vec<Vertex>,
+    pub indices : Vec<u32>,
+    pub material : Option<ExportMaterial>,
+}
+
+impl ExportMesh
+{
+    pub fn new(name : &str, vertices : Vec<Vertex>, indices : Vec<u32>) -> Self
+    {
+        Self
+        {
+            name : name.to_string(),
+            vertices,
+            indices,
+            material : None,
+        }
+    }
+
+    /// Shapes without indices get the same ones `Mesh::from_shape` would generate
+    pub fn from_shape<T, U>(name : &str, shape : &ProspectShape<T, U>) -> Self
+        where   T : Into<Vec<Vertex>> + Clone,
+                U : Into<Vec<u32>> + Clone
+    {
+        Self::new(name, shape.get_vertices(), shape.get_indices::<Vertex>())
+    }
+
+    pub fn with_material(mut self, material : ExportMaterial) -> Self
+    {
+        self.material = Some(material);
+        self
+    }
+
+    /// Indices wound the way OBJ, PLY and glTF expect, the reverse of `Mesh`
+    fn file_indices(&self) -> impl Iterator<Item = [u32; 3]> + '_
+    {
+        self.indices.chunks_exact(3).map(|triangle| [triangle[0], triangle[2], triangle[1]])
+    }
+}
+
+impl From<&ObjSubmesh> for ExportMesh
+{
+    fn from(value : &ObjSubmesh) -> Self
+    {
+        let mut mesh = Self::new(value.material.as_deref().unwrap_or("Mesh"), value.vertices.clone(), value.indices.clone());
+        mesh.material = value.material.as_ref().map(|name| ExportMaterial::new(name, [1.; 4]));
+        mesh
+    }
+}
+
+/// Every distinct material by name, in the order they're first used
+fn unique_materials<'a>(meshes : impl IntoIterator<Item = &'a ExportMesh>) -> Vec<&'a ExportMaterial>
+{
+    let mut materials : Vec<&ExportMaterial> = Vec::new();
+    for material in meshes.into_iter().filter_map(|mesh| mesh.material.as_ref())
+    {
+        if !materials.iter().any(|existing| existing.name == material.name)
+        {
+            materials.push(material);
+        }
+    }
+    materials
+}
+
+fn encode_png(image : &RgbaImage) -> Result<Vec<u8>, String>
+{
+    let mut bytes = Cursor::new(Vec::new());
+    image.write_to(&mut bytes, ImageOutputFormat::Png).map_err(|error| error.to_string())?;
+    Ok(bytes.into_inner())
+}
+
+/// OBJ names can't span lines
+fn single_line(name : &str) -> String
+{
+    name.replace(['\n', '\r'], " ")
+}
+
+/// The OBJ text for `meshes`, referring to `mtllib` if one is given
+pub fn encode_obj(meshes : &[ExportMesh], mtllib : Option<&str>) -> String
+{
+    let mut obj = String::from("# Exported by Prospect\n");
+    if let Some(mtllib) = mtllib
+    {
+        let _ = writeln!(obj, "mtllib {}", mtllib);
+    }
+
+    let mut offset = 1;
+    for mesh in meshes
+    {
+        let _ = writeln!(obj, "o {}", single_line(&mesh.name));
+        for vertex in &mesh.vertices
+        {
+            let _ = writeln!(obj, "v {} {} {}", vertex.position[0], vertex.position[1], vertex.position[2]);
+        }
+        for vertex in &mesh.vertices
+        {
+            // OBJ's V points up, `Vertex` follows wgpu's where it points down
+            let _ = writeln!(obj, "vt {} {}", vertex.uv[0], 1. - vertex.uv[1]);
+        }
+        for vertex in &mesh.vertices
+        {
+            let _ = writeln!(obj, "vn {} {} {}", vertex.normal[0], vertex.normal[1], vertex.normal[2]);
+        }
+
+        if let Some(material) = &mesh.material
+        {
+            let _ = writeln!(obj, "usemtl {}", single_line(&material.name));
+        }
+        for [a, b, c] in mesh.file_indices()
+        {
+            let [a, b, c] = [a, b, c].map(|index| index as usize + offset);
+            let _ = writeln!(obj, "f {a}/{a}/{a} {b}/{b}/{b} {c}/{c}/{c}");
+        }
+        offset += mesh.vertices.len();
+    }
+    obj
+}
+
+/// The MTL text for every material in `meshes`, `texture_names` maps material names to their texture files
+pub fn encode_mtl(meshes : &[ExportMesh], texture_names : &HashMap<String, String>) -> String
+{
+    let mut mtl = String::from("# Exported by Prospect\n");
+    for material in unique_materials(meshes)
+    {
+        let [r, g, b, a] = material.colour;
+        let _ = writeln!(mtl, "\nnewmtl {}", single_line(&material.name));
+        let _ = writeln!(mtl, "Kd {} {} {}", r, g, b);
+        let _ = writeln!(mtl, "d {}", a);
+        if let Some(texture) = texture_names.get(&material.name)
+        {
+            let _ = writeln!(mtl, "map_Kd {}", texture);
+        }
+    }
+    mtl
+}
+
+/// Writes `meshes` to an OBJ at `path`. If any have materials an MTL is written next to it with the same name, along with a PNG for each texture
+pub fn write_obj<P : AsRef<Path>>(path : P, meshes : &[ExportMesh]) -> Result<(), ()>
+{
+    let path = path.as_ref();
+    let stem = path.file_stem().map_or(String::from("mesh"), |stem| stem.to_string_lossy().into_owned());
+    let materials = unique_materials(meshes);
+
+    let mut texture_names = HashMap::new();
+    for (index, material) in materials.iter().enumerate()
+    {
+        let Some(texture) = &material.texture else { continue; };
+        let name = format!("{}_{}.png", stem, index);
+        let texture_path = path.with_file_name(&name);
+        texture.save(&texture_path).map_err(|error| log::error!("Unable to write {}: {}", texture_path.display(), error))?;
+        texture_names.insert(material.name.clone(), name);
+    }
+
+    let mtllib = if materials.is_empty() {None} else
+    {
+        let name = format!("{}.mtl", stem);
+        let mtl_path = path.with_file_name(&name);
+        std::fs::write(&mtl_path, encode_mtl(meshes, &texture_names)).map_err(|error| log::error!("Unable to write {}: {}", mtl_path.display(), error))?;
+        Some(name)
+    };
+
+    std::fs::write(path, encode_obj(meshes, mtllib.as_deref())).map_err(|error| log::error!("Unable to write {}: {}", path.display(), error))
+}
+
+/// A binary little endian PLY with positions, normals and texture coordinates. PLY has no materials so they're left out
+pub fn encode_ply(meshes : &[ExportMesh]) -> Vec<u8>
+{
+    let vertex_count : usize = meshes.iter().map(|mesh| mesh.vertices.len()).sum();
+    let face_count : usize = meshes.iter().map(|mesh| mesh.indices.len() / 3).sum();
+
+    let header = format!("ply\nformat binary_little_endian 1.0\ncomment Exported by Prospect\nelement vertex {}\nproperty float x\nproperty float y\nproperty float z\nproperty float nx\nproperty float ny\nproperty float nz\nproperty float s\nproperty float t\nelement face {}\nproperty list uchar uint vertex_indices\nend_header\n", vertex_count, face_count);
+    let mut ply = header.into_bytes();
+    ply.reserve(vertex_count * 32 + face_count * 13);
+
+    for vertex in meshes.iter().flat_map(|mesh| &mesh.vertices)
+    {
+        // PLY's t points up like OBJ's V
+        let values = [vertex.position[0], vertex.position[1], vertex.position[2], vertex.normal[0], vertex.normal[1], vertex.normal[2], vertex.uv[0], 1. - vertex.uv[1]];
+        for value in values
+        {
+            ply.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+
+    let mut offset = 0;
+    for mesh in meshes
+    {
+        for triangle in mesh.file_indices()
+        {
+            ply.push(3);
+            for index in triangle
+            {
+                ply.extend_from_slice(&(index + offset).to_le_bytes());
+            }
+        }
+        offset += mesh.vertices.len() as u32;
+    }
+    ply
+}
+
+pub fn write_ply<P : AsRef<Path>>(path : P, meshes : &[ExportMesh]) -> Result<(), ()>
+{
+    let path = path.as_ref();
+    std::fs::write(path, encode_ply(meshes)).map_err(|error| log::error!("Unable to write {}: {}", path.display(), error))
+}
+
+fn json_string(text : &str) -> String
+{
+    let mut escaped = String::with_capacity(text.len() + 2);
+    escaped.push('"');
+    for character in text.chars()
+    {
+        match character
+        {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            character if (character as u32) < 0x20 => { let _ = write!(escaped, "\\u{:04x}", character as u32); }
+            character => escaped.push(character),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// The binary chunk of a GLB, with the JSON for every buffer view and accessor pointing into it
+#[derive(Default)]
+struct GlbBuilder
+{
+    binary : Vec<u8>,
+    buffer_views : Vec<String>,
+    accessors : Vec<String>,
+}
+
+impl GlbBuilder
+{
+    fn push_view(&mut self, bytes : &[u8], target : Option<u32>) -> usize
+    {
+        let offset = self.binary.len();
+        self.binary.extend_from_slice(bytes);
+        self.binary.resize(self.binary.len().next_multiple_of(4), 0);
+
+        let target = target.map_or(String::new(), |target| format!(",\"target\":{}", target));
+        self.buffer_views.push(format!("{{\"buffer\":0,\"byteOffset\":{},\"byteLength\":{}{}}}", offset, bytes.len(), target));
+        self.buffer_views.len() - 1
+    }
+
+    /// `N` floats per element, e.g 3 for `VEC3`
+    fn push_floats<const N : usize>(&mut self, values : &[[f32; N]], bounds : bool) -> usize
+    {
+        let bytes : Vec<u8> = values.iter().flatten().flat_map(|value| value.to_le_bytes()).collect();
+        let view = self.push_view(&bytes, Some(34962));
+        let kind = match N { 2 => "VEC2", 3 => "VEC3", _ => "VEC4" };
+
+        // POSITION accessors must have bounds
+        let bounds = if bounds && !values.is_empty()
+        {
+            let mut min = [f32::MAX; N];
+            let mut max = [f32::MIN; N];
+            for value in values
+            {
+                for i in 0..N
+                {
+                    min[i] = min[i].min(value[i]);
+                    max[i] = max[i].max(value[i]);
+                }
+            }
+            format!(",\"min\":{:?},\"max\":{:?}", min, max)
+        } else
+        {
+            String::new()
+        };
+
+        self.accessors.push(format!("{{\"bufferView\":{},\"componentType\":5126,\"count\":{},\"type\":\"{}\"{}}}", view, values.len(), kind, bounds));
+        self.accessors.len() - 1
+    }
+
+    fn push_indices(&mut self, indices : &[u32]) -> usize
+    {
+        let view = self.push_view(bytemuck::cast_slice(indices), Some(34963));
+        self.accessors.push(format!("{{\"bufferView\":{},\"componentType\":5125,\"count\":{},\"type\":\"SCALAR\"}}", view, indices.len()));
+        self.accessors.len() - 1
+    }
+}
+
+/// A binary glTF with a node per mesh, materials become base colour factors and embedded PNG textures
+pub fn encode_glb(meshes : &[ExportMesh]) -> Result<Vec<u8>, String>
+{
+    // glTF doesn't allow empty accessors or mesh lists, and JSON has no NaN or infinity
+    let mut exported = Vec::new();
+    for mesh in meshes
+    {
+        if mesh.vertices.is_empty() || mesh.indices.len() < 3
+        {
+            log::warn!("Skipping {} in GLB export, it has no triangles", mesh.name);
+            continue;
+        }
+        if mesh.indices.iter().any(|index| *index as usize >= mesh.vertices.len())
+        {
+            return Err(format!("{} has an index past its {} vertices", mesh.name, mesh.vertices.len()));
+        }
+        let finite = mesh.vertices.iter().all(|vertex| vertex.position.iter().chain(&vertex.normal).chain(&vertex.uv).all(|value| value.is_finite()));
+        if !finite
+        {
+            return Err(format!("{} has a position, normal or uv that isn't a finite number", mesh.name));
+        }
+        if let Some(material) = mesh.material.as_ref().filter(|material| !material.colour.iter().all(|value| value.is_finite()))
+        {
+            return Err(format!("material {} has a colour that isn't a finite number", material.name));
+        }
+        exported.push(mesh);
+    }
+    if exported.is_empty()
+    {
+        return Err(String::from("there are no meshes with triangles to export"));
+    }
+    let meshes = exported;
+
+    let mut builder = GlbBuilder::default();
+    let materials = unique_materials(meshes.iter().copied());
+
+    let mut images = Vec::new();
+    let mut material_json = Vec::new();
+    for material in &materials
+    {
+        let texture = match &material.texture
+        {
+            Some(texture) =>
+            {
+                let view = builder.push_view(&encode_png(texture)?, None);
+                images.push(format!("{{\"bufferView\":{},\"mimeType\":\"image/png\"}}", view));
+                format!(",\"baseColorTexture\":{{\"index\":{}}}", images.len() - 1)
+            }
+            None => String::new(),
+        };
+        material_json.push(format!("{{\"name\":{},\"pbrMetallicRoughness\":{{\"baseColorFactor\":{:?},\"metallicFactor\":0,\"roughnessFactor\":1{}}}}}", json_string(&material.name), material.colour, texture));
+    }
+
+    let mut mesh_json = Vec::new();
+    for mesh in &meshes
+    {
+        let positions : Vec<[f32; 3]> = mesh.vertices.iter().map(|vertex| vertex.position).collect();
+        let normals : Vec<[f32; 3]> = mesh.vertices.iter().map(|vertex| vertex.normal).collect();
+        let uvs : Vec<[f32; 2]> = mesh.vertices.iter().map(|vertex| vertex.uv).collect();
+        let indices : Vec<u32> = mesh.file_indices().flatten().collect();
+
+        let position = builder.push_floats(&positions, true);
+        let normal = builder.push_floats(&normals, false);
+        let uv = builder.push_floats(&uvs, false);
+        let indices = builder.push_indices(&indices);
+        let material = mesh.material.as_ref().and_then(|material| materials.iter().position(|existing| existing.name == material.name)).map_or(String::new(), |index| format!(",\"material\":{}", index));
+
+        mesh_json.push(format!("{{\"name\":{},\"primitives\":[{{\"attributes\":{{\"POSITION\":{},\"NORMAL\":{},\"TEXCOORD_0\":{}}},\"indices\":{}{}}}]}}", json_string(&mesh.name), position, normal, uv, indices, material));
+    }
+
+    let nodes : Vec<String> = meshes.iter().enumerate().map(|(index, mesh)| format!("{{\"name\":{},\"mesh\":{}}}", json_string(&mesh.name), index)).collect();
+    let textures : Vec<String> = (0..images.len()).map(|index| format!("{{\"source\":{}}}", index)).collect();
+    let scene_nodes : Vec<String> = (0..meshes.len()).map(|index| index.to_string()).collect();
+
+    let mut json = format!("{{\"asset\":{{\"version\":\"2.0\",\"generator\":\"Prospect\"}},\"scene\":0,\"scenes\":[{{\"nodes\":[{}]}}],\"nodes\":[{}],\"meshes\":[{}]", scene_nodes.join(","), nodes.join(","), mesh_json.join(","));
+    if !material_json.is_empty()
+    {
+        let _ = write!(json, ",\"materials\":[{}]", material_json.join(","));
+    }
+    if !images.is_empty()
+    {
+        let _ = write!(json, ",\"images\":[{}],\"textures\":[{}]", images.join(","), textures.join(","));
+    }
+    if !builder.binary.is_empty()
+    {
+        let _ = write!(json, ",\"buffers\":[{{\"byteLength\":{}}}],\"bufferViews\":[{}],\"accessors\":[{}]", builder.binary.len(), builder.buffer_views.join(","), builder.accessors.join(","));
+    }
+    json.push('}');
+
+    // Chunks are padded to 4 bytes, JSON with spaces and the binary with zeros
+    let mut json = json.into_bytes();
+    json.resize(json.len().next_multiple_of(4), b' ');
+
+    let mut glb = Vec::with_capacity(12 + 8 + json.len() + 8 + builder.binary.len());
+    let total = 12 + 8 + json.len() + if builder.binary.is_empty() {0} else {8 + builder.binary.len()};
+    glb.extend_from_slice(b"glTF");
+    glb.extend_from_slice(&2u32.to_le_bytes());
+    glb.extend_from_slice(&(total as u32).to_le_bytes());
+    glb.extend_from_slice(&(json.len() as u32).to_le_bytes());
+    glb.extend_from_slice(b"JSON");
+    glb.extend_from_slice(&json);
+    if !builder.binary.is_empty()
+    {
+        glb.extend_from_slice(&(builder.binary.len() as u32).to_le_bytes());
+        glb.extend_from_slice(b"BIN\0");
+        glb.extend_from_slice(&builder.binary);
+    }
+    Ok(glb)
+}
+
+pub fn write_glb<P : AsRef<Path>>(path : P, meshes : &[ExportMesh]) -> Result<(), ()>
+{
+    let path = path.as_ref();
+    let glb = encode_glb(meshes).map_err(|error| log::error!("Unable to encode {}: {}", path.display(), error))?;
+    std::fs::write(path, glb).map_err(|error| log::error!("Unable to write {}: {}", path.display(), error))
+}
@@ -0,0 +1,548 @@
+use std::{collections::HashMap, path::Path};
+
+use base64::Engine;
+use gltf::{khr_lights_punctual::Kind, mesh::Mode, texture::{MagFilter, MinFilter, WrappingMode}, Document, Gltf};
+use image::{Rgba, RgbaImage};
+use vecto_rs::linear::{Vector, VectorTrait};
+use wgpu::{AddressMode, Device, FilterMode, RenderPass};
+
+use crate::{abstraction::{mesh::Mesh, prospect_window::ProspectWindow, vertex::Vertex}, model::Model3D, prospect_camera::{ProjectionType, ProspectCamera}, prospect_light::ProspectPointLight, prospect_material::Material, prospect_texture::{ProspectTexture, SamplerOptions, TextureOptions}, prospect_transform::{matrix_from_trs, multiply_matrices, TransformUniform}, shaders::default_3d::Default3D, smart::{SmartBindGroup, SmartRenderPipeline}};
+
+/// Extensions that are understood, anything else a file uses is reported in `GltfScene::unsupported_extensions`
+const SUPPORTED_EXTENSIONS : &[&str] = &["KHR_lights_punctual"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GltfProjection
+{
+    /// `yfov` is in radians, `zfar` is `None` for an infinite projection
+    Perspective { yfov : f32, aspect_ratio : Option<f32>, znear : f32, zfar : Option<f32> },
+    Orthographic { xmag : f32, ymag : f32, znear : f32, zfar : f32 },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GltfCamera
+{
+    pub name : Option<String>,
+    pub projection : GltfProjection,
+}
+
+impl GltfCamera
+{
+    /// A camera placed by `world`, the world matrix of the node it's attached to
+    pub fn create_camera(&self, world : &[[f32; 4]; 4], device : &Device) -> ProspectCamera
+    {
+        let mut camera = ProspectCamera::new(device);
+        camera.eye = Vector::new3(world[3][0], world[3][1], world[3][2]);
+
+        // `ProspectCamera` undoes its rotation as x, then y, then z, so the node's rotation is split up as Rz * Ry * Rx
+        let axes = [world[0], world[1], world[2]].map(|axis|
+        {
+            let length = (axis[0] * axis[0] + axis[1] * axis[1] + axis[2] * axis[2]).sqrt().max(f32::EPSILON);
+            [axis[0] / length, axis[1] / length, axis[2] / length]
+        });
+        camera.rotation = Vector::new3(axes[1][2].atan2(axes[2][2]), (-axes[0][2]).clamp(-1., 1.).asin(), axes[0][1].atan2(axes[0][0]));
+
+        match self.projection
+        {
+            GltfProjection::Perspective { yfov, znear, zfar, .. } =>
+            {
+                camera.projection_type = ProjectionType::Perspective(yfov.to_degrees());
+                camera.znear = znear;
+                camera.zfar = zfar.unwrap_or(1000.);
+            }
+            GltfProjection::Orthographic { xmag, ymag, znear, zfar } =>
+            {
+                camera.projection_type = ProjectionType::Orthographic(xmag, -xmag, ymag, -ymag);
+                camera.znear = znear;
+                camera.zfar = zfar;
+            }
+        }
+        camera
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GltfLightKind
+{
+    Directional,
+    Point,
+    /// Angles are in radians from the centre of the cone
+    Spot { inner_cone_angle : f32, outer_cone_angle : f32 },
+}
+
+/// A light from `KHR_lights_punctual`, pointing down the local -Z axis of its node
+#[derive(Debug, Clone, PartialEq)]
+pub struct GltfLight
+{
+    pub name : Option<String>,
+    pub kind : GltfLightKind,
+    pub colour : [f32; 3],
+    /// Candela for point and spot lights, lux for directional ones
+    pub intensity : f32,
+    pub range : Option<f32>,
+}
+
+impl GltfLight
+{
+    /// A `ProspectPointLight` at the position of `world`, whatever the kind. Intensity is left out since it's in physical units
+    pub fn create_point_light(&self, world : &[[f32; 4]; 4], window : &mut ProspectWindow) -> ProspectPointLight
+    {
+        let mut light = ProspectPointLight::new(window);
+        light.position = Vector::new3(world[3][0], world[3][1], world[3][2]);
+        light.colour = Vector::new3(self.colour[0], self.colour[1], self.colour[2]);
+        light
+    }
+}
+
+/// A glTF material's parameters alongside the `Material` made from it, which only has the base colour texture (or factor).
+/// The rest are kept for shaders that can use them
+#[derive(Debug, Clone)]
+pub struct GltfMaterial
+{
+    pub name : Option<String>,
+    /// Linear RGBA
+    pub base_colour : [f32; 4],
+    pub metallic : f32,
+    pub roughness : f32,
+    pub emissive : [f32; 3],
+    pub double_sided : bool,
+    pub material : Material,
+}
+
+#[derive(Debug)]
+pub struct GltfMesh
+{
+    pub name : Option<String>,
+    /// One `Mesh` per triangle primitive
+    pub primitives : Vec<Mesh>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GltfNode
+{
+    pub name : Option<String>,
+    pub parent : Option<usize>,
+    pub children : Vec<usize>,
+    pub translation : [f32; 3],
+    /// A quaternion, `[x, y, z, w]`
+    pub rotation : [f32; 4],
+    pub scale : [f32; 3],
+    /// Indices into `GltfScene::meshes`, `cameras` and `lights`
+    pub mesh : Option<usize>,
+    pub camera : Option<usize>,
+    pub light : Option<usize>,
+}
+
+impl GltfNode
+{
+    /// Relative to the parent, column major
+    pub fn local_matrix(&self) -> [[f32; 4]; 4]
+    {
+        matrix_from_trs(self.translation, self.rotation, self.scale)
+    }
+}
+
+/// Everything imported from a glTF file. Nodes can be moved by changing their `translation`, `rotation` and `scale`
+pub struct GltfScene
+{
+    pub nodes : Vec<GltfNode>,
+    /// The top level nodes of the default scene
+    pub roots : Vec<usize>,
+    pub meshes : Vec<GltfMesh>,
+    pub materials : Vec<GltfMaterial>,
+    pub cameras : Vec<GltfCamera>,
+    pub lights : Vec<GltfLight>,
+    /// Extensions the file uses that were ignored
+    pub unsupported_extensions : Vec<String>,
+    /// A `Model3D` for every node with a mesh, since each needs its own transform buffer
+    models : Vec<Option<Model3D>>,
+}
+
+impl GltfScene
+{
+    /// The node's matrix with every parent's applied, column major
+    pub fn world_matrix(&self, node : usize) -> [[f32; 4]; 4]
+    {
+        let mut matrix = self.nodes[node].local_matrix();
+        let mut parent = self.nodes[node].parent;
+
+        // Bounded so a (invalid) cycle in the hierarchy can't hang
+        for _ in 0..self.nodes.len()
+        {
+            let Some(index) = parent else { break; };
+            matrix = multiply_matrices(&self.nodes[index].local_matrix(), &matrix);
+            parent = self.nodes[index].parent;
+        }
+        matrix
+    }
+
+    pub fn find_node(&self, name : &str) -> Option<usize>
+    {
+        self.nodes.iter().position(|node| node.name.as_deref() == Some(name))
+    }
+
+    /// Sets a bind group on every mesh, e.g a light at group 2
+    pub fn set_bind_group(&mut self, loc : u32, bind_group : &SmartBindGroup)
+    {
+        for mesh in self.meshes.iter_mut().flat_map(|mesh| mesh.primitives.iter_mut())
+        {
+            mesh.set_bind_group(loc, bind_group);
+        }
+    }
+
+    /// Draws every node with a mesh at its world matrix
+    pub fn draw<'a>(&'a self, render_pass : &mut RenderPass<'a>, window : &'a ProspectWindow, camera : &'a ProspectCamera)
+    {
+        for (index, node) in self.nodes.iter().enumerate()
+        {
+            let (Some(mesh), Some(model)) = (node.mesh, &self.models[index]) else { continue; };
+            let matrix = TransformUniform::from_columns(self.world_matrix(index));
+            for primitive in &self.meshes[mesh].primitives
+            {
+                model.draw_with_matrix(render_pass, window, camera, primitive, matrix);
+            }
+        }
+    }
+}
+
+fn percent_decode(uri : &str) -> String
+{
+    let bytes = uri.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len()
+    {
+        let escaped = (bytes[i] == b'%').then(|| uri.get(i + 1..i + 3)).flatten().and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped
+        {
+            Some(byte) =>
+            {
+                decoded.push(byte);
+                i += 3;
+            }
+            None =>
+            {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Reads a base64 data URI, or a file relative to `directory`
+fn read_uri(uri : &str, directory : Option<&Path>) -> Result<Vec<u8>, String>
+{
+    if let Some(data) = uri.strip_prefix("data:")
+    {
+        let (_, encoded) = data.split_once(";base64,").ok_or("only base64 data URIs are supported")?;
+        return base64::engine::general_purpose::STANDARD.decode(encoded).map_err(|error| error.to_string());
+    }
+
+    let directory = directory.ok_or_else(|| format!("`{}` is an external file, but there's no directory to find it in", uri))?;
+    let path = directory.join(percent_decode(uri));
+    std::fs::read(&path).map_err(|error| format!("unable to read {}: {}", path.display(), error))
+}
+
+fn load_buffers(document : &Document, blob : Option<Vec<u8>>, directory : Option<&Path>) -> Result<Vec<Vec<u8>>, String>
+{
+    let mut blob = blob;
+    document.buffers().map(|buffer|
+    {
+        let data = match buffer.source()
+        {
+            gltf::buffer::Source::Bin => blob.take().ok_or("the GLB binary chunk is missing")?,
+            gltf::buffer::Source::Uri(uri) => read_uri(uri, directory)?,
+        };
+
+        if data.len() < buffer.length()
+        {
+            return Err(format!("buffer {} is {} bytes, expected {}", buffer.index(), data.len(), buffer.length()));
+        }
+        Ok(data)
+    }).collect()
+}
+
+fn load_image(image : &gltf::Image, buffers : &[Vec<u8>], directory : Option<&Path>) -> Result<RgbaImage, String>
+{
+    let bytes = match image.source()
+    {
+        gltf::image::Source::View { view, .. } =>
+        {
+            let buffer = &buffers[view.buffer().index()];
+            buffer.get(view.offset()..view.offset() + view.length()).ok_or("image buffer view is out of range")?.to_vec()
+        }
+        gltf::image::Source::Uri { uri, .. } => read_uri(uri, directory)?,
+    };
+    image::load_from_memory(&bytes).map(|image| image.to_rgba8()).map_err(|error| error.to_string())
+}
+
+fn sampler_options(sampler : &gltf::texture::Sampler) -> SamplerOptions
+{
+    let address_mode = |mode| match mode
+    {
+        WrappingMode::ClampToEdge => AddressMode::ClampToEdge,
+        WrappingMode::MirroredRepeat => AddressMode::MirrorRepeat,
+        WrappingMode::Repeat => AddressMode::Repeat,
+    };
+
+    SamplerOptions
+    {
+        address_mode_u : address_mode(sampler.wrap_s()),
+        address_mode_v : address_mode(sampler.wrap_t()),
+        mag_filter : if sampler.mag_filter() == Some(MagFilter::Nearest) {FilterMode::Nearest} else {FilterMode::Linear},
+        min_filter : match sampler.min_filter()
+        {
+            Some(MinFilter::Nearest | MinFilter::NearestMipmapNearest | MinFilter::NearestMipmapLinear) => FilterMode::Nearest,
+            _ => FilterMode::Linear,
+        },
+        mipmap_filter : match sampler.min_filter()
+        {
+            Some(MinFilter::NearestMipmapNearest | MinFilter::LinearMipmapNearest) => FilterMode::Nearest,
+            _ => FilterMode::Linear,
+        },
+        ..Default::default()
+    }
+}
+
+fn linear_to_srgb(channel : f32) -> u8
+{
+    let channel = channel.clamp(0., 1.);
+    let encoded = if channel <= 0.0031308 {channel * 12.92} else {1.055 * channel.powf(1. / 2.4) - 0.055};
+    (encoded * 255.).round() as u8
+}
+
+/// A 1x1 sRGB texture of a linear colour, also used for MTL colours
+pub(super) fn colour_texture(name : &str, colour : [f32; 4], window : &mut ProspectWindow) -> ProspectTexture
+{
+    let pixel = Rgba([linear_to_srgb(colour[0]), linear_to_srgb(colour[1]), linear_to_srgb(colour[2]), (colour[3].clamp(0., 1.) * 255.).round() as u8]);
+    let options = TextureOptions { mipmaps : false, ..Default::default() };
+    ProspectTexture::from_image_with_options(name, RgbaImage::from_pixel(1, 1, pixel), &options, window)
+}
+
+struct Importer<'a>
+{
+    document : &'a Document,
+    buffers : Vec<Vec<u8>>,
+    directory : Option<&'a Path>,
+    shader : &'a Default3D,
+    pipeline : &'a SmartRenderPipeline,
+    /// Decoded once per image, `None` if it failed
+    images : HashMap<usize, Option<RgbaImage>>,
+}
+
+impl<'a> Importer<'a>
+{
+    fn load_material(&mut self, material : &gltf::Material, window : &mut ProspectWindow) -> GltfMaterial
+    {
+        let pbr = material.pbr_metallic_roughness();
+        let name = material.name().map(str::to_string);
+        let label = name.clone().unwrap_or_else(|| format!("glTF Material {}", material.index().unwrap_or(0)));
+
+        // The base colour factor tints the texture in glTF, Default3D can't so the texture wins
+        let texture = pbr.base_color_texture().and_then(|info|
+        {
+            let texture = info.texture();
+            let image = texture.source();
+            let directory = self.directory;
+            let buffers = &self.buffers;
+            let decoded = self.images.entry(image.index()).or_insert_with(|| load_image(&image, buffers, directory).map_err(|error| log::error!("Unable to load glTF image {}: {}", image.index(), error)).ok());
+            let options = TextureOptions { sampler : sampler_options(&texture.sampler()), ..Default::default() };
+            decoded.clone().map(|decoded| ProspectTexture::from_image_with_options(&label, decoded, &options, window))
+        }).unwrap_or_else(|| colour_texture(&label, pbr.base_color_factor(), window));
+
+        let mut created = Material::new(&label, self.pipeline);
+        created.set_texture(1, self.shader.get_texture_layout(), &texture, window);
+
+        GltfMaterial
+        {
+            name,
+            base_colour : pbr.base_color_factor(),
+            metallic : pbr.metallic_factor(),
+            roughness : pbr.roughness_factor(),
+            emissive : material.emissive_factor(),
+            double_sided : material.double_sided(),
+            material : created,
+        }
+    }
+
+    fn load_primitive(&self, primitive : &gltf::Primitive, device : &Device) -> Result<Mesh, String>
+    {
+        let reader = primitive.reader(|buffer| self.buffers.get(buffer.index()).map(Vec::as_slice));
+        let positions : Vec<[f32; 3]> = reader.read_positions().ok_or("it has no positions")?.collect();
+        let uvs : Vec<[f32; 2]> = reader.read_tex_coords(0).map(|uvs| uvs.into_f32().collect()).unwrap_or_else(|| vec![[0.; 2]; positions.len()]);
+        let normals : Option<Vec<[f32; 3]>> = reader.read_normals().map(Iterator::collect);
+        let mut indices : Vec<u32> = reader.read_indices().map(|indices| indices.into_u32().collect()).unwrap_or_else(|| (0..positions.len() as u32).collect());
+
+        if indices.iter().any(|index| *index as usize >= positions.len()) || uvs.len() != positions.len()
+        {
+            return Err("its indices or attributes are out of range".to_string());
+        }
+        indices.truncate(indices.len() - indices.len() % 3);
+
+        let vertices : Vec<Vertex> = match normals
+        {
+            Some(normals) if normals.len() == positions.len() => positions.iter().zip(&uvs).zip(&normals).map(|((position, uv), normal)| Vertex { position : *position, uv : *uv, normal : *normal }).collect(),
+            // glTF says to use flat normals when there are none, which means every triangle gets its own vertices
+            _ =>
+            {
+                let mut vertices = Vec::with_capacity(indices.len());
+                for triangle in indices.chunks_exact(3)
+                {
+                    let [a, b, c] = [0, 1, 2].map(|corner| positions[triangle[corner] as usize]);
+                    let (ab, ac) = ([b[0] - a[0], b[1] - a[1], b[2] - a[2]], [c[0] - a[0], c[1] - a[1], c[2] - a[2]]);
+                    let normal = [ab[1] * ac[2] - ab[2] * ac[1], ab[2] * ac[0] - ab[0] * ac[2], ab[0] * ac[1] - ab[1] * ac[0]];
+                    let length = (normal[0] * normal[0] + normal[1] * normal[1] + normal[2] * normal[2]).sqrt().max(f32::EPSILON);
+                    for index in triangle
+                    {
+                        vertices.push(Vertex { position : positions[*index as usize], uv : uvs[*index as usize], normal : normal.map(|axis| axis / length) });
+                    }
+                }
+                indices = (0..vertices.len() as u32).collect();
+                vertices
+            }
+        };
+
+        // Wound the same way as `Mesh::from_shape`, which is the reverse of glTF
+        for triangle in indices.chunks_exact_mut(3)
+        {
+            triangle.swap(1, 2);
+        }
+
+        Ok(Mesh::new(vertices, indices, device, self.pipeline))
+    }
+}
+
+/// Imports a `.gltf` (with embedded or external buffers and images) or `.glb` file. Meshes are drawn with `pipeline`, which should be
+/// built from `shader` with the camera at group 0, see `GltfScene::draw`.
+///
+/// Only triangle primitives are imported. Errors are logged, extensions the file uses but that aren't supported are logged as warnings
+/// and listed in `GltfScene::unsupported_extensions`, unless the file requires them in which case it fails to load
+pub fn load_gltf<P : AsRef<Path>>(path : P, shader : &Default3D, pipeline : &SmartRenderPipeline, window : &mut ProspectWindow) -> Result<GltfScene, ()>
+{
+    let path = path.as_ref();
+    let bytes = std::fs::read(path).map_err(|error| log::error!("Unable to read {}: {}", path.display(), error))?;
+    load_gltf_from_bytes(&bytes, Some(path.parent().unwrap_or(Path::new(""))), shader, pipeline, window)
+}
+
+/// Same as `load_gltf` for a file already in memory, e.g from `include_bytes!`. External files are found relative to `directory`
+pub fn load_gltf_from_bytes(bytes : &[u8], directory : Option<&Path>, shader : &Default3D, pipeline : &SmartRenderPipeline, window : &mut ProspectWindow) -> Result<GltfScene, ()>
+{
+    let Gltf { document, blob } = Gltf::from_slice(bytes).map_err(|error| log::error!("Invalid glTF: {}", error))?;
+
+    let unsupported_extensions : Vec<String> = document.extensions_used().filter(|extension| !SUPPORTED_EXTENSIONS.contains(extension)).map(str::to_string).collect();
+    for extension in &unsupported_extensions
+    {
+        if document.extensions_required().any(|required| required == extension)
+        {
+            log::error!("glTF requires the unsupported extension {}", extension);
+            return Err(());
+        }
+        log::warn!("glTF uses the unsupported extension {}, it will be ignored", extension);
+    }
+
+    let buffers = load_buffers(&document, blob, directory).map_err(|error| log::error!("Unable to load glTF buffers: {}", error))?;
+    let mut importer = Importer { document : &document, buffers, directory, shader, pipeline, images : HashMap::new() };
+
+    let materials : Vec<GltfMaterial> = importer.document.materials().map(|material| importer.load_material(&material, window)).collect();
+    let mut default_material = None;
+
+    let mut meshes = Vec::new();
+    for mesh in document.meshes()
+    {
+        let mut primitives = Vec::new();
+        for primitive in mesh.primitives()
+        {
+            if primitive.mode() != Mode::Triangles
+            {
+                log::warn!("Skipping a {:?} primitive of glTF mesh {}, only triangles are supported", primitive.mode(), mesh.index());
+                continue;
+            }
+
+            match importer.load_primitive(&primitive, window.get_device())
+            {
+                Ok(mut created) =>
+                {
+                    let material = match primitive.material().index()
+                    {
+                        Some(index) => &materials[index].material,
+                        None => &default_material.get_or_insert_with(|| importer.load_material(&primitive.material(), window)).material,
+                    };
+                    created.set_material(material);
+                    primitives.push(created);
+                }
+                Err(error) => log::error!("Skipping a primitive of glTF mesh {}: {}", mesh.index(), error),
+            }
+        }
+        meshes.push(GltfMesh { name : mesh.name().map(str::to_string), primitives });
+    }
+
+    let mut nodes : Vec<GltfNode> = document.nodes().map(|node|
+    {
+        let (translation, rotation, scale) = node.transform().decomposed();
+        GltfNode
+        {
+            name : node.name().map(str::to_string),
+            parent : None,
+            children : node.children().map(|child| child.index()).collect(),
+            translation,
+            rotation,
+            scale,
+            mesh : node.mesh().map(|mesh| mesh.index()),
+            camera : node.camera().map(|camera| camera.index()),
+            light : node.light().map(|light| light.index()),
+        }
+    }).collect();
+
+    for index in 0..nodes.len()
+    {
+        for child in nodes[index].children.clone()
+        {
+            nodes[child].parent = Some(index);
+        }
+    }
+
+    let roots = match document.default_scene().or_else(|| document.scenes().next())
+    {
+        Some(scene) => scene.nodes().map(|node| node.index()).collect(),
+        None => (0..nodes.len()).filter(|index| nodes[*index].parent.is_none()).collect(),
+    };
+
+    let cameras = document.cameras().map(|camera| GltfCamera
+    {
+        name : camera.name().map(str::to_string),
+        projection : match camera.projection()
+        {
+            gltf::camera::Projection::Perspective(perspective) => GltfProjection::Perspective { yfov : perspective.yfov(), aspect_ratio : perspective.aspect_ratio(), znear : perspective.znear(), zfar : perspective.zfar() },
+            gltf::camera::Projection::Orthographic(orthographic) => GltfProjection::Orthographic { xmag : orthographic.xmag(), ymag : orthographic.ymag(), znear : orthographic.znear(), zfar : orthographic.zfar() },
+        },
+    }).collect();
+
+    let lights = document.lights().into_iter().flatten().map(|light| GltfLight
+    {
+        name : light.name().map(str::to_string),
+        kind : match light.kind()
+        {
+            Kind::Directional => GltfLightKind::Directional,
+            Kind::Point => GltfLightKind::Point,
+            Kind::Spot { inner_cone_angle, outer_cone_angle } => GltfLightKind::Spot { inner_cone_angle, outer_cone_angle },
+        },
+        colour : light.color(),
+        intensity : light.intensity(),
+        range : light.range(),
+    }).collect();
+
+    let models = nodes.iter().map(|node| node.mesh.map(|_| Model3D::new(shader, window))).collect();
+
+    Ok(GltfScene
+    {
+        nodes,
+        roots,
+        meshes,
+        materials,
+        cameras,
+        lights,
+        unsupported_extensions,
+        models,
+    })
+}
@@ -0,0 +1,11 @@
+mod parse_error;
+mod obj;
+mod mtl;
+mod gltf_import;
+mod export;
+
+pub use parse_error::*;
+pub use obj::*;
+pub use mtl::*;
+pub use gltf_import::*;
+pub use export::*;
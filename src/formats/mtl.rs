@@ -0,0 +1,202 @@
+use std::path::Path;
+
+use crate::{abstraction::prospect_window::ProspectWindow, prospect_material::Material, prospect_texture::{ProspectTexture, TextureBindLayout, TextureOptions}, smart::SmartRenderPipeline};
+
+use super::{gltf_import::colour_texture, parse_error::parse_floats, ParseError};
+
+/// A `newmtl` from an MTL file, colours are linear RGB
+#[derive(Debug, Clone, PartialEq)]
+pub struct MtlMaterial
+{
+    pub name : String,
+    /// `Ka`
+    pub ambient : [f32; 3],
+    /// `Kd`
+    pub diffuse : [f32; 3],
+    /// `Ks`
+    pub specular : [f32; 3],
+    /// `Ke`
+    pub emissive : [f32; 3],
+    /// `Ns`
+    pub shininess : f32,
+    /// `d`, or 1 - `Tr`
+    pub opacity : f32,
+    /// `map_Kd`, relative to the MTL
+    pub diffuse_map : Option<String>,
+    /// `map_Bump`, `bump` or `norm`, relative to the MTL
+    pub normal_map : Option<String>,
+}
+
+impl MtlMaterial
+{
+    /// White rather than the MTL default of 0.8 grey so a missing `Kd` doesn't darken the diffuse map
+    pub fn new(name : &str) -> Self
+    {
+        Self
+        {
+            name : name.to_string(),
+            ambient : [0.; 3],
+            diffuse : [1.; 3],
+            specular : [0.; 3],
+            emissive : [0.; 3],
+            shininess : 0.,
+            opacity : 1.,
+            diffuse_map : None,
+            normal_map : None,
+        }
+    }
+
+    /// A `Material` with the diffuse map (or a 1x1 texture of the diffuse colour if there isn't one, or it won't load) bound at group 1 through `layout`.
+    /// Maps are found relative to `directory`
+    pub fn create_material(&self, directory : &Path, layout : &TextureBindLayout, pipeline : &SmartRenderPipeline, window : &mut ProspectWindow) -> Material
+    {
+        let map = self.diffuse_map.as_ref().and_then(|map|
+        {
+            let path = directory.join(map);
+            match image::open(&path)
+            {
+                Ok(image) => Some(ProspectTexture::from_image_with_options(&path.display().to_string(), image.to_rgba8(), &TextureOptions::default(), window)),
+                Err(error) =>
+                {
+                    log::warn!("Unable to load {} for material \"{}\", using its colour instead: {}", path.display(), self.name, error);
+                    None
+                }
+            }
+        });
+
+        let texture = map.unwrap_or_else(||
+        {
+            let [r, g, b] = self.diffuse;
+            colour_texture(&self.name, [r, g, b, self.opacity], window)
+        });
+
+        let mut material = Material::new(&self.name, pipeline);
+        material.set_texture(1, layout, &texture, window);
+        material
+    }
+}
+
+fn parse_colour(line : usize, keyword : &str, arguments : &[&str]) -> Result<[f32; 3], ParseError>
+{
+    if arguments.first().is_some_and(|first| *first == "spectral" || *first == "xyz")
+    {
+        return Err(ParseError::new(line, format!("`{} {}` colours aren't supported, use RGB", keyword, arguments[0])));
+    }
+
+    // `Kd r` is shorthand for `Kd r r r`
+    match parse_floats(line, keyword, arguments, 1, 3)?[..]
+    {
+        [value] => Ok([value; 3]),
+        [r, g, b] => Ok([r, g, b]),
+        _ => Err(ParseError::new(line, format!("`{}` expects 1 or 3 numbers, found 2", keyword))),
+    }
+}
+
+/// The file name at the end of a `map_` statement, skipping any options such as `-s 1 1 1` before it
+fn parse_map(line : usize, keyword : &str, arguments : &[&str]) -> Result<String, ParseError>
+{
+    match arguments.iter().position(|argument| argument.starts_with('-'))
+    {
+        // Names may contain spaces, which can't be told apart from option values, so only the last word is used with options
+        Some(_) => arguments.last().filter(|last| !last.starts_with('-')).map(|last| last.to_string()),
+        None => (!arguments.is_empty()).then(|| arguments.join(" ")),
+    }.ok_or_else(|| ParseError::new(line, format!("`{}` needs a file name", keyword)))
+}
+
+/// Parses MTL source into its materials. Statements that only matter to offline renderers (`illum`, `Ni`, other maps...) are skipped
+pub fn read_mtl(source : &str) -> Result<Vec<MtlMaterial>, ParseError>
+{
+    let mut materials : Vec<MtlMaterial> = Vec::new();
+
+    for (index, text) in source.lines().enumerate()
+    {
+        let line = index + 1;
+        let text = text.split('#').next().unwrap_or("").trim();
+        let mut tokens = text.split_whitespace();
+        let Some(keyword) = tokens.next() else { continue; };
+        let arguments : Vec<&str> = tokens.collect();
+
+        if keyword == "newmtl"
+        {
+            if arguments.is_empty()
+            {
+                return Err(ParseError::new(line, "`newmtl` needs a name"));
+            }
+            materials.push(MtlMaterial::new(&arguments.join(" ")));
+            continue;
+        }
+
+        let is_known = matches!(keyword, "Ka" | "Kd" | "Ks" | "Ke" | "Ns" | "d" | "Tr" | "map_Kd" | "map_Bump" | "map_bump" | "bump" | "norm");
+        let Some(material) = materials.last_mut() else
+        {
+            if is_known
+            {
+                return Err(ParseError::new(line, format!("`{}` before any `newmtl`", keyword)));
+            }
+            continue;
+        };
+
+        match keyword
+        {
+            "Ka" => material.ambient = parse_colour(line, keyword, &arguments)?,
+            "Kd" => material.diffuse = parse_colour(line, keyword, &arguments)?,
+            "Ks" => material.specular = parse_colour(line, keyword, &arguments)?,
+            "Ke" => material.emissive = parse_colour(line, keyword, &arguments)?,
+            "Ns" => material.shininess = parse_floats(line, keyword, &arguments, 1, 1)?[0],
+            "d" => material.opacity = parse_floats(line, keyword, &arguments, 1, 1)?[0],
+            "Tr" => material.opacity = 1. - parse_floats(line, keyword, &arguments, 1, 1)?[0],
+            "map_Kd" => material.diffuse_map = Some(parse_map(line, keyword, &arguments)?),
+            "map_Bump" | "map_bump" | "bump" | "norm" => material.normal_map = Some(parse_map(line, keyword, &arguments)?),
+            _ => {}
+        }
+    }
+
+    Ok(materials)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn test_materials()
+    {
+        let source = "# exported\nnewmtl red\nKd 1 0 0\nNs 10\nd 0.5\nillum 2\nmap_Kd -s 1 1 1 red.png\n\nnewmtl grey stone\nKd 0.5\nTr 0.25\nmap_Bump stone normal.png\n";
+        let materials = read_mtl(source).unwrap();
+        assert_eq!(materials.len(), 2);
+
+        let red = &materials[0];
+        assert_eq!(red.name, "red");
+        assert_eq!(red.diffuse, [1., 0., 0.]);
+        assert_eq!(red.shininess, 10.);
+        assert_eq!(red.opacity, 0.5);
+        assert_eq!(red.diffuse_map.as_deref(), Some("red.png"));
+
+        let grey = &materials[1];
+        assert_eq!(grey.name, "grey stone");
+        assert_eq!(grey.diffuse, [0.5; 3]);
+        assert_eq!(grey.opacity, 0.75);
+        assert_eq!(grey.diffuse_map, None);
+        assert_eq!(grey.normal_map.as_deref(), Some("stone normal.png"));
+    }
+
+    #[test]
+    fn test_error_lines()
+    {
+        let cases = [
+            ("Kd 1 1 1\n", 1),
+            ("newmtl a\nKd 1 1\n", 2),
+            ("newmtl a\n\nKd spectral red.spd\n", 3),
+            ("newmtl a\nNs\n", 2),
+            ("newmtl a\nd 1\nmap_Kd\n", 3),
+            ("illum 2\nnewmtl\n", 2),
+        ];
+
+        for (source, line) in cases
+        {
+            let error = read_mtl(source).unwrap_err();
+            assert_eq!(error.line, line, "{:?} gave {}", source, error);
+        }
+    }
+}
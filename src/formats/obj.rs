@@ -0,0 +1,353 @@
+use std::{collections::HashMap, path::Path};
+
+use wgpu::Device;
+
+use crate::{abstraction::{mesh::Mesh, prospect_window::ProspectWindow, vertex::Vertex}, prospect_material::Material, prospect_shape::ProspectShape, prospect_texture::TextureBindLayout, smart::SmartRenderPipeline, utils::math::{add, cross, normalize}};
+
+use super::{parse_error::parse_floats, read_mtl, ParseError};
+
+/// The triangles of an OBJ drawn with one material, with their own deduplicated vertices
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjSubmesh
+{
+    /// The `usemtl` these faces came after, `None` for faces before any
+    pub material : Option<String>,
+    pub vertices : Vec<Vertex>,
+    pub indices : Vec<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ObjModel
+{
+    pub submeshes : Vec<ObjSubmesh>,
+    /// Every `mtllib`, relative to the OBJ
+    pub material_libraries : Vec<String>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum NormalKey
+{
+    Index(usize),
+    /// Faces without normals get a flat one, so only corners of faces facing the same way are shared
+    Generated([u32; 3]),
+}
+
+struct SubmeshBuilder
+{
+    submesh : ObjSubmesh,
+    lookup : HashMap<(usize, Option<usize>, NormalKey), u32>,
+}
+
+impl SubmeshBuilder
+{
+    fn new(material : Option<String>) -> Self
+    {
+        Self
+        {
+            submesh : ObjSubmesh { material, vertices : Vec::new(), indices : Vec::new() },
+            lookup : HashMap::new(),
+        }
+    }
+}
+
+/// Turns a 1 based (or negative, counting back from the end) OBJ index into a 0 based one
+fn resolve_index(line : usize, text : &str, count : usize, kind : &str) -> Result<usize, ParseError>
+{
+    let index = text.parse::<i64>().map_err(|_| ParseError::new(line, format!("`{}` is not a valid {} index", text, kind)))?;
+    let resolved = if index > 0 {index - 1} else {count as i64 + index};
+
+    if index == 0 || resolved < 0 || resolved >= count as i64
+    {
+        return Err(ParseError::new(line, format!("{} index {} is out of range, there are {}", kind, index, count)));
+    }
+    Ok(resolved as usize)
+}
+
+/// Parses OBJ source into indexed submeshes, one per material.
+///
+/// Polygons are fanned into triangles and wound to match `Mesh::from_shape` (the reverse of OBJ), faces without normals get flat
+/// ones and V is flipped so textures aren't upside down. Keywords that don't affect the geometry (`o`, `g`, `s`, ...) are skipped
+pub fn read_obj(source : &str) -> Result<ObjModel, ParseError>
+{
+    let mut positions : Vec<[f32; 3]> = Vec::new();
+    let mut uvs : Vec<[f32; 2]> = Vec::new();
+    let mut normals : Vec<[f32; 3]> = Vec::new();
+    let mut material_libraries = Vec::new();
+    let mut builders = vec![SubmeshBuilder::new(None)];
+    let mut current = 0;
+
+    for (index, text) in source.lines().enumerate()
+    {
+        let line = index + 1;
+        let text = text.split('#').next().unwrap_or("").trim();
+        let mut tokens = text.split_whitespace();
+        let Some(keyword) = tokens.next() else { continue; };
+        let arguments : Vec<&str> = tokens.collect();
+
+        match keyword
+        {
+            "v" =>
+            {
+                // w or vertex colours may follow, neither are used
+                let values = parse_floats(line, keyword, &arguments, 3, 7)?;
+                positions.push([values[0], values[1], values[2]]);
+            }
+            "vt" =>
+            {
+                let values = parse_floats(line, keyword, &arguments, 1, 3)?;
+                uvs.push([values[0], 1. - values.get(1).copied().unwrap_or(0.)]);
+            }
+            "vn" =>
+            {
+                let values = parse_floats(line, keyword, &arguments, 3, 3)?;
+                normals.push([values[0], values[1], values[2]]);
+            }
+            "mtllib" =>
+            {
+                if arguments.is_empty()
+                {
+                    return Err(ParseError::new(line, "`mtllib` needs a file name"));
+                }
+                material_libraries.push(arguments.join(" "));
+            }
+            "usemtl" =>
+            {
+                if arguments.is_empty()
+                {
+                    return Err(ParseError::new(line, "`usemtl` needs a material name"));
+                }
+                let material = Some(arguments.join(" "));
+                current = match builders.iter().position(|builder| builder.submesh.material == material)
+                {
+                    Some(existing) => existing,
+                    None =>
+                    {
+                        builders.push(SubmeshBuilder::new(material));
+                        builders.len() - 1
+                    }
+                };
+            }
+            "f" =>
+            {
+                if arguments.len() < 3
+                {
+                    return Err(ParseError::new(line, format!("a face needs at least 3 corners, found {}", arguments.len())));
+                }
+
+                let mut corners = Vec::with_capacity(arguments.len());
+                for corner in &arguments
+                {
+                    let mut parts = corner.split('/');
+                    let position = resolve_index(line, parts.next().unwrap_or(""), positions.len(), "position")?;
+                    let uv = match parts.next()
+                    {
+                        Some("") | None => None,
+                        Some(uv) => Some(resolve_index(line, uv, uvs.len(), "texture coordinate")?),
+                    };
+                    let normal = match parts.next()
+                    {
+                        Some("") | None => None,
+                        Some(normal) => Some(resolve_index(line, normal, normals.len(), "normal")?),
+                    };
+                    if parts.next().is_some()
+                    {
+                        return Err(ParseError::new(line, format!("`{}` has too many parts for a face corner", corner)));
+                    }
+                    corners.push((position, uv, normal));
+                }
+
+                // Newell's method, so concave or slightly non planar polygons still get a sensible normal
+                let flat_normal = if corners.iter().any(|corner| corner.2.is_none())
+                {
+                    let mut normal = [0.; 3];
+                    for (i, corner) in corners.iter().enumerate()
+                    {
+                        let next = corners[(i + 1) % corners.len()];
+                        let edge = cross(positions[corner.0], positions[next.0]);
+                        normal = add(normal, edge);
+                    }
+                    normalize(normal).unwrap_or([0., 1., 0.])
+                } else
+                {
+                    [0.; 3]
+                };
+
+                let builder = &mut builders[current];
+                let mut indices = Vec::with_capacity(corners.len());
+                for (position, uv, normal) in corners
+                {
+                    let key = match normal
+                    {
+                        Some(normal) => NormalKey::Index(normal),
+                        None => NormalKey::Generated(flat_normal.map(f32::to_bits)),
+                    };
+
+                    let vertex_count = builder.submesh.vertices.len() as u32;
+                    let vertices = &mut builder.submesh.vertices;
+                    let index = *builder.lookup.entry((position, uv, key)).or_insert_with(||
+                    {
+                        vertices.push(Vertex
+                        {
+                            position : positions[position],
+                            uv : uv.map_or([0., 0.], |uv| uvs[uv]),
+                            normal : normal.map_or(flat_normal, |normal| normals[normal]),
+                        });
+                        vertex_count
+                    });
+                    indices.push(index);
+                }
+
+                for i in 1..indices.len() - 1
+                {
+                    builder.submesh.indices.extend([indices[0], indices[i + 1], indices[i]]);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(ObjModel
+    {
+        submeshes : builders.into_iter().map(|builder| builder.submesh).filter(|submesh| !submesh.indices.is_empty()).collect(),
+        material_libraries,
+    })
+}
+
+impl ObjModel
+{
+    /// Every submesh in one shape, for when materials don't matter
+    pub fn to_shape(&self) -> ProspectShape<Vec<Vertex>, Vec<u32>>
+    {
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        for submesh in &self.submeshes
+        {
+            let offset = vertices.len() as u32;
+            vertices.extend_from_slice(&submesh.vertices);
+            indices.extend(submesh.indices.iter().map(|index| index + offset));
+        }
+
+        ProspectShape { vertices, indices : Some(indices) }
+    }
+
+    /// A `Mesh` per submesh, using the material of the same name if there is one
+    pub fn create_meshes(&self, materials : &HashMap<String, Material>, pipeline : &SmartRenderPipeline, device : &Device) -> Vec<Mesh>
+    {
+        self.submeshes.iter().map(|submesh|
+        {
+            let mut mesh = Mesh::new(submesh.vertices.clone(), submesh.indices.clone(), device, pipeline);
+            if let Some(material) = submesh.material.as_ref().and_then(|name| materials.get(name))
+            {
+                mesh.set_material(material);
+            }
+            mesh
+        }).collect()
+    }
+}
+
+/// Reads an OBJ and the MTL files it uses into a `Mesh` per material, their diffuse maps or colours bound at group 1 through `layout`.
+///
+/// Errors are logged with the file and line. A missing or broken MTL only loses its materials, the meshes still load
+pub fn load_obj<P : AsRef<Path>>(path : P, layout : &TextureBindLayout, pipeline : &SmartRenderPipeline, window : &mut ProspectWindow) -> Result<Vec<Mesh>, ()>
+{
+    let path = path.as_ref();
+    let source = std::fs::read_to_string(path).map_err(|error| log::error!("Unable to read {}: {}", path.display(), error))?;
+    let model = read_obj(&source).map_err(|error| log::error!("{}: {}", path.display(), error))?;
+    let directory = path.parent().unwrap_or(Path::new(""));
+
+    let mut materials = HashMap::new();
+    for library in &model.material_libraries
+    {
+        let library_path = directory.join(library);
+        let parsed = std::fs::read_to_string(&library_path)
+            .map_err(|error| log::error!("Unable to read {}: {}", library_path.display(), error))
+            .and_then(|source| read_mtl(&source).map_err(|error| log::error!("{}: {}", library_path.display(), error)));
+
+        for material in parsed.into_iter().flatten()
+        {
+            let created = material.create_material(library_path.parent().unwrap_or(Path::new("")), layout, pipeline, window);
+            materials.insert(material.name, created);
+        }
+    }
+
+    Ok(model.create_meshes(&materials, pipeline, window.get_device()))
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    const SQUARE_POSITIONS : &str = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n";
+
+    #[test]
+    fn test_negative_indices()
+    {
+        let positive = read_obj(&format!("{}f 1 2 3\n", SQUARE_POSITIONS)).unwrap();
+        let negative = read_obj(&format!("{}f -4 -3 -2\n", SQUARE_POSITIONS)).unwrap();
+        assert_eq!(positive, negative);
+
+        // Negative indices count back from the last vertex read so far, not the end of the file
+        let interleaved = read_obj("v 0 0 0\nv 1 0 0\nv 1 1 0\nf -3 -2 -1\nv 5 5 5\n").unwrap();
+        assert_eq!(interleaved.submeshes[0].vertices, positive.submeshes[0].vertices);
+    }
+
+    #[test]
+    fn test_polygon_fanning()
+    {
+        let model = read_obj(&format!("{}vn 0 0 1\nf 1//1 2//1 3//1 4//1\n", SQUARE_POSITIONS)).unwrap();
+        assert_eq!(model.submeshes.len(), 1);
+
+        let submesh = &model.submeshes[0];
+        assert_eq!(submesh.vertices.len(), 4);
+        // Fanned from the first corner, with the winding reversed
+        assert_eq!(submesh.indices, vec![0, 2, 1, 0, 3, 2]);
+        assert!(submesh.vertices.iter().all(|vertex| vertex.normal == [0., 0., 1.]));
+    }
+
+    #[test]
+    fn test_usemtl_splits_submeshes()
+    {
+        let source = format!("{}f 1 2 3\nusemtl red\nf 1 3 4\nusemtl blue\nf 1 2 4\nusemtl red\nf 2 3 4\n", SQUARE_POSITIONS);
+        let model = read_obj(&source).unwrap();
+
+        let materials : Vec<Option<&str>> = model.submeshes.iter().map(|submesh| submesh.material.as_deref()).collect();
+        assert_eq!(materials, vec![None, Some("red"), Some("blue")]);
+
+        // Going back to a material adds to its submesh
+        let triangle_counts : Vec<usize> = model.submeshes.iter().map(|submesh| submesh.indices.len() / 3).collect();
+        assert_eq!(triangle_counts, vec![1, 2, 1]);
+        assert_eq!(model.to_shape().indices.unwrap().len(), 12);
+    }
+
+    #[test]
+    fn test_flat_normals_and_uvs()
+    {
+        let model = read_obj("v 0 0 0\nv 1 0 0\nv 0 1 0\nvt 0 0\nvt 1 0\nvt 0 0.25\nf 1/1 2/2 3/3\n").unwrap();
+        let vertices = &model.submeshes[0].vertices;
+        assert!(vertices.iter().all(|vertex| vertex.normal == [0., 0., 1.]));
+        assert_eq!(vertices[2].uv, [0., 0.75]);
+    }
+
+    #[test]
+    fn test_error_lines()
+    {
+        let cases = [
+            ("v 0 0 0\nv 1 0\n", 2),
+            ("v 0 0 0\n\n# comment\nv 1 0 x\n", 4),
+            (&*format!("{}f 1 2\n", SQUARE_POSITIONS), 5),
+            (&*format!("{}o square\nf 1 2 5\n", SQUARE_POSITIONS), 6),
+            (&*format!("{}f 1 2 -5\n", SQUARE_POSITIONS), 5),
+            (&*format!("{}f 1 2 0\n", SQUARE_POSITIONS), 5),
+            (&*format!("{}f 1/1 2 3\n", SQUARE_POSITIONS), 5),
+            (&*format!("{}f 1/1/1/1 2 3\n", SQUARE_POSITIONS), 5),
+            ("usemtl\n", 1),
+        ];
+
+        for (source, line) in cases
+        {
+            let error = read_obj(source).unwrap_err();
+            assert_eq!(error.line, line, "{:?} gave {}", source, error);
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use std::fmt::Display;
+
+/// A line of a text format that couldn't be understood, `line` starts at 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError
+{
+    pub line : usize,
+    pub message : String,
+}
+
+impl ParseError
+{
+    pub fn new<S : Into<String>>(line : usize, message : S) -> Self
+    {
+        Self
+        {
+            line,
+            message : message.into(),
+        }
+    }
+}
+
+impl Display for ParseError
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parses every whitespace separated argument after the keyword as an `f32`
+pub(crate) fn parse_floats(line : usize, keyword : &str, arguments : &[&str], min : usize, max : usize) -> Result<Vec<f32>, ParseError>
+{
+    if arguments.len() < min || arguments.len() > max
+    {
+        let expected = if min == max {min.to_string()} else {format!("{} to {}", min, max)};
+        return Err(ParseError::new(line, format!("`{}` expects {} numbers, found {}", keyword, expected, arguments.len())));
+    }
+
+    arguments.iter().map(|argument| argument.parse::<f32>().map_err(|_| ParseError::new(line, format!("`{}` is not a number", argument)))).collect()
+}
@@ -1,6 +1,10 @@
+// Lets prospect-derive refer to `::prospect` from inside this crate
+extern crate self as prospect;
+
 pub mod abstraction;
 pub mod prospect_app;
 pub mod prospect_shape;
+pub mod prospect_primitives;
 pub mod shaders;
 pub mod utils;
 pub mod prospect_camera;
@@ -11,9 +15,27 @@ pub mod model;
 pub mod prospect_texture;
 pub mod smart;
 pub mod prospect_framebuffer;
+pub mod prospect_material;
+pub mod prospect_cubemap;
+pub mod prospect_skybox;
+pub mod prospect_environment;
+pub mod prospect_texture_array;
+pub mod prospect_texture_atlas;
+pub mod prospect_dynamic_texture;
+pub mod text;
+pub mod sprite;
+pub mod prospect_debug_draw;
+pub mod post_process;
+pub mod assets;
+pub mod formats;
+pub mod animation;
+#[cfg(feature = "egui")]
+pub mod prospect_egui;
 
 // Re-exports
 pub use wgpu;
 pub use prospect_obj::*;
 pub use vecto_rs::*;
-pub use winit;
\ No newline at end of file
+pub use winit;
+#[cfg(feature = "egui")]
+pub use egui;
\ No newline at end of file
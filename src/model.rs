@@ -1,4 +1,4 @@
-use crate::{abstraction::{mesh::Meshable, prospect_window::ProspectWindow, graphics_context::GraphicsContext, high_level_abstraction::HighLevelGraphicsContext, shader::ProspectShader}, prospect_transform::{Transform, TransformUniform}, prospect_camera::ProspectCamera, smart::SmartBindGroup};
+use crate::{abstraction::{mesh::{Meshable, Mesh}, prospect_window::ProspectWindow, graphics_context::GraphicsContext, high_level_abstraction::HighLevelGraphicsContext, shader::ProspectShader}, prospect_transform::{Transform, TransformUniform}, prospect_camera::ProspectCamera, smart::SmartBindGroup, prospect_material::Material};
 use wgpu::*;
 
 
@@ -33,4 +33,20 @@ impl Model3D
         self.bind_group.set_bind_group(render_pass, index, &[]);
         mesh.draw(render_pass, cam);
     }
+
+    /// Draws with `matrix` instead of `transform`, for placements a `Transform` can't describe such as a `GltfScene` node
+    pub fn draw_with_matrix<'a>(&'a self, render_pass : &mut RenderPass<'a>, window : &'a ProspectWindow, cam : &'a ProspectCamera, mesh : &'a impl Meshable, matrix : TransformUniform)
+    {
+        GraphicsContext::update_buffer(window.get_queue(), &self.matrix_buffer, 0, &[matrix]);
+        self.bind_group.set_bind_group(render_pass, 3, &[]);
+        mesh.draw(render_pass, cam);
+    }
+
+    pub fn draw_with_material<'a>(&'a self, render_pass : &mut RenderPass<'a>, window : &'a ProspectWindow, cam : &'a ProspectCamera, mesh : &'a Mesh, material : &'a Material)
+    {
+        let data = self.transform.generate_matrix();
+        GraphicsContext::update_buffer(window.get_queue(), &self.matrix_buffer, 0, &[data]);
+        self.bind_group.set_bind_group(render_pass, 3, &[]);
+        mesh.draw_with_material(render_pass, cam, material);
+    }
 }
\ No newline at end of file
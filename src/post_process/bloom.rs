@@ -0,0 +1,139 @@
+use std::any::Any;
+
+use wgpu::*;
+
+use crate::{abstraction::{graphics_context::GraphicsContext, high_level_abstraction::HighLevelGraphicsContext, prospect_window::ProspectWindow}, prospect_framebuffer::ProspectFramebuffer, prospect_texture::BindableTexture};
+
+use super::{FullscreenPass, PostProcessEffect, PostProcessResources};
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable, Default)]
+struct BloomUniform
+{
+    threshold : f32,
+    soft_knee : f32,
+    intensity : f32,
+    _padding : f32,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable, Default)]
+struct BlurUniform
+{
+    direction : [f32; 2],
+    _padding : [f32; 2],
+}
+
+/// Bright pass, separable gaussian blur at half resolution, then added back on top of the scene
+pub struct Bloom
+{
+    /// Brightness above which pixels start to glow
+    pub threshold : f32,
+    /// 0 is a hard cut off at the threshold, 1 fades in over the whole range below it
+    pub soft_knee : f32,
+    pub intensity : f32,
+    /// Each pass is one horizontal and one vertical blur
+    pub blur_passes : u32,
+    threshold_pass : FullscreenPass,
+    blur_pass : FullscreenPass,
+    composite_pass : FullscreenPass,
+    texture_layout : BindGroupLayout,
+    params_buffer : Buffer,
+    params_bind_group : BindGroup,
+    blur_bind_groups : [BindGroup; 2],
+    targets : [ProspectFramebuffer; 2],
+}
+
+impl Bloom
+{
+    pub fn new(window : &ProspectWindow) -> Self
+    {
+        let device = window.get_device();
+        let format = window.get_surface_config().format;
+
+        let params_layout = FullscreenPass::create_uniform_layout(device, "Bloom Params Layout");
+        let texture_layout = FullscreenPass::create_texture_layout(device, "Bloom Texture Layout");
+
+        let params_buffer = GraphicsContext::create_buffer(device, "Bloom Params Buffer", &[BloomUniform::default()], BufferUsages::UNIFORM | BufferUsages::COPY_DST);
+        let params_bind_group = HighLevelGraphicsContext::create_uniform_with_bind_group(device, "Bloom Params", &params_buffer, &params_layout);
+
+        let horizontal = GraphicsContext::create_buffer(device, "Bloom Horizontal Blur Buffer", &[BlurUniform { direction : [1., 0.], _padding : [0.; 2] }], BufferUsages::UNIFORM);
+        let vertical = GraphicsContext::create_buffer(device, "Bloom Vertical Blur Buffer", &[BlurUniform { direction : [0., 1.], _padding : [0.; 2] }], BufferUsages::UNIFORM);
+        let blur_bind_groups = [
+            HighLevelGraphicsContext::create_uniform_with_bind_group(device, "Bloom Horizontal Blur", &horizontal, &params_layout),
+            HighLevelGraphicsContext::create_uniform_with_bind_group(device, "Bloom Vertical Blur", &vertical, &params_layout),
+        ];
+
+        Self
+        {
+            threshold : 0.8,
+            soft_knee : 0.5,
+            intensity : 1.,
+            blur_passes : 2,
+            threshold_pass : FullscreenPass::new(device, "Bloom Threshold", include_str!("shaders/bloom_threshold.wgsl"), format, vec![&params_layout]),
+            blur_pass : FullscreenPass::new(device, "Bloom Blur", include_str!("shaders/bloom_blur.wgsl"), format, vec![&params_layout]),
+            composite_pass : FullscreenPass::new(device, "Bloom Composite", include_str!("shaders/bloom_composite.wgsl"), format, vec![&params_layout, &texture_layout]),
+            texture_layout,
+            params_buffer,
+            params_bind_group,
+            blur_bind_groups,
+            targets : Self::create_targets(device, window.size, format),
+        }
+    }
+
+    fn create_targets(device : &Device, size : (u32, u32), format : TextureFormat) -> [ProspectFramebuffer; 2]
+    {
+        let width = (size.0 / 2).max(1);
+        let height = (size.1 / 2).max(1);
+
+        [
+            ProspectFramebuffer::new_with_format(device, width, height, format),
+            ProspectFramebuffer::new_with_format(device, width, height, format),
+        ]
+    }
+}
+
+impl PostProcessEffect for Bloom
+{
+    fn get_name(&self) -> &str
+    {
+        "Bloom"
+    }
+
+    fn resize(&mut self, window : &ProspectWindow, width : u32, height : u32)
+    {
+        self.targets = Self::create_targets(window.get_device(), (width, height), window.get_surface_config().format);
+    }
+
+    fn apply(&mut self, window : &ProspectWindow, resources : &PostProcessResources, encoder : &mut CommandEncoder, input : &TextureView, output : &TextureView)
+    {
+        let device = window.get_device();
+
+        let data = BloomUniform { threshold : self.threshold, soft_knee : self.soft_knee, intensity : self.intensity, _padding : 0. };
+        GraphicsContext::update_buffer(window.get_queue(), &self.params_buffer, 0, &[data]);
+
+        let scene = self.threshold_pass.create_input_bind_group(device, input, &resources.sampler, &resources.globals);
+        self.threshold_pass.draw(encoder, self.targets[0].get_texture_view(), &[&scene, &self.params_bind_group]);
+
+        let from_first = self.blur_pass.create_input_bind_group(device, self.targets[0].get_texture_view(), &resources.sampler, &resources.globals);
+        let from_second = self.blur_pass.create_input_bind_group(device, self.targets[1].get_texture_view(), &resources.sampler, &resources.globals);
+        for _ in 0..self.blur_passes
+        {
+            self.blur_pass.draw(encoder, self.targets[1].get_texture_view(), &[&from_first, &self.blur_bind_groups[0]]);
+            self.blur_pass.draw(encoder, self.targets[0].get_texture_view(), &[&from_second, &self.blur_bind_groups[1]]);
+        }
+
+        let bloom_entries = vec![
+            GraphicsContext::create_texture_view_resource(0, self.targets[0].get_texture_view()),
+            GraphicsContext::create_sampler_resource(1, &resources.sampler),
+        ];
+        let bloom = GraphicsContext::create_bind_group(device, "Bloom Texture", &self.texture_layout, &bloom_entries);
+        let scene = self.composite_pass.create_input_bind_group(device, input, &resources.sampler, &resources.globals);
+        self.composite_pass.draw(encoder, output, &[&scene, &self.params_bind_group, &bloom]);
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any
+    {
+        self
+    }
+}
@@ -0,0 +1,98 @@
+use std::any::Any;
+
+use wgpu::*;
+
+use crate::{abstraction::{graphics_context::GraphicsContext, high_level_abstraction::HighLevelGraphicsContext, prospect_window::ProspectWindow}, prospect_texture::BindableTexture};
+
+use super::{FullscreenPass, PostProcessEffect, PostProcessResources};
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable, Default)]
+struct ColourGradingUniform
+{
+    lut_size : f32,
+    strength : f32,
+    _padding : [f32; 2],
+}
+
+/// Remaps colours through a 3D lookup table stored as a horizontal strip of blue slices.
+///
+/// A LUT of size N is an (N * N) x N image, for example 256x16 for a 16 cell LUT
+pub struct ColourGrading
+{
+    /// 0 leaves the image untouched, 1 applies the LUT fully
+    pub strength : f32,
+    lut_size : u32,
+    pass : FullscreenPass,
+    params_buffer : Buffer,
+    params_bind_group : BindGroup,
+    texture_layout : BindGroupLayout,
+    lut_sampler : Sampler,
+    lut_bind_group : BindGroup,
+}
+
+impl ColourGrading
+{
+    pub fn new<T : BindableTexture>(window : &ProspectWindow, lut : &T, lut_size : u32) -> Self
+    {
+        let device = window.get_device();
+
+        let params_layout = FullscreenPass::create_uniform_layout(device, "Colour Grading Params Layout");
+        let texture_layout = FullscreenPass::create_texture_layout(device, "Colour Grading LUT Layout");
+        let params_buffer = GraphicsContext::create_buffer(device, "Colour Grading Params Buffer", &[ColourGradingUniform::default()], BufferUsages::UNIFORM | BufferUsages::COPY_DST);
+        let params_bind_group = HighLevelGraphicsContext::create_uniform_with_bind_group(device, "Colour Grading Params", &params_buffer, &params_layout);
+
+        let lut_sampler = GraphicsContext::create_sampler("Colour Grading LUT Sampler", device, Some(FilterMode::Linear), Some(FilterMode::Linear));
+        let lut_bind_group = Self::create_lut_bind_group(device, &texture_layout, &lut_sampler, lut.get_texture_view());
+
+        Self
+        {
+            strength : 1.,
+            lut_size,
+            pass : FullscreenPass::new(device, "Colour Grading", include_str!("shaders/colour_grading.wgsl"), window.get_surface_config().format, vec![&params_layout, &texture_layout]),
+            params_buffer,
+            params_bind_group,
+            texture_layout,
+            lut_sampler,
+            lut_bind_group,
+        }
+    }
+
+    fn create_lut_bind_group(device : &Device, layout : &BindGroupLayout, sampler : &Sampler, view : &TextureView) -> BindGroup
+    {
+        let entries = vec![
+            GraphicsContext::create_texture_view_resource(0, view),
+            GraphicsContext::create_sampler_resource(1, sampler),
+        ];
+
+        GraphicsContext::create_bind_group(device, "Colour Grading LUT", layout, &entries)
+    }
+
+    pub fn set_lut<T : BindableTexture>(&mut self, window : &ProspectWindow, lut : &T, lut_size : u32)
+    {
+        self.lut_size = lut_size;
+        self.lut_bind_group = Self::create_lut_bind_group(window.get_device(), &self.texture_layout, &self.lut_sampler, lut.get_texture_view());
+    }
+}
+
+impl PostProcessEffect for ColourGrading
+{
+    fn get_name(&self) -> &str
+    {
+        "Colour Grading"
+    }
+
+    fn apply(&mut self, window : &ProspectWindow, resources : &PostProcessResources, encoder : &mut CommandEncoder, input : &TextureView, output : &TextureView)
+    {
+        let data = ColourGradingUniform { lut_size : self.lut_size as f32, strength : self.strength, _padding : [0.; 2] };
+        GraphicsContext::update_buffer(window.get_queue(), &self.params_buffer, 0, &[data]);
+
+        let input = self.pass.create_input_bind_group(window.get_device(), input, &resources.sampler, &resources.globals);
+        self.pass.draw(encoder, output, &[&input, &self.params_bind_group, &self.lut_bind_group]);
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any
+    {
+        self
+    }
+}
@@ -0,0 +1,74 @@
+use std::any::Any;
+
+use wgpu::*;
+
+use crate::abstraction::{graphics_context::GraphicsContext, high_level_abstraction::HighLevelGraphicsContext, prospect_window::ProspectWindow};
+
+use super::{FullscreenPass, PostProcessEffect, PostProcessResources};
+
+const CUSTOM_PRELUDE : &str = "
+struct CustomEffectParams {
+    values: array<vec4<f32>, 4>,
+}
+
+@group(1) @binding(0)
+var<uniform> params: CustomEffectParams;
+";
+
+/// A user supplied effect.
+///
+/// The source must define `fn fs_main(in: FullscreenOutput) -> @location(0) vec4<f32>`.
+/// Along with everything in `fullscreen.wgsl` it can read `params.values`, four `vec4<f32>`s set from `params`
+pub struct CustomEffect
+{
+    name : String,
+    pub params : [[f32; 4]; 4],
+    pass : FullscreenPass,
+    params_buffer : Buffer,
+    params_bind_group : BindGroup,
+}
+
+impl CustomEffect
+{
+    pub fn new(window : &ProspectWindow, name : &str, src : &str) -> Self
+    {
+        let device = window.get_device();
+
+        let params = [[0.; 4]; 4];
+        let params_layout = FullscreenPass::create_uniform_layout(device, &format!("{} Params Layout", name));
+        let params_buffer = GraphicsContext::create_buffer(device, &format!("{} Params Buffer", name), &[params], BufferUsages::UNIFORM | BufferUsages::COPY_DST);
+        let params_bind_group = HighLevelGraphicsContext::create_uniform_with_bind_group(device, name, &params_buffer, &params_layout);
+
+        let src = format!("{}\n{}", CUSTOM_PRELUDE, src);
+
+        Self
+        {
+            name : name.to_string(),
+            params,
+            pass : FullscreenPass::new(device, name, &src, window.get_surface_config().format, vec![&params_layout]),
+            params_buffer,
+            params_bind_group,
+        }
+    }
+}
+
+impl PostProcessEffect for CustomEffect
+{
+    fn get_name(&self) -> &str
+    {
+        &self.name
+    }
+
+    fn apply(&mut self, window : &ProspectWindow, resources : &PostProcessResources, encoder : &mut CommandEncoder, input : &TextureView, output : &TextureView)
+    {
+        GraphicsContext::update_buffer(window.get_queue(), &self.params_buffer, 0, &[self.params]);
+
+        let input = self.pass.create_input_bind_group(window.get_device(), input, &resources.sampler, &resources.globals);
+        self.pass.draw(encoder, output, &[&input, &self.params_bind_group]);
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any
+    {
+        self
+    }
+}
@@ -0,0 +1,117 @@
+use wgpu::*;
+
+use crate::abstraction::graphics_context::GraphicsContext;
+
+/// A pipeline that draws a single triangle covering the whole target.
+///
+/// The fragment source is appended to `fullscreen.wgsl`, so it can use `FullscreenOutput`, `t_input`, `s_input` and `globals` from group 0
+pub struct FullscreenPass
+{
+    name : String,
+    pipeline : RenderPipeline,
+    input_layout : BindGroupLayout,
+}
+
+impl FullscreenPass
+{
+    pub const PRELUDE : &'static str = include_str!("shaders/fullscreen.wgsl");
+
+    pub fn new(device : &Device, name : &str, fragment_src : &str, format : TextureFormat, extra_layouts : Vec<&BindGroupLayout>) -> Self
+    {
+        let src = format!("{}\n{}", Self::PRELUDE, fragment_src);
+        let module = GraphicsContext::load_shader(name, &src, device);
+
+        let input_layout = Self::create_input_layout(device, name);
+
+        let mut layouts = vec![&input_layout];
+        layouts.extend(extra_layouts);
+        let pipeline_layout = GraphicsContext::create_pipeline_layout(name, device, &layouts);
+
+        let targets = [Some(ColorTargetState {
+            format,
+            blend: Some(BlendState::REPLACE),
+            write_mask: ColorWrites::ALL,
+        })];
+
+        let fragment_state = FragmentState {
+            module: &module,
+            entry_point: "fs_main",
+            targets: &targets,
+        };
+
+        let vertex_state = VertexState {
+            module: &module,
+            entry_point: "vs_fullscreen",
+            buffers: &[],
+        };
+
+        let mut state = GraphicsContext::DEFAULT_PRIMITIVE_STATE;
+        state.cull_mode = None;
+
+        let pipeline = GraphicsContext::create_render_pipeline_without_depth(name, &pipeline_layout, fragment_state, vertex_state, device, state);
+
+        Self
+        {
+            name : name.to_string(),
+            pipeline,
+            input_layout,
+        }
+    }
+
+    fn create_input_layout(device : &Device, name : &str) -> BindGroupLayout
+    {
+        let entries = [
+            GraphicsContext::create_bind_group_layout_entry(0, ShaderStages::FRAGMENT, GraphicsContext::create_texture_binding_type(false, TextureViewDimension::D2, TextureSampleType::Float { filterable: true })),
+            GraphicsContext::create_bind_group_layout_entry(1, ShaderStages::FRAGMENT, GraphicsContext::create_sample_binding_type(SamplerBindingType::Filtering)),
+            GraphicsContext::create_bind_group_layout_entry(2, ShaderStages::FRAGMENT, GraphicsContext::create_uniform_binding_type()),
+        ];
+
+        GraphicsContext::create_bind_group_layout(device, &format!("{} Input Layout", name), &entries)
+    }
+
+    /// Layout for a single uniform buffer at binding 0
+    pub fn create_uniform_layout(device : &Device, label : &str) -> BindGroupLayout
+    {
+        let entries = [
+            GraphicsContext::create_bind_group_layout_entry(0, ShaderStages::FRAGMENT, GraphicsContext::create_uniform_binding_type())
+        ];
+
+        GraphicsContext::create_bind_group_layout(device, label, &entries)
+    }
+
+    /// Layout for a texture at binding 0 and its sampler at binding 1
+    pub fn create_texture_layout(device : &Device, label : &str) -> BindGroupLayout
+    {
+        let entries = [
+            GraphicsContext::create_bind_group_layout_entry(0, ShaderStages::FRAGMENT, GraphicsContext::create_texture_binding_type(false, TextureViewDimension::D2, TextureSampleType::Float { filterable: true })),
+            GraphicsContext::create_bind_group_layout_entry(1, ShaderStages::FRAGMENT, GraphicsContext::create_sample_binding_type(SamplerBindingType::Filtering)),
+        ];
+
+        GraphicsContext::create_bind_group_layout(device, label, &entries)
+    }
+
+    pub fn create_input_bind_group(&self, device : &Device, input : &TextureView, sampler : &Sampler, globals : &Buffer) -> BindGroup
+    {
+        let entries = vec![
+            GraphicsContext::create_texture_view_resource(0, input),
+            GraphicsContext::create_sampler_resource(1, sampler),
+            GraphicsContext::create_bind_group_entry(2, globals.as_entire_binding()),
+        ];
+
+        GraphicsContext::create_bind_group(device, &format!("{} Input", self.name), &self.input_layout, &entries)
+    }
+
+    /// `bind_groups` start at group 0, which should come from `create_input_bind_group`
+    pub fn draw(&self, encoder : &mut CommandEncoder, output : &TextureView, bind_groups : &[&BindGroup])
+    {
+        let mut render_pass = GraphicsContext::begin_render_pass_colour_only(Some((0., 0., 0., 1.)), &self.name, output, encoder);
+        render_pass.set_pipeline(&self.pipeline);
+
+        for (index, bind_group) in bind_groups.iter().enumerate()
+        {
+            render_pass.set_bind_group(index as u32, bind_group, &[]);
+        }
+
+        render_pass.draw(0..3, 0..1);
+    }
+}
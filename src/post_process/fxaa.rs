@@ -0,0 +1,74 @@
+use std::any::Any;
+
+use wgpu::*;
+
+use crate::abstraction::{graphics_context::GraphicsContext, high_level_abstraction::HighLevelGraphicsContext, prospect_window::ProspectWindow};
+
+use super::{FullscreenPass, PostProcessEffect, PostProcessResources};
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable, Default)]
+struct FxaaUniform
+{
+    edge_threshold : f32,
+    edge_threshold_min : f32,
+    subpixel_quality : f32,
+    _padding : f32,
+}
+
+/// Fast approximate anti aliasing, best placed after any effects that add new edges
+pub struct Fxaa
+{
+    /// Minimum local contrast (relative to the brightest neighbour) needed to count as an edge
+    pub edge_threshold : f32,
+    /// Ignores edges in very dark areas
+    pub edge_threshold_min : f32,
+    pub subpixel_quality : f32,
+    pass : FullscreenPass,
+    params_buffer : Buffer,
+    params_bind_group : BindGroup,
+}
+
+impl Fxaa
+{
+    pub fn new(window : &ProspectWindow) -> Self
+    {
+        let device = window.get_device();
+
+        let params_layout = FullscreenPass::create_uniform_layout(device, "FXAA Params Layout");
+        let params_buffer = GraphicsContext::create_buffer(device, "FXAA Params Buffer", &[FxaaUniform::default()], BufferUsages::UNIFORM | BufferUsages::COPY_DST);
+        let params_bind_group = HighLevelGraphicsContext::create_uniform_with_bind_group(device, "FXAA Params", &params_buffer, &params_layout);
+
+        Self
+        {
+            edge_threshold : 0.125,
+            edge_threshold_min : 0.0312,
+            subpixel_quality : 0.75,
+            pass : FullscreenPass::new(device, "FXAA", include_str!("shaders/fxaa.wgsl"), window.get_surface_config().format, vec![&params_layout]),
+            params_buffer,
+            params_bind_group,
+        }
+    }
+}
+
+impl PostProcessEffect for Fxaa
+{
+    fn get_name(&self) -> &str
+    {
+        "FXAA"
+    }
+
+    fn apply(&mut self, window : &ProspectWindow, resources : &PostProcessResources, encoder : &mut CommandEncoder, input : &TextureView, output : &TextureView)
+    {
+        let data = FxaaUniform { edge_threshold : self.edge_threshold, edge_threshold_min : self.edge_threshold_min, subpixel_quality : self.subpixel_quality, _padding : 0. };
+        GraphicsContext::update_buffer(window.get_queue(), &self.params_buffer, 0, &[data]);
+
+        let input = self.pass.create_input_bind_group(window.get_device(), input, &resources.sampler, &resources.globals);
+        self.pass.draw(encoder, output, &[&input, &self.params_bind_group]);
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any
+    {
+        self
+    }
+}
@@ -0,0 +1,15 @@
+mod fullscreen_pass;
+mod post_process_chain;
+mod bloom;
+mod fxaa;
+mod vignette;
+mod colour_grading;
+mod custom_effect;
+
+pub use fullscreen_pass::*;
+pub use post_process_chain::*;
+pub use bloom::*;
+pub use fxaa::*;
+pub use vignette::*;
+pub use colour_grading::*;
+pub use custom_effect::*;
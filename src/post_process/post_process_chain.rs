@@ -0,0 +1,196 @@
+use std::{any::Any, time::SystemTime};
+
+use wgpu::*;
+
+use crate::{abstraction::{graphics_context::GraphicsContext, prospect_window::ProspectWindow}, prospect_framebuffer::ProspectFramebuffer, prospect_texture::BindableTexture};
+
+use super::FullscreenPass;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable, Default)]
+pub struct PostProcessGlobalsUniform
+{
+    resolution : [f32; 2],
+    texel_size : [f32; 2],
+    time : f32,
+    // Due to uniforms requiring 16 byte (4 float) spacing, we need to use a padding field here
+    _padding : [f32; 3],
+}
+
+/// Shared state handed to every effect when it is applied
+pub struct PostProcessResources
+{
+    pub globals : Buffer,
+    pub sampler : Sampler,
+    pub format : TextureFormat,
+    pub size : (u32, u32),
+}
+
+pub trait PostProcessEffect
+{
+    fn get_name(&self) -> &str;
+
+    /// Called when the window size changes so effects can rebuild any targets they own
+    fn resize(&mut self, _window : &ProspectWindow, _width : u32, _height : u32) {}
+
+    /// Read from `input` and write the result into `output`, both are the size of the window
+    fn apply(&mut self, window : &ProspectWindow, resources : &PostProcessResources, encoder : &mut CommandEncoder, input : &TextureView, output : &TextureView);
+
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+struct PostProcessEntry
+{
+    effect : Box<dyn PostProcessEffect>,
+    enabled : bool,
+}
+
+/// Full screen effects applied in order to a scene framebuffer.
+///
+/// Draw the scene into `get_scene_view()` (with `window.get_depth_buffer()` as the depth buffer), then call `apply` with the surface view
+pub struct PostProcessChain
+{
+    scene : ProspectFramebuffer,
+    targets : [ProspectFramebuffer; 2],
+    effects : Vec<PostProcessEntry>,
+    resources : PostProcessResources,
+    copy_pass : FullscreenPass,
+    start_time : SystemTime,
+}
+
+impl PostProcessChain
+{
+    pub fn new(window : &ProspectWindow) -> Self
+    {
+        let device = window.get_device();
+        let format = window.get_surface_config().format;
+        // Made while minimised, the first `process_frame` after the window comes back sizes them properly
+        let size = (window.size.0.max(1), window.size.1.max(1));
+
+        let globals = GraphicsContext::create_buffer(device, "Post Process Globals Buffer", &[PostProcessGlobalsUniform::default()], BufferUsages::UNIFORM | BufferUsages::COPY_DST);
+        let sampler = GraphicsContext::create_sampler("Post Process Sampler", device, Some(FilterMode::Linear), Some(FilterMode::Linear));
+
+        Self
+        {
+            scene : ProspectFramebuffer::new_with_format(device, size.0, size.1, format),
+            targets : Self::create_targets(device, size, format),
+            effects : vec![],
+            resources : PostProcessResources { globals, sampler, format, size },
+            copy_pass : FullscreenPass::new(device, "Post Process Copy", include_str!("shaders/copy.wgsl"), format, vec![]),
+            start_time : SystemTime::now(),
+        }
+    }
+
+    fn create_targets(device : &Device, size : (u32, u32), format : TextureFormat) -> [ProspectFramebuffer; 2]
+    {
+        [
+            ProspectFramebuffer::new_with_format(device, size.0, size.1, format),
+            ProspectFramebuffer::new_with_format(device, size.0, size.1, format),
+        ]
+    }
+
+    /// Returns the index of the effect in the chain
+    pub fn push<E : PostProcessEffect + 'static>(&mut self, effect : E) -> usize
+    {
+        self.effects.push(PostProcessEntry { effect : Box::new(effect), enabled : true });
+        self.effects.len() - 1
+    }
+
+    pub fn set_enabled(&mut self, index : usize, enabled : bool)
+    {
+        if let Some(entry) = self.effects.get_mut(index)
+        {
+            entry.enabled = enabled;
+        }
+    }
+
+    pub fn is_enabled(&self, index : usize) -> bool
+    {
+        self.effects.get(index).map(|entry| entry.enabled).unwrap_or(false)
+    }
+
+    pub fn get_effect_mut<E : PostProcessEffect + 'static>(&mut self, index : usize) -> Option<&mut E>
+    {
+        self.effects.get_mut(index)?.effect.as_any_mut().downcast_mut::<E>()
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.effects.len()
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.effects.is_empty()
+    }
+
+    pub fn get_scene_view(&self) -> &TextureView
+    {
+        self.scene.get_texture_view()
+    }
+
+    pub fn get_scene(&self) -> &ProspectFramebuffer
+    {
+        &self.scene
+    }
+
+    /// Resizes the targets to match the window and updates the globals uniform, call once per frame before drawing the scene
+    pub fn process_frame(&mut self, window : &ProspectWindow)
+    {
+        if window.size != self.resources.size
+        {
+            self.resize(window);
+        }
+
+        let (width, height) = self.resources.size;
+        let data = PostProcessGlobalsUniform
+        {
+            resolution : [width as f32, height as f32],
+            texel_size : [1. / width as f32, 1. / height as f32],
+            time : SystemTime::now().duration_since(self.start_time).unwrap_or_default().as_secs_f32(),
+            _padding : [0.; 3],
+        };
+        GraphicsContext::update_buffer(window.get_queue(), &self.resources.globals, 0, &[data]);
+    }
+
+    /// Keeps the old targets while the window is minimised, zero sized textures aren't allowed
+    fn resize(&mut self, window : &ProspectWindow)
+    {
+        let device = window.get_device();
+        let size = window.size;
+        if size.0 == 0 || size.1 == 0
+        {
+            return;
+        }
+
+        self.resources.size = size;
+        self.scene = ProspectFramebuffer::new_with_format(device, size.0, size.1, self.resources.format);
+        self.targets = Self::create_targets(device, size, self.resources.format);
+
+        for entry in &mut self.effects
+        {
+            entry.effect.resize(window, size.0, size.1);
+        }
+    }
+
+    /// Runs every enabled effect, ping-ponging between the internal targets and writing the last one into `output`
+    pub fn apply(&mut self, window : &ProspectWindow, encoder : &mut CommandEncoder, output : &TextureView)
+    {
+        let enabled = self.effects.iter().filter(|entry| entry.enabled).count();
+
+        if enabled == 0
+        {
+            let input = self.copy_pass.create_input_bind_group(window.get_device(), self.scene.get_texture_view(), &self.resources.sampler, &self.resources.globals);
+            self.copy_pass.draw(encoder, output, &[&input]);
+            return;
+        }
+
+        let mut input = self.scene.get_texture_view();
+        for (i, entry) in self.effects.iter_mut().filter(|entry| entry.enabled).enumerate()
+        {
+            let target = if i + 1 == enabled { output } else { self.targets[i % 2].get_texture_view() };
+            entry.effect.apply(window, &self.resources, encoder, input, target);
+            input = target;
+        }
+    }
+}
@@ -0,0 +1,23 @@
+struct BlurParams {
+    direction: vec2<f32>,
+    _padding: vec2<f32>,
+}
+
+@group(1) @binding(0)
+var<uniform> params: BlurParams;
+
+// 9 tap gaussian using linear sampling, so only 5 fetches are needed
+@fragment
+fn fs_main(in: FullscreenOutput) -> @location(0) vec4<f32>
+{
+    let texel = 1.0 / vec2<f32>(textureDimensions(t_input));
+    let step = params.direction * texel;
+
+    var result = textureSample(t_input, s_input, in.uv).rgb * 0.2270270270;
+    result += textureSample(t_input, s_input, in.uv + step * 1.3846153846).rgb * 0.3162162162;
+    result += textureSample(t_input, s_input, in.uv - step * 1.3846153846).rgb * 0.3162162162;
+    result += textureSample(t_input, s_input, in.uv + step * 3.2307692308).rgb * 0.0702702703;
+    result += textureSample(t_input, s_input, in.uv - step * 3.2307692308).rgb * 0.0702702703;
+
+    return vec4<f32>(result, 1.0);
+}
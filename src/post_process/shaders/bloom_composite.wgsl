@@ -0,0 +1,22 @@
+struct BloomParams {
+    threshold: f32,
+    soft_knee: f32,
+    intensity: f32,
+    _padding: f32,
+}
+
+@group(1) @binding(0)
+var<uniform> params: BloomParams;
+
+@group(2) @binding(0)
+var t_bloom: texture_2d<f32>;
+@group(2) @binding(1)
+var s_bloom: sampler;
+
+@fragment
+fn fs_main(in: FullscreenOutput) -> @location(0) vec4<f32>
+{
+    let scene = textureSample(t_input, s_input, in.uv);
+    let bloom = textureSample(t_bloom, s_bloom, in.uv).rgb;
+    return vec4<f32>(scene.rgb + bloom * params.intensity, scene.a);
+}
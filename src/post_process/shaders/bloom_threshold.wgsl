@@ -0,0 +1,24 @@
+struct BloomParams {
+    threshold: f32,
+    soft_knee: f32,
+    intensity: f32,
+    _padding: f32,
+}
+
+@group(1) @binding(0)
+var<uniform> params: BloomParams;
+
+@fragment
+fn fs_main(in: FullscreenOutput) -> @location(0) vec4<f32>
+{
+    let colour = textureSample(t_input, s_input, in.uv).rgb;
+    let brightness = max(colour.r, max(colour.g, colour.b));
+
+    // Quadratic soft knee so pixels just under the threshold fade in instead of popping
+    let knee = params.threshold * params.soft_knee + 0.00001;
+    var soft = clamp(brightness - params.threshold + knee, 0.0, 2.0 * knee);
+    soft = soft * soft / (4.0 * knee);
+
+    let contribution = max(soft, brightness - params.threshold) / max(brightness, 0.00001);
+    return vec4<f32>(colour * contribution, 1.0);
+}
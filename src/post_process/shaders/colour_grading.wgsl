@@ -0,0 +1,38 @@
+struct ColourGradingParams {
+    // Number of cells along each axis of the LUT, e.g 16 for a 256x16 strip
+    lut_size: f32,
+    strength: f32,
+    _padding: vec2<f32>,
+}
+
+@group(1) @binding(0)
+var<uniform> params: ColourGradingParams;
+
+@group(2) @binding(0)
+var t_lut: texture_2d<f32>;
+@group(2) @binding(1)
+var s_lut: sampler;
+
+// The LUT is laid out as a horizontal strip of blue slices, each slice maps red on x and green on y
+fn sample_slice(slice: f32, rg: vec2<f32>) -> vec3<f32>
+{
+    let size = params.lut_size;
+    let texel = (rg * (size - 1.0) + 0.5) / size;
+    let uv = vec2<f32>((slice + texel.x) / size, texel.y);
+    return textureSampleLevel(t_lut, s_lut, uv, 0.0).rgb;
+}
+
+@fragment
+fn fs_main(in: FullscreenOutput) -> @location(0) vec4<f32>
+{
+    let colour = textureSample(t_input, s_input, in.uv);
+    let graded_input = clamp(colour.rgb, vec3<f32>(0.0), vec3<f32>(1.0));
+
+    let blue = graded_input.b * (params.lut_size - 1.0);
+    let lower = floor(blue);
+    let upper = min(lower + 1.0, params.lut_size - 1.0);
+
+    let graded = mix(sample_slice(lower, graded_input.rg), sample_slice(upper, graded_input.rg), blue - lower);
+
+    return vec4<f32>(mix(colour.rgb, graded, params.strength), colour.a);
+}
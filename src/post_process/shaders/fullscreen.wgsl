@@ -0,0 +1,31 @@
+// Shared prelude for every post processing pass, effect shaders are appended to this
+
+struct FullscreenOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+struct PostProcessGlobals {
+    resolution: vec2<f32>,
+    texel_size: vec2<f32>,
+    time: f32,
+}
+
+@group(0) @binding(0)
+var t_input: texture_2d<f32>;
+@group(0) @binding(1)
+var s_input: sampler;
+@group(0) @binding(2)
+var<uniform> globals: PostProcessGlobals;
+
+// One triangle that covers the whole screen, no vertex buffer required
+@vertex
+fn vs_fullscreen(@builtin(vertex_index) index: u32) -> FullscreenOutput
+{
+    var out: FullscreenOutput;
+    let x = f32((index << 1u) & 2u);
+    let y = f32(index & 2u);
+    out.uv = vec2<f32>(x, y);
+    out.clip_position = vec4<f32>(x * 2.0 - 1.0, 1.0 - y * 2.0, 0.0, 1.0);
+    return out;
+}
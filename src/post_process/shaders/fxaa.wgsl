@@ -0,0 +1,154 @@
+struct FxaaParams {
+    edge_threshold: f32,
+    edge_threshold_min: f32,
+    subpixel_quality: f32,
+    _padding: f32,
+}
+
+@group(1) @binding(0)
+var<uniform> params: FxaaParams;
+
+fn luma(colour: vec3<f32>) -> f32
+{
+    return dot(colour, vec3<f32>(0.299, 0.587, 0.114));
+}
+
+fn sample_luma(uv: vec2<f32>) -> f32
+{
+    return luma(textureSampleLevel(t_input, s_input, uv, 0.0).rgb);
+}
+
+// Simplified FXAA 3.11 (console quality), searches a fixed number of steps along the edge
+@fragment
+fn fs_main(in: FullscreenOutput) -> @location(0) vec4<f32>
+{
+    let texel = globals.texel_size;
+    let centre = textureSampleLevel(t_input, s_input, in.uv, 0.0);
+
+    let luma_m = luma(centre.rgb);
+    let luma_n = sample_luma(in.uv + vec2<f32>(0.0, -texel.y));
+    let luma_s = sample_luma(in.uv + vec2<f32>(0.0, texel.y));
+    let luma_e = sample_luma(in.uv + vec2<f32>(texel.x, 0.0));
+    let luma_w = sample_luma(in.uv + vec2<f32>(-texel.x, 0.0));
+
+    let luma_min = min(luma_m, min(min(luma_n, luma_s), min(luma_e, luma_w)));
+    let luma_max = max(luma_m, max(max(luma_n, luma_s), max(luma_e, luma_w)));
+    let luma_range = luma_max - luma_min;
+
+    if (luma_range < max(params.edge_threshold_min, luma_max * params.edge_threshold))
+    {
+        return centre;
+    }
+
+    let luma_nw = sample_luma(in.uv + vec2<f32>(-texel.x, -texel.y));
+    let luma_ne = sample_luma(in.uv + vec2<f32>(texel.x, -texel.y));
+    let luma_sw = sample_luma(in.uv + vec2<f32>(-texel.x, texel.y));
+    let luma_se = sample_luma(in.uv + vec2<f32>(texel.x, texel.y));
+
+    let edge_horizontal = abs(luma_nw + luma_ne - 2.0 * luma_n) + 2.0 * abs(luma_w + luma_e - 2.0 * luma_m) + abs(luma_sw + luma_se - 2.0 * luma_s);
+    let edge_vertical = abs(luma_nw + luma_sw - 2.0 * luma_w) + 2.0 * abs(luma_n + luma_s - 2.0 * luma_m) + abs(luma_ne + luma_se - 2.0 * luma_e);
+    let is_horizontal = edge_horizontal >= edge_vertical;
+
+    var luma_positive = luma_e;
+    var luma_negative = luma_w;
+    var step_length = texel.x;
+    if (is_horizontal)
+    {
+        luma_positive = luma_s;
+        luma_negative = luma_n;
+        step_length = texel.y;
+    }
+
+    let gradient_positive = abs(luma_positive - luma_m);
+    let gradient_negative = abs(luma_negative - luma_m);
+    var luma_local = 0.5 * (luma_positive + luma_m);
+    let gradient_scaled = 0.25 * max(gradient_positive, gradient_negative);
+    if (gradient_negative >= gradient_positive)
+    {
+        step_length = -step_length;
+        luma_local = 0.5 * (luma_negative + luma_m);
+    }
+
+    var edge_uv = in.uv;
+    var search_step = vec2<f32>(texel.x, 0.0);
+    if (is_horizontal)
+    {
+        edge_uv.y += step_length * 0.5;
+    } else
+    {
+        edge_uv.x += step_length * 0.5;
+        search_step = vec2<f32>(0.0, texel.y);
+    }
+
+    var uv_positive = edge_uv + search_step;
+    var uv_negative = edge_uv - search_step;
+    var delta_positive = sample_luma(uv_positive) - luma_local;
+    var delta_negative = sample_luma(uv_negative) - luma_local;
+    var done_positive = abs(delta_positive) >= gradient_scaled;
+    var done_negative = abs(delta_negative) >= gradient_scaled;
+
+    for (var i = 0; i < 10; i++)
+    {
+        if (done_positive && done_negative)
+        {
+            break;
+        }
+
+        if (!done_positive)
+        {
+            uv_positive += search_step * 1.5;
+            delta_positive = sample_luma(uv_positive) - luma_local;
+            done_positive = abs(delta_positive) >= gradient_scaled;
+        }
+
+        if (!done_negative)
+        {
+            uv_negative -= search_step * 1.5;
+            delta_negative = sample_luma(uv_negative) - luma_local;
+            done_negative = abs(delta_negative) >= gradient_scaled;
+        }
+    }
+
+    var distance_positive = uv_positive.x - in.uv.x;
+    var distance_negative = in.uv.x - uv_negative.x;
+    if (!is_horizontal)
+    {
+        distance_positive = uv_positive.y - in.uv.y;
+        distance_negative = in.uv.y - uv_negative.y;
+    }
+
+    let closest = min(distance_positive, distance_negative);
+    let edge_length = distance_positive + distance_negative;
+    var pixel_offset = -closest / edge_length + 0.5;
+
+    // Only blend if the end of the edge we found has the opposite variation to the centre
+    let centre_smaller = (luma_m - luma_local) < 0.0;
+    var closest_delta = delta_negative;
+    if (distance_positive < distance_negative)
+    {
+        closest_delta = delta_positive;
+    }
+    if ((closest_delta < 0.0) == centre_smaller)
+    {
+        pixel_offset = 0.0;
+    }
+
+    // Sub pixel anti aliasing for single pixel features
+    let luma_average = (1.0 / 12.0) * (2.0 * (luma_n + luma_s + luma_e + luma_w) + luma_nw + luma_ne + luma_sw + luma_se);
+    let subpixel_offset = clamp(abs(luma_average - luma_m) / luma_range, 0.0, 1.0);
+    let subpixel_smooth = (-2.0 * subpixel_offset + 3.0) * subpixel_offset * subpixel_offset;
+    let subpixel_final = subpixel_smooth * subpixel_smooth * params.subpixel_quality;
+
+    let final_offset = max(pixel_offset, subpixel_final);
+
+    var final_uv = in.uv;
+    if (is_horizontal)
+    {
+        final_uv.y += final_offset * step_length;
+    } else
+    {
+        final_uv.x += final_offset * step_length;
+    }
+
+    return textureSampleLevel(t_input, s_input, final_uv, 0.0);
+}
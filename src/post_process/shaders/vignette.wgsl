@@ -0,0 +1,25 @@
+struct VignetteParams {
+    colour: vec4<f32>,
+    intensity: f32,
+    radius: f32,
+    smoothness: f32,
+}
+
+@group(1) @binding(0)
+var<uniform> params: VignetteParams;
+
+@fragment
+fn fs_main(in: FullscreenOutput) -> @location(0) vec4<f32>
+{
+    let colour = textureSample(t_input, s_input, in.uv);
+
+    // Keep the vignette circular regardless of aspect ratio
+    var offset = in.uv - vec2<f32>(0.5);
+    offset.x *= globals.resolution.x / globals.resolution.y;
+
+    let distance = length(offset);
+    let amount = 1.0 - smoothstep(params.radius - params.smoothness, params.radius, distance);
+    let factor = mix(1.0, amount, params.intensity);
+
+    return vec4<f32>(mix(params.colour.rgb, colour.rgb, factor), colour.a);
+}
@@ -0,0 +1,84 @@
+use std::any::Any;
+
+use vecto_rs::linear::{Vector, VectorTrait};
+use wgpu::*;
+
+use crate::abstraction::{graphics_context::GraphicsContext, high_level_abstraction::HighLevelGraphicsContext, prospect_window::ProspectWindow};
+
+use super::{FullscreenPass, PostProcessEffect, PostProcessResources};
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable, Default)]
+struct VignetteUniform
+{
+    colour : [f32; 4],
+    intensity : f32,
+    radius : f32,
+    smoothness : f32,
+    _padding : f32,
+}
+
+pub struct Vignette
+{
+    pub colour : Vector,
+    /// 0 disables the effect, 1 fully replaces the edges with `colour`
+    pub intensity : f32,
+    /// Distance from the centre where the darkening ends, 0.5 touches the screen edges
+    pub radius : f32,
+    pub smoothness : f32,
+    pass : FullscreenPass,
+    params_buffer : Buffer,
+    params_bind_group : BindGroup,
+}
+
+impl Vignette
+{
+    pub fn new(window : &ProspectWindow) -> Self
+    {
+        let device = window.get_device();
+
+        let params_layout = FullscreenPass::create_uniform_layout(device, "Vignette Params Layout");
+        let params_buffer = GraphicsContext::create_buffer(device, "Vignette Params Buffer", &[VignetteUniform::default()], BufferUsages::UNIFORM | BufferUsages::COPY_DST);
+        let params_bind_group = HighLevelGraphicsContext::create_uniform_with_bind_group(device, "Vignette Params", &params_buffer, &params_layout);
+
+        Self
+        {
+            colour : Vector::new3(0., 0., 0.),
+            intensity : 0.75,
+            radius : 0.75,
+            smoothness : 0.45,
+            pass : FullscreenPass::new(device, "Vignette", include_str!("shaders/vignette.wgsl"), window.get_surface_config().format, vec![&params_layout]),
+            params_buffer,
+            params_bind_group,
+        }
+    }
+}
+
+impl PostProcessEffect for Vignette
+{
+    fn get_name(&self) -> &str
+    {
+        "Vignette"
+    }
+
+    fn apply(&mut self, window : &ProspectWindow, resources : &PostProcessResources, encoder : &mut CommandEncoder, input : &TextureView, output : &TextureView)
+    {
+        let data = VignetteUniform
+        {
+            colour : [self.colour.x, self.colour.y, self.colour.z, 1.],
+            intensity : self.intensity,
+            radius : self.radius,
+            smoothness : self.smoothness,
+            _padding : 0.,
+        };
+        GraphicsContext::update_buffer(window.get_queue(), &self.params_buffer, 0, &[data]);
+
+        let input = self.pass.create_input_bind_group(window.get_device(), input, &resources.sampler, &resources.globals);
+        self.pass.draw(encoder, output, &[&input, &self.params_bind_group]);
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any
+    {
+        self
+    }
+}
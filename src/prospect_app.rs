@@ -1,5 +1,5 @@
 use vecto_rs::linear::Vector;
-use winit::event::{VirtualKeyCode, ElementState, MouseButton};
+use winit::event::{VirtualKeyCode, ElementState, MouseButton, MouseScrollDelta, ModifiersState};
 
 use crate::{abstraction::prospect_window::ProspectWindow};
 
@@ -10,7 +10,11 @@ pub enum ProspectEvent
     CursorMoveEvent(Vector),
     CursorDelta(Vector),
     Focused(bool),
-    CursorClicked(ElementState, MouseButton)
+    CursorClicked(ElementState, MouseButton),
+    /// Typed text, already taking the keyboard layout and modifiers into account
+    ReceivedCharacter(char),
+    MouseWheel(MouseScrollDelta),
+    ModifiersChanged(ModifiersState),
 }
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -80,6 +80,15 @@ impl ProspectCamera {
         }
     }
 
+    /// An orthographic camera showing `left` to `right` and `bottom` to `top` of the z = 0 plane, for 2D such as a `SpriteBatch`
+    pub fn new_orthographic(device : &Device, left : f32, right : f32, bottom : f32, top : f32) -> ProspectCamera {
+        let mut camera = Self::new(device);
+        camera.projection_type = ProjectionType::Orthographic(right, left, top, bottom);
+        camera.znear = -1.;
+        camera.zfar = 1.;
+        camera
+    }
+
     pub fn new_from(device : &Device, camera : &ProspectCamera) -> ProspectCamera {
         let uniform = CamUniform::new();
         let buffer = GraphicsContext::create_buffer(&device, "Camera View Uniform Buffer", &[uniform], BufferUsages::UNIFORM | BufferUsages::COPY_DST);
@@ -132,32 +141,33 @@ impl ProspectCamera {
 
     pub fn generate_projection_matrix(&self, width : f32, height : f32) -> Mat4
     {
-        match self.projection_type
+        self.generate_matrix(width, height, true)
+    }
+
+    /// The projection and rotation without the camera's position, for things infinitely far away such as a skybox
+    pub fn generate_rotation_matrix(&self, width : f32, height : f32) -> Mat4
+    {
+        self.generate_matrix(width, height, false)
+    }
+
+    fn generate_matrix(&self, width : f32, height : f32, translate : bool) -> Mat4
+    {
+        let mut view = Mat4::identity();
+        view.rotate(-self.rotation.x, Vector::new3(1., 0., 0.));
+        view.rotate(-self.rotation.y, Vector::new3(0., 1., 0.));
+        view.rotate(-self.rotation.z, Vector::new3(0., 0., 1.));
+        if translate
         {
-            ProjectionType::Perspective(fov) =>
-            {
-                let mut view = Mat4::identity();
-                view.rotate(-self.rotation.x, Vector::new3(1., 0., 0.));
-                view.rotate(-self.rotation.y, Vector::new3(0., 1., 0.));
-                view.rotate(-self.rotation.z, Vector::new3(0., 0., 1.));
-                view.translate(self.eye * -1.);
-                let projection = Mat4::new_perspective_matrix(width, height, fov, self.znear, self.zfar);
-                let cam_matrix = OPENGL_TO_WGPU_MATRIX * projection * view;
-                cam_matrix
-            },
-            ProjectionType::Orthographic(right, left, top, bottom) =>
-            {
-                let mut view = Mat4::identity();
-                view.rotate(-self.rotation.x, Vector::new3(1., 0., 0.));
-                view.rotate(-self.rotation.y, Vector::new3(0., 1., 0.));
-                view.rotate(-self.rotation.z, Vector::new3(0., 0., 1.));
-                view.translate(self.eye * -1.);
-                let projection = Mat4::new_orthographic_matrix(bottom, top, left, right, self.znear, self.zfar);
-                let cam_matrix = OPENGL_TO_WGPU_MATRIX * projection * view;
-                cam_matrix
-            }
+            view.translate(self.eye * -1.);
         }
 
+        let projection = match self.projection_type
+        {
+            ProjectionType::Perspective(fov) => Mat4::new_perspective_matrix(width, height, fov, self.znear, self.zfar),
+            ProjectionType::Orthographic(right, left, top, bottom) => Mat4::new_orthographic_matrix(bottom, top, left, right, self.znear, self.zfar),
+        };
+
+        OPENGL_TO_WGPU_MATRIX * projection * view
     }
 
     fn create_uniform(buffer : &Buffer, device : &Device) -> (BindGroup, BindGroupLayout)
@@ -0,0 +1,185 @@
+use image::{RgbaImage, imageops};
+use wgpu::*;
+
+use crate::{abstraction::{graphics_context::GraphicsContext, prospect_window::ProspectWindow}, prospect_texture::BindableTexture};
+
+/// How the six faces are arranged in a single image
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CubemapLayout
+{
+    /// 4x3 faces, -X +Z +X -Z across the middle row with +Y above and -Y below +Z
+    HorizontalCross,
+    /// 3x4 faces, -X +Z +X across the second row with +Y above, -Y below and -Z (upside down) at the bottom
+    VerticalCross,
+    /// 6x1 faces in the order +X -X +Y -Y +Z -Z
+    HorizontalStrip,
+    /// 1x6 faces in the order +X -X +Y -Y +Z -Z
+    VerticalStrip,
+}
+
+impl CubemapLayout
+{
+    /// Works the layout out from the aspect ratio
+    pub fn detect(width : u32, height : u32) -> Option<Self>
+    {
+        if width * 3 == height * 4
+        {
+            Some(Self::HorizontalCross)
+        } else if width * 4 == height * 3
+        {
+            Some(Self::VerticalCross)
+        } else if width == height * 6
+        {
+            Some(Self::HorizontalStrip)
+        } else if width * 6 == height
+        {
+            Some(Self::VerticalStrip)
+        } else
+        {
+            None
+        }
+    }
+
+    /// Splits `image` into faces in the order +X, -X, +Y, -Y, +Z, -Z
+    pub fn extract_faces(&self, image : &RgbaImage) -> [RgbaImage; 6]
+    {
+        let face_size = match self
+        {
+            Self::HorizontalCross => image.width() / 4,
+            Self::VerticalCross => image.width() / 3,
+            Self::HorizontalStrip => image.height(),
+            Self::VerticalStrip => image.width(),
+        };
+
+        // Grid cell of each face, in the order +X, -X, +Y, -Y, +Z, -Z
+        let cells : [(u32, u32); 6] = match self
+        {
+            Self::HorizontalCross => [(2, 1), (0, 1), (1, 0), (1, 2), (1, 1), (3, 1)],
+            Self::VerticalCross => [(2, 1), (0, 1), (1, 0), (1, 2), (1, 1), (1, 3)],
+            Self::HorizontalStrip => [(0, 0), (1, 0), (2, 0), (3, 0), (4, 0), (5, 0)],
+            Self::VerticalStrip => [(0, 0), (0, 1), (0, 2), (0, 3), (0, 4), (0, 5)],
+        };
+
+        let mut faces = cells.map(|(x, y)| imageops::crop_imm(image, x * face_size, y * face_size, face_size, face_size).to_image());
+        if *self == Self::VerticalCross
+        {
+            // The back face of a vertical cross is stored upside down
+            faces[5] = imageops::rotate180(&faces[5]);
+        }
+        faces
+    }
+}
+
+/// A cube texture, bind it with a `TextureViewDimension::Cube` binding, such as `ProspectSkybox`
+pub struct ProspectCubemap
+{
+    name : String,
+    texture : Texture,
+    view : TextureView,
+}
+
+impl ProspectCubemap
+{
+    /// Faces in the order +X, -X, +Y, -Y, +Z, -Z, each square and the same size
+    pub fn from_faces(name : &str, faces : [RgbaImage; 6], window : &ProspectWindow) -> Result<Self, ()>
+    {
+        Self::from_faces_with_format(name, faces, TextureFormat::Rgba8UnormSrgb, window)
+    }
+
+    pub fn from_faces_with_format(name : &str, faces : [RgbaImage; 6], format : TextureFormat, window : &ProspectWindow) -> Result<Self, ()>
+    {
+        let texture = GraphicsContext::create_cubemap_from_images(name, &faces, window.get_device(), window.get_queue(), format, Some(window.get_mipmap_generator()))?;
+
+        Ok(Self
+        {
+            name : name.to_string(),
+            view : GraphicsContext::create_cube_texture_view(&texture),
+            texture,
+        })
+    }
+
+    /// Six encoded images (png, jpeg) in the order +X, -X, +Y, -Y, +Z, -Z
+    pub fn from_face_bytes(name : &str, faces : [&[u8]; 6], window : &ProspectWindow) -> Result<Self, ()>
+    {
+        let mut images = Vec::with_capacity(6);
+        for face in faces
+        {
+            images.push(image::load_from_memory(face).map_err(|_| ())?.to_rgba8());
+        }
+
+        Self::from_faces(name, images.try_into().map_err(|_| ())?, window)
+    }
+
+    pub fn from_image(name : &str, image : &RgbaImage, layout : CubemapLayout, window : &ProspectWindow) -> Result<Self, ()>
+    {
+        Self::from_faces(name, layout.extract_faces(image), window)
+    }
+
+    /// A single encoded cross or strip image, the layout is detected from its aspect ratio
+    pub fn from_bytes(name : &str, bytes : &[u8], window : &ProspectWindow) -> Result<Self, ()>
+    {
+        let image = image::load_from_memory(bytes).map_err(|_| ())?.to_rgba8();
+        let layout = CubemapLayout::detect(image.width(), image.height()).ok_or(())?;
+        Self::from_image(name, &image, layout, window)
+    }
+
+    /// A vertical gradient from `top` through `horizon` to `bottom`, a stand in sky for when there's no cubemap to hand
+    pub fn from_gradient(name : &str, size : u32, top : [u8; 4], horizon : [u8; 4], bottom : [u8; 4], window : &ProspectWindow) -> Result<Self, ()>
+    {
+        let mix = |a : [u8; 4], b : [u8; 4], t : f32| [0, 1, 2, 3].map(|i| (a[i] as f32 + (b[i] as f32 - a[i] as f32) * t).round() as u8);
+
+        let faces = [0, 1, 2, 3, 4, 5].map(|face| RgbaImage::from_fn(size, size, |x, y|
+        {
+            let u = (x as f32 + 0.5) / size as f32 * 2. - 1.;
+            let v = (y as f32 + 0.5) / size as f32 * 2. - 1.;
+
+            // Only the vertical part of the direction through this texel matters, +Y and -Y are faces 2 and 3
+            let up = match face
+            {
+                2 => 1.,
+                3 => -1.,
+                _ => -v,
+            };
+            let height = up / (u * u + v * v + 1.).sqrt();
+
+            let colour = if height >= 0. { mix(horizon, top, height) } else { mix(horizon, bottom, -height) };
+            image::Rgba(colour)
+        }));
+
+        Self::from_faces(name, faces, window)
+    }
+
+    /// Wraps a texture with six array layers, such as one rendered by `ProspectEnvironment`
+    pub fn from_texture(name : &str, texture : Texture) -> Self
+    {
+        Self
+        {
+            name : name.to_string(),
+            view : GraphicsContext::create_cube_texture_view(&texture),
+            texture,
+        }
+    }
+
+    pub fn get_texture(&self) -> &Texture
+    {
+        &self.texture
+    }
+
+    pub fn get_name(&self) -> String
+    {
+        self.name.clone()
+    }
+}
+
+impl BindableTexture for ProspectCubemap
+{
+    fn get_texture_view(&self) -> &TextureView
+    {
+        &self.view
+    }
+
+    fn get_name(&self) -> String
+    {
+        self.name.clone()
+    }
+}
@@ -0,0 +1,344 @@
+use std::f32::consts::TAU;
+
+use vecto_rs::{linear::{Vector, VectorTrait}, trig::to_radians};
+use wgpu::*;
+
+use crate::{abstraction::{graphics_context::GraphicsContext, prospect_window::ProspectWindow, vertex::VertexLayout}, prospect_camera::{ProjectionType, ProspectCamera}, prospect_transform::Transform, utils::math::{add, cross, length, normalize, scale, sub}};
+
+/// Line segments used for each circle of a wire sphere
+const CIRCLE_SEGMENTS : u32 = 32;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable, VertexLayout)]
+struct DebugVertex
+{
+    position : [f32; 3],
+    colour : [f32; 4],
+}
+
+fn to_array(vector : Vector) -> [f32; 3]
+{
+    [vector.x, vector.y, vector.z]
+}
+
+/// Immediate mode lines for visualising bounds, lights, cameras and the like.
+///
+/// Shapes are queued every frame, uploaded by `process_frame` and drawn by `draw` in the same render pass as the scene,
+/// after which the queue starts empty again. Whether shapes are hidden behind the scene is set with `set_depth_test`
+/// and applies to everything queued after it
+pub struct DebugDraw
+{
+    depth_pipeline : RenderPipeline,
+    overlay_pipeline : RenderPipeline,
+    vertex_buffer : Buffer,
+    capacity : u32,
+    depth_tested : Vec<DebugVertex>,
+    overlay : Vec<DebugVertex>,
+    depth_test : bool,
+    /// Vertices from the last `process_frame`, depth tested ones first
+    depth_count : u32,
+    overlay_count : u32,
+}
+
+impl DebugDraw
+{
+    /// `camera_layout` is `ProspectCamera::get_layout` of the camera the lines are drawn with
+    pub fn new(window : &ProspectWindow, camera_layout : &BindGroupLayout) -> Self
+    {
+        let device = window.get_device();
+        let module = GraphicsContext::load_shader("Debug Draw Shader", include_str!("shaders/debug.wgsl"), device);
+        let pipeline_layout = GraphicsContext::create_pipeline_layout("Debug Draw Pipeline Layout", device, &vec![camera_layout]);
+
+        let targets = [Some(ColorTargetState {
+            format: window.get_surface_config().format,
+            blend: Some(BlendState::ALPHA_BLENDING),
+            write_mask: ColorWrites::ALL,
+        })];
+
+        let mut state = GraphicsContext::DEFAULT_PRIMITIVE_STATE;
+        state.topology = PrimitiveTopology::LineList;
+        state.cull_mode = None;
+
+        let create_pipeline = |name : &str, depth_compare : CompareFunction|
+        {
+            let fragment_state = FragmentState {
+                module: &module,
+                entry_point: "fs_main",
+                targets: &targets,
+            };
+
+            let vertex_state = VertexState {
+                module: &module,
+                entry_point: "vs_main",
+                buffers: &[DebugVertex::VERTEX_BUFFER_LAYOUT],
+            };
+
+            // Lines never write depth, so they can't hide each other or anything drawn after them
+            let depth_stencil = DepthStencilState {
+                format: GraphicsContext::DEPTH_FORMAT,
+                depth_write_enabled: false,
+                depth_compare,
+                stencil: StencilState::default(),
+                bias: DepthBiasState::default(),
+            };
+
+            GraphicsContext::create_render_pipeline_with_depth_stencil(name, &pipeline_layout, fragment_state, vertex_state, device, state, depth_stencil)
+        };
+
+        let depth_pipeline = create_pipeline("Debug Draw Depth Tested Pipeline", CompareFunction::LessEqual);
+        let overlay_pipeline = create_pipeline("Debug Draw Overlay Pipeline", CompareFunction::Always);
+
+        let capacity = 1024;
+
+        Self
+        {
+            depth_pipeline,
+            overlay_pipeline,
+            vertex_buffer : Self::create_buffer(device, capacity),
+            capacity,
+            depth_tested : Vec::new(),
+            overlay : Vec::new(),
+            depth_test : true,
+            depth_count : 0,
+            overlay_count : 0,
+        }
+    }
+
+    fn create_buffer(device : &Device, capacity : u32) -> Buffer
+    {
+        let vertices = vec![DebugVertex { position : [0.; 3], colour : [0.; 4] }; capacity as usize];
+        GraphicsContext::create_buffer(device, "Debug Draw Vertex Buffer", &vertices, BufferUsages::VERTEX | BufferUsages::COPY_DST)
+    }
+
+    /// True by default, false draws shapes queued from now on over the top of everything
+    pub fn set_depth_test(&mut self, depth_test : bool)
+    {
+        self.depth_test = depth_test;
+    }
+
+    pub fn get_depth_test(&self) -> bool
+    {
+        self.depth_test
+    }
+
+    fn push_line(&mut self, start : [f32; 3], end : [f32; 3], colour : [f32; 4])
+    {
+        let vertices = if self.depth_test { &mut self.depth_tested } else { &mut self.overlay };
+        vertices.push(DebugVertex { position : start, colour });
+        vertices.push(DebugVertex { position : end, colour });
+    }
+
+    pub fn line(&mut self, start : Vector, end : Vector, colour : [f32; 4])
+    {
+        self.push_line(to_array(start), to_array(end), colour);
+    }
+
+    /// An axis aligned box between two opposite corners
+    pub fn aabb(&mut self, min : Vector, max : Vector, colour : [f32; 4])
+    {
+        let (min, max) = (to_array(min), to_array(max));
+        let corner = |index : usize| [
+            if index & 1 == 0 { min[0] } else { max[0] },
+            if index & 2 == 0 { min[1] } else { max[1] },
+            if index & 4 == 0 { min[2] } else { max[2] },
+        ];
+
+        // Each corner joins to the corners one bit away from it
+        for index in 0..8
+        {
+            for bit in [1, 2, 4]
+            {
+                if index & bit == 0
+                {
+                    self.push_line(corner(index), corner(index | bit), colour);
+                }
+            }
+        }
+    }
+
+    /// A circle around `centre` in the plane of the two (perpendicular, unit length) axes
+    fn push_circle(&mut self, centre : [f32; 3], axis_a : [f32; 3], axis_b : [f32; 3], radius : f32, colour : [f32; 4])
+    {
+        let point = |segment : u32|
+        {
+            let (sin, cos) = (segment as f32 / CIRCLE_SEGMENTS as f32 * TAU).sin_cos();
+            add(centre, add(scale(axis_a, cos * radius), scale(axis_b, sin * radius)))
+        };
+
+        for segment in 0..CIRCLE_SEGMENTS
+        {
+            self.push_line(point(segment), point(segment + 1), colour);
+        }
+    }
+
+    /// A circle facing along `normal`
+    pub fn circle(&mut self, centre : Vector, normal : Vector, radius : f32, colour : [f32; 4])
+    {
+        let normal = normalize(to_array(normal)).unwrap_or([0., 1., 0.]);
+        let reference = if normal[1].abs() < 0.99 { [0., 1., 0.] } else { [1., 0., 0.] };
+        let axis_a = normalize(cross(normal, reference)).unwrap_or([1., 0., 0.]);
+        let axis_b = cross(normal, axis_a);
+        self.push_circle(to_array(centre), axis_a, axis_b, radius, colour);
+    }
+
+    /// Three circles, one around each axis
+    pub fn wire_sphere(&mut self, centre : Vector, radius : f32, colour : [f32; 4])
+    {
+        let centre = to_array(centre);
+        self.push_circle(centre, [1., 0., 0.], [0., 1., 0.], radius, colour);
+        self.push_circle(centre, [0., 1., 0.], [0., 0., 1.], radius, colour);
+        self.push_circle(centre, [0., 0., 1.], [1., 0., 0.], radius, colour);
+    }
+
+    /// A line with a four pronged head at `end`, a fifth of its length
+    pub fn arrow(&mut self, start : Vector, end : Vector, colour : [f32; 4])
+    {
+        let (start, end) = (to_array(start), to_array(end));
+        self.push_line(start, end, colour);
+
+        let offset = sub(end, start);
+        let Some(direction) = normalize(offset) else { return; };
+        let head_length = length(offset) * 0.2;
+
+        let reference = if direction[1].abs() < 0.99 { [0., 1., 0.] } else { [1., 0., 0.] };
+        let side = normalize(cross(direction, reference)).unwrap_or([1., 0., 0.]);
+        let up = cross(side, direction);
+        let base = sub(end, scale(direction, head_length));
+
+        for prong in [side, scale(side, -1.), up, scale(up, -1.)]
+        {
+            self.push_line(end, add(base, scale(prong, head_length * 0.5)), colour);
+        }
+    }
+
+    /// Red, green and blue arrows `length` long along the transform's rotated x, y and z axes
+    pub fn transform_axes(&mut self, transform : &Transform, length : f32)
+    {
+        let (sin_x, cos_x) = to_radians(transform.rotation.x).sin_cos();
+        let (sin_y, cos_y) = to_radians(transform.rotation.y).sin_cos();
+        let (sin_z, cos_z) = to_radians(transform.rotation.z).sin_cos();
+
+        // Same order as `Transform::generate_matrix`, x then y then z
+        let rotate = |v : [f32; 3]|
+        {
+            let v = [v[0] * cos_z - v[1] * sin_z, v[0] * sin_z + v[1] * cos_z, v[2]];
+            let v = [v[0] * cos_y + v[2] * sin_y, v[1], -v[0] * sin_y + v[2] * cos_y];
+            [v[0], v[1] * cos_x - v[2] * sin_x, v[1] * sin_x + v[2] * cos_x]
+        };
+
+        let origin = transform.position;
+        for (axis, colour) in [([1., 0., 0.], [1., 0., 0., 1.]), ([0., 1., 0.], [0., 1., 0., 1.]), ([0., 0., 1.], [0., 0., 1., 1.])]
+        {
+            let end = add(to_array(origin), scale(rotate(axis), length));
+            self.arrow(origin, Vector::new3(end[0], end[1], end[2]), colour);
+        }
+    }
+
+    /// The outline of a frustum from its near corners followed by its far corners, each in the order
+    /// bottom left, bottom right, top right, top left
+    pub fn frustum(&mut self, corners : &[Vector; 8], colour : [f32; 4])
+    {
+        let corners = corners.map(to_array);
+        for index in 0..4
+        {
+            let next = (index + 1) % 4;
+            self.push_line(corners[index], corners[next], colour);
+            self.push_line(corners[index + 4], corners[next + 4], colour);
+            self.push_line(corners[index], corners[index + 4], colour);
+        }
+    }
+
+    /// The view volume of `camera`, which is looking along `forward`. `aspect` is width over height and only matters for perspective cameras
+    pub fn camera_frustum(&mut self, camera : &ProspectCamera, forward : Vector, up : Vector, aspect : f32, colour : [f32; 4])
+    {
+        let position = to_array(camera.eye);
+        let forward = normalize(to_array(forward)).unwrap_or([0., 0., -1.]);
+        let right = normalize(cross(forward, to_array(up))).unwrap_or([1., 0., 0.]);
+        let up = cross(right, forward);
+
+        // Left, right, bottom and top of the plane at `distance` in front of the camera
+        let bounds = |distance : f32| match camera.projection_type
+        {
+            ProjectionType::Perspective(fov) =>
+            {
+                let half_height = (to_radians(fov) / 2.).tan() * distance;
+                (-half_height * aspect, half_height * aspect, -half_height, half_height)
+            }
+            ProjectionType::Orthographic(right, left, top, bottom) => (left, right, bottom, top),
+        };
+
+        let plane = |distance : f32|
+        {
+            let centre = add(position, scale(forward, distance));
+            let (left, right_edge, bottom, top) = bounds(distance);
+            [(left, bottom), (right_edge, bottom), (right_edge, top), (left, top)].map(|(x, y)|
+            {
+                let corner = add(centre, add(scale(right, x), scale(up, y)));
+                Vector::new3(corner[0], corner[1], corner[2])
+            })
+        };
+
+        let (near, far) = (plane(camera.znear), plane(camera.zfar));
+        self.frustum(&[near[0], near[1], near[2], near[3], far[0], far[1], far[2], far[3]], colour);
+    }
+
+    /// A square grid on the xz plane around `centre`, `cells` across in each direction
+    pub fn grid(&mut self, centre : Vector, cell_size : f32, cells : u32, colour : [f32; 4])
+    {
+        let centre = to_array(centre);
+        let half = cell_size * cells as f32 / 2.;
+
+        for line in 0..=cells
+        {
+            let offset = line as f32 * cell_size - half;
+            self.push_line(add(centre, [offset, 0., -half]), add(centre, [offset, 0., half]), colour);
+            self.push_line(add(centre, [-half, 0., offset]), add(centre, [half, 0., offset]), colour);
+        }
+    }
+
+    /// Uploads everything queued since the last call and clears the queue
+    pub fn process_frame(&mut self, window : &ProspectWindow)
+    {
+        self.depth_count = self.depth_tested.len() as u32;
+        self.overlay_count = self.overlay.len() as u32;
+
+        let vertex_count = self.depth_count + self.overlay_count;
+        if vertex_count > self.capacity
+        {
+            self.capacity = vertex_count.next_power_of_two();
+            self.vertex_buffer = Self::create_buffer(window.get_device(), self.capacity);
+        }
+
+        if vertex_count > 0
+        {
+            self.depth_tested.append(&mut self.overlay);
+            GraphicsContext::update_buffer(window.get_queue(), &self.vertex_buffer, 0, &self.depth_tested);
+        }
+        self.depth_tested.clear();
+        self.overlay.clear();
+    }
+
+    /// Draw after the scene so depth tested lines are hidden by it
+    pub fn draw<'a>(&'a self, render_pass : &mut RenderPass<'a>, camera : &'a ProspectCamera)
+    {
+        if self.depth_count + self.overlay_count == 0
+        {
+            return;
+        }
+
+        camera.bind(render_pass, 0);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+
+        if self.depth_count > 0
+        {
+            render_pass.set_pipeline(&self.depth_pipeline);
+            render_pass.draw(0..self.depth_count, 0..1);
+        }
+
+        if self.overlay_count > 0
+        {
+            render_pass.set_pipeline(&self.overlay_pipeline);
+            render_pass.draw(self.depth_count..self.depth_count + self.overlay_count, 0..1);
+        }
+    }
+}
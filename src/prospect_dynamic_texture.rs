@@ -0,0 +1,252 @@
+use image::{Rgba, RgbaImage};
+use wgpu::*;
+
+use crate::{abstraction::{graphics_context::GraphicsContext, mipmaps::AtlasLayout, prospect_window::ProspectWindow}, prospect_texture::{BindableTexture, TextureOptions}};
+
+/// Past this many separate dirty rectangles they're merged into one, so a scattering of single pixels doesn't become hundreds of uploads
+const MAX_DIRTY_REGIONS : usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct DirtyRegion
+{
+    x : u32,
+    y : u32,
+    width : u32,
+    height : u32,
+}
+
+impl DirtyRegion
+{
+    /// True if the two overlap or share an edge
+    fn touches(&self, other : &DirtyRegion) -> bool
+    {
+        self.x <= other.x + other.width && other.x <= self.x + self.width &&
+        self.y <= other.y + other.height && other.y <= self.y + self.height
+    }
+
+    fn union(&self, other : &DirtyRegion) -> DirtyRegion
+    {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        DirtyRegion
+        {
+            x,
+            y,
+            width : (self.x + self.width).max(other.x + other.width) - x,
+            height : (self.y + self.height).max(other.y + other.height) - y,
+        }
+    }
+}
+
+/// A texture with a CPU copy that can be drawn into, only the changed rectangles are uploaded by `process_frame`.
+///
+/// Useful for minimaps, painted masks, procedural effects and debug heatmaps. Mipmaps are off by default since
+/// every upload would need all of them rebuilding, turning them on in the `TextureOptions` does exactly that
+pub struct DynamicTexture
+{
+    name : String,
+    image : RgbaImage,
+    texture : Texture,
+    view : TextureView,
+    sampler : Option<Sampler>,
+    atlas : Option<AtlasLayout>,
+    dirty : Vec<DirtyRegion>,
+}
+
+impl DynamicTexture
+{
+    /// Transparent black to start with
+    pub fn new(name : &str, width : u32, height : u32, window : &ProspectWindow) -> Self
+    {
+        Self::from_image(name, RgbaImage::new(width, height), window)
+    }
+
+    pub fn from_image(name : &str, image : RgbaImage, window : &ProspectWindow) -> Self
+    {
+        let options = TextureOptions
+        {
+            mipmaps : false,
+            ..Default::default()
+        };
+        Self::from_image_with_options(name, image, &options, window)
+    }
+
+    /// `options.atlas` is respected when rebuilding mipmaps
+    pub fn from_image_with_options(name : &str, image : RgbaImage, options : &TextureOptions, window : &ProspectWindow) -> Self
+    {
+        let texture = GraphicsContext::create_texture_from_image_with_mipmaps(name, image.clone(), window.get_device(), window.get_queue(), options.format, options.mipmaps.then_some(window.get_mipmap_generator()), options.atlas.as_ref());
+
+        Self
+        {
+            name : name.to_string(),
+            view : GraphicsContext::create_texture_view(&texture),
+            sampler : Some(options.sampler.create_sampler(name, window.get_device())),
+            texture,
+            image,
+            atlas : options.atlas,
+            dirty : Vec::new(),
+        }
+    }
+
+    pub fn width(&self) -> u32
+    {
+        self.image.width()
+    }
+
+    pub fn height(&self) -> u32
+    {
+        self.image.height()
+    }
+
+    /// The CPU copy, as of the last write
+    pub fn get_image(&self) -> &RgbaImage
+    {
+        &self.image
+    }
+
+    /// Gives direct access to the CPU copy and marks all of it as changed
+    pub fn get_image_mut(&mut self) -> &mut RgbaImage
+    {
+        self.mark_dirty(0, 0, self.image.width(), self.image.height());
+        &mut self.image
+    }
+
+    /// Pixels outside the texture are ignored
+    pub fn set_pixel(&mut self, x : u32, y : u32, colour : Rgba<u8>)
+    {
+        if x < self.image.width() && y < self.image.height()
+        {
+            self.image.put_pixel(x, y, colour);
+            self.mark_dirty(x, y, 1, 1);
+        }
+    }
+
+    /// Fills a rectangle, clipped to the texture
+    pub fn fill_region(&mut self, x : u32, y : u32, width : u32, height : u32, colour : Rgba<u8>)
+    {
+        let (width, height) = self.clip(x, y, width, height);
+        for row in y..y + height
+        {
+            for column in x..x + width
+            {
+                self.image.put_pixel(column, row, colour);
+            }
+        }
+        self.mark_dirty(x, y, width, height);
+    }
+
+    /// Copies `source` with its top left at `x`, `y`, anything hanging off the texture is clipped
+    pub fn write_region(&mut self, x : u32, y : u32, source : &RgbaImage)
+    {
+        let (width, height) = self.clip(x, y, source.width(), source.height());
+        for row in 0..height
+        {
+            for column in 0..width
+            {
+                self.image.put_pixel(x + column, y + row, *source.get_pixel(column, row));
+            }
+        }
+        self.mark_dirty(x, y, width, height);
+    }
+
+    /// Same as `write_region` but calls `f(x, y)` for the colour of every texel in the rectangle
+    pub fn update_region<F : FnMut(u32, u32) -> Rgba<u8>>(&mut self, x : u32, y : u32, width : u32, height : u32, mut f : F)
+    {
+        let (width, height) = self.clip(x, y, width, height);
+        for row in y..y + height
+        {
+            for column in x..x + width
+            {
+                self.image.put_pixel(column, row, f(column, row));
+            }
+        }
+        self.mark_dirty(x, y, width, height);
+    }
+
+    /// For when the image was changed some other way, the region is clipped to the texture
+    pub fn mark_dirty(&mut self, x : u32, y : u32, width : u32, height : u32)
+    {
+        let (width, height) = self.clip(x, y, width, height);
+        if width == 0 || height == 0
+        {
+            return;
+        }
+
+        let mut region = DirtyRegion { x, y, width, height };
+
+        // Swallow everything this touches, growing until nothing else does
+        let mut index = 0;
+        while index < self.dirty.len()
+        {
+            if self.dirty[index].touches(&region)
+            {
+                region = region.union(&self.dirty.swap_remove(index));
+                index = 0;
+            } else
+            {
+                index += 1;
+            }
+        }
+        self.dirty.push(region);
+
+        if self.dirty.len() > MAX_DIRTY_REGIONS
+        {
+            let bounds = self.dirty.iter().skip(1).fold(self.dirty[0], |bounds, region| bounds.union(region));
+            self.dirty = vec![bounds];
+        }
+    }
+
+    pub fn is_dirty(&self) -> bool
+    {
+        !self.dirty.is_empty()
+    }
+
+    fn clip(&self, x : u32, y : u32, width : u32, height : u32) -> (u32, u32)
+    {
+        (width.min(self.image.width().saturating_sub(x)), height.min(self.image.height().saturating_sub(y)))
+    }
+
+    /// Uploads every rectangle changed since the last call
+    pub fn process_frame(&mut self, window : &ProspectWindow)
+    {
+        if self.dirty.is_empty()
+        {
+            return;
+        }
+
+        for region in self.dirty.drain(..)
+        {
+            GraphicsContext::update_texture_region(window.get_queue(), &self.texture, &self.image, region.x, region.y, region.width, region.height);
+        }
+
+        window.get_mipmap_generator().generate(window.get_device(), window.get_queue(), &self.texture, self.atlas.as_ref());
+    }
+
+    pub fn get_texture(&self) -> &Texture
+    {
+        &self.texture
+    }
+
+    pub fn get_name(&self) -> String
+    {
+        self.name.clone()
+    }
+}
+
+impl BindableTexture for DynamicTexture
+{
+    fn get_texture_view(&self) -> &TextureView
+    {
+        &self.view
+    }
+
+    fn get_name(&self) -> String
+    {
+        self.name.clone()
+    }
+
+    fn get_sampler(&self) -> Option<&Sampler>
+    {
+        self.sampler.as_ref()
+    }
+}
@@ -0,0 +1,517 @@
+use std::{collections::HashMap, ops::Range, time::SystemTime};
+
+use egui::{epaint::{ImageDelta, Primitive}, Context, Event, ImageData, Key, Modifiers, PointerButton, Pos2, RawInput, Rect, TextureFilter, TextureId, Vec2};
+use image::RgbaImage;
+use wgpu::*;
+use winit::event::{ElementState, ModifiersState, MouseButton, MouseScrollDelta, VirtualKeyCode};
+
+use crate::{abstraction::{graphics_context::GraphicsContext, high_level_abstraction::HighLevelGraphicsContext, prospect_window::ProspectWindow}, prospect_app::ProspectEvent, prospect_texture::{BindableTexture, TextureBindLayout}, smart::SmartBindGroup};
+
+/// Points scrolled per line for mice that scroll in lines rather than pixels
+const POINTS_PER_SCROLL_LINE : f32 = 50.;
+
+const EGUI_VERTEX_LAYOUT : VertexBufferLayout<'static> = VertexBufferLayout {
+    array_stride: std::mem::size_of::<egui::epaint::Vertex>() as BufferAddress,
+    step_mode: VertexStepMode::Vertex,
+    attributes: &vertex_attr_array![0 => Float32x2, 1 => Float32x2, 2 => Unorm8x4],
+};
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct ScreenUniform
+{
+    size : [f32; 2],
+    padding : [f32; 2],
+}
+
+struct EguiTexture
+{
+    /// `None` for textures registered with `register_texture`, which egui never updates
+    texture : Option<Texture>,
+    bind_group : SmartBindGroup,
+}
+
+struct EguiDraw
+{
+    texture : TextureId,
+    /// x, y, width and height in physical pixels
+    scissor : (u32, u32, u32, u32),
+    indices : Range<u32>,
+    base_vertex : i32,
+}
+
+fn egui_key(key : VirtualKeyCode) -> Option<Key>
+{
+    Some(match key
+    {
+        VirtualKeyCode::Down => Key::ArrowDown,
+        VirtualKeyCode::Left => Key::ArrowLeft,
+        VirtualKeyCode::Right => Key::ArrowRight,
+        VirtualKeyCode::Up => Key::ArrowUp,
+        VirtualKeyCode::Escape => Key::Escape,
+        VirtualKeyCode::Tab => Key::Tab,
+        VirtualKeyCode::Back => Key::Backspace,
+        VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter => Key::Enter,
+        VirtualKeyCode::Space => Key::Space,
+        VirtualKeyCode::Insert => Key::Insert,
+        VirtualKeyCode::Delete => Key::Delete,
+        VirtualKeyCode::Home => Key::Home,
+        VirtualKeyCode::End => Key::End,
+        VirtualKeyCode::PageUp => Key::PageUp,
+        VirtualKeyCode::PageDown => Key::PageDown,
+        VirtualKeyCode::Minus | VirtualKeyCode::NumpadSubtract => Key::Minus,
+        VirtualKeyCode::Equals | VirtualKeyCode::Plus | VirtualKeyCode::NumpadAdd => Key::PlusEquals,
+        VirtualKeyCode::Key0 | VirtualKeyCode::Numpad0 => Key::Num0,
+        VirtualKeyCode::Key1 | VirtualKeyCode::Numpad1 => Key::Num1,
+        VirtualKeyCode::Key2 | VirtualKeyCode::Numpad2 => Key::Num2,
+        VirtualKeyCode::Key3 | VirtualKeyCode::Numpad3 => Key::Num3,
+        VirtualKeyCode::Key4 | VirtualKeyCode::Numpad4 => Key::Num4,
+        VirtualKeyCode::Key5 | VirtualKeyCode::Numpad5 => Key::Num5,
+        VirtualKeyCode::Key6 | VirtualKeyCode::Numpad6 => Key::Num6,
+        VirtualKeyCode::Key7 | VirtualKeyCode::Numpad7 => Key::Num7,
+        VirtualKeyCode::Key8 | VirtualKeyCode::Numpad8 => Key::Num8,
+        VirtualKeyCode::Key9 | VirtualKeyCode::Numpad9 => Key::Num9,
+        VirtualKeyCode::A => Key::A,
+        VirtualKeyCode::B => Key::B,
+        VirtualKeyCode::C => Key::C,
+        VirtualKeyCode::D => Key::D,
+        VirtualKeyCode::E => Key::E,
+        VirtualKeyCode::F => Key::F,
+        VirtualKeyCode::G => Key::G,
+        VirtualKeyCode::H => Key::H,
+        VirtualKeyCode::I => Key::I,
+        VirtualKeyCode::J => Key::J,
+        VirtualKeyCode::K => Key::K,
+        VirtualKeyCode::L => Key::L,
+        VirtualKeyCode::M => Key::M,
+        VirtualKeyCode::N => Key::N,
+        VirtualKeyCode::O => Key::O,
+        VirtualKeyCode::P => Key::P,
+        VirtualKeyCode::Q => Key::Q,
+        VirtualKeyCode::R => Key::R,
+        VirtualKeyCode::S => Key::S,
+        VirtualKeyCode::T => Key::T,
+        VirtualKeyCode::U => Key::U,
+        VirtualKeyCode::V => Key::V,
+        VirtualKeyCode::W => Key::W,
+        VirtualKeyCode::X => Key::X,
+        VirtualKeyCode::Y => Key::Y,
+        VirtualKeyCode::Z => Key::Z,
+        VirtualKeyCode::F1 => Key::F1,
+        VirtualKeyCode::F2 => Key::F2,
+        VirtualKeyCode::F3 => Key::F3,
+        VirtualKeyCode::F4 => Key::F4,
+        VirtualKeyCode::F5 => Key::F5,
+        VirtualKeyCode::F6 => Key::F6,
+        VirtualKeyCode::F7 => Key::F7,
+        VirtualKeyCode::F8 => Key::F8,
+        VirtualKeyCode::F9 => Key::F9,
+        VirtualKeyCode::F10 => Key::F10,
+        VirtualKeyCode::F11 => Key::F11,
+        VirtualKeyCode::F12 => Key::F12,
+        _ => return None,
+    })
+}
+
+fn egui_modifiers(modifiers : ModifiersState) -> Modifiers
+{
+    let mac = cfg!(target_os = "macos");
+    Modifiers
+    {
+        alt : modifiers.alt(),
+        ctrl : modifiers.ctrl(),
+        shift : modifiers.shift(),
+        mac_cmd : mac && modifiers.logo(),
+        command : if mac { modifiers.logo() } else { modifiers.ctrl() },
+    }
+}
+
+/// Runs egui on top of a `ProspectWindow`, available with the `egui` feature.
+///
+/// Pass every `ProspectEvent` to `handle_event` from `ProspectApp::process`, it returns true when egui used the event so the
+/// app can skip its own handling (e.g moving the camera). Each frame build the UI with `run`, then call `draw` after everything
+/// else so the UI ends up on top. The clipboard and cursor icons aren't hooked up
+pub struct ProspectEgui
+{
+    context : Context,
+    input : RawInput,
+    modifiers : Modifiers,
+    pointer : Pos2,
+    pixels_per_point : f32,
+    start : SystemTime,
+    pipeline : RenderPipeline,
+    screen_buffer : Buffer,
+    screen_bind_group : BindGroup,
+    texture_layout : TextureBindLayout,
+    textures : HashMap<TextureId, EguiTexture>,
+    next_user_texture : u64,
+    /// Freed by egui during the last `run`, removed at the start of the next one once they've been drawn
+    to_free : Vec<TextureId>,
+    vertex_buffer : Buffer,
+    index_buffer : Buffer,
+    vertex_capacity : usize,
+    index_capacity : usize,
+    draws : Vec<EguiDraw>,
+}
+
+impl ProspectEgui
+{
+    pub fn new(window : &ProspectWindow) -> Self
+    {
+        let device = window.get_device();
+        let module = GraphicsContext::load_shader("Egui Shader", include_str!("shaders/egui.wgsl"), device);
+
+        let screen_buffer = GraphicsContext::create_buffer(device, "Egui Screen Buffer", &[ScreenUniform { size : [1.; 2], padding : [0.; 2] }], BufferUsages::UNIFORM | BufferUsages::COPY_DST);
+        let (screen_layout, screen_bind_group) = HighLevelGraphicsContext::create_uniform_and_bind_group(device, "Egui Screen Bind Group", ShaderStages::VERTEX, &screen_buffer);
+
+        let sampler = GraphicsContext::create_sampler("Egui Sampler", device, Some(FilterMode::Linear), Some(FilterMode::Linear));
+        let texture_layout = TextureBindLayout::new(device, "Egui Texture Bind Group", sampler);
+        let pipeline_layout = GraphicsContext::create_pipeline_layout("Egui Pipeline Layout", device, &vec![&screen_layout, texture_layout.get_layout()]);
+
+        // egui outputs premultiplied alpha
+        let blend = BlendState {
+            color: BlendComponent {
+                src_factor: BlendFactor::One,
+                dst_factor: BlendFactor::OneMinusSrcAlpha,
+                operation: BlendOperation::Add,
+            },
+            alpha: BlendComponent {
+                src_factor: BlendFactor::OneMinusDstAlpha,
+                dst_factor: BlendFactor::One,
+                operation: BlendOperation::Add,
+            },
+        };
+
+        let format = window.get_surface_config().format;
+        let targets = [Some(ColorTargetState {
+            format,
+            blend: Some(blend),
+            write_mask: ColorWrites::ALL,
+        })];
+
+        let fragment_state = FragmentState {
+            module: &module,
+            entry_point: if format.is_srgb() { "fs_linear" } else { "fs_gamma" },
+            targets: &targets,
+        };
+
+        let vertex_state = VertexState {
+            module: &module,
+            entry_point: "vs_main",
+            buffers: &[EGUI_VERTEX_LAYOUT],
+        };
+
+        let mut state = GraphicsContext::DEFAULT_PRIMITIVE_STATE;
+        state.cull_mode = None;
+
+        let pipeline = GraphicsContext::create_render_pipeline_without_depth("Egui Pipeline", &pipeline_layout, fragment_state, vertex_state, device, state);
+
+        let (vertex_capacity, index_capacity) = (1024, 4096);
+
+        Self
+        {
+            context : Context::default(),
+            input : RawInput::default(),
+            modifiers : Modifiers::default(),
+            pointer : Pos2::ZERO,
+            pixels_per_point : window.get_scale_factor() as f32,
+            start : SystemTime::now(),
+            pipeline,
+            screen_buffer,
+            screen_bind_group,
+            texture_layout,
+            textures : HashMap::new(),
+            next_user_texture : 0,
+            to_free : Vec::new(),
+            vertex_buffer : Self::create_vertex_buffer(device, vertex_capacity),
+            index_buffer : Self::create_index_buffer(device, index_capacity),
+            vertex_capacity,
+            index_capacity,
+            draws : Vec::new(),
+        }
+    }
+
+    fn create_vertex_buffer(device : &Device, capacity : usize) -> Buffer
+    {
+        GraphicsContext::create_buffer(device, "Egui Vertex Buffer", &vec![egui::epaint::Vertex::default(); capacity], BufferUsages::VERTEX | BufferUsages::COPY_DST)
+    }
+
+    fn create_index_buffer(device : &Device, capacity : usize) -> Buffer
+    {
+        GraphicsContext::create_buffer(device, "Egui Index Buffer", &vec![0u32; capacity], BufferUsages::INDEX | BufferUsages::COPY_DST)
+    }
+
+    /// For anything not covered by `run`, such as changing the style
+    pub fn get_context(&self) -> &Context
+    {
+        &self.context
+    }
+
+    /// True while the pointer is over or dragging egui, as of the last `run`
+    pub fn wants_pointer_input(&self) -> bool
+    {
+        self.context.wants_pointer_input()
+    }
+
+    /// True while a text field or similar has focus, as of the last `run`
+    pub fn wants_keyboard_input(&self) -> bool
+    {
+        self.context.wants_keyboard_input()
+    }
+
+    /// Queues `event` for the next `run`, returns true if egui is using that kind of input and the app should ignore it
+    pub fn handle_event(&mut self, event : &ProspectEvent) -> bool
+    {
+        match *event
+        {
+            ProspectEvent::CursorMoveEvent(position) =>
+            {
+                self.pointer = Pos2::new(position.x, position.y) / self.pixels_per_point;
+                self.input.events.push(Event::PointerMoved(self.pointer));
+                self.wants_pointer_input()
+            }
+            ProspectEvent::CursorDelta(_) => self.wants_pointer_input(),
+            ProspectEvent::CursorClicked(state, button) =>
+            {
+                let button = match button
+                {
+                    MouseButton::Left => PointerButton::Primary,
+                    MouseButton::Right => PointerButton::Secondary,
+                    MouseButton::Middle => PointerButton::Middle,
+                    MouseButton::Other(_) => return self.wants_pointer_input(),
+                };
+
+                self.input.events.push(Event::PointerButton
+                {
+                    pos : self.pointer,
+                    button,
+                    pressed : state == ElementState::Pressed,
+                    modifiers : self.modifiers,
+                });
+                self.wants_pointer_input()
+            }
+            ProspectEvent::MouseWheel(delta) =>
+            {
+                let delta = match delta
+                {
+                    MouseScrollDelta::LineDelta(x, y) => Vec2::new(x, y) * POINTS_PER_SCROLL_LINE,
+                    MouseScrollDelta::PixelDelta(delta) => Vec2::new(delta.x as f32, delta.y as f32) / self.pixels_per_point,
+                };
+                self.input.events.push(Event::Scroll(delta));
+                self.wants_pointer_input()
+            }
+            ProspectEvent::KeyboardInput(Some(key), state) =>
+            {
+                let pressed = state == ElementState::Pressed;
+                if pressed && self.modifiers.command
+                {
+                    match key
+                    {
+                        VirtualKeyCode::C => self.input.events.push(Event::Copy),
+                        VirtualKeyCode::X => self.input.events.push(Event::Cut),
+                        _ => {}
+                    }
+                }
+
+                if let Some(key) = egui_key(key)
+                {
+                    self.input.events.push(Event::Key { key, pressed, repeat : false, modifiers : self.modifiers });
+                }
+                self.wants_keyboard_input()
+            }
+            ProspectEvent::KeyboardInput(None, _) => self.wants_keyboard_input(),
+            ProspectEvent::ReceivedCharacter(character) =>
+            {
+                // Enter, backspace and the like arrive as keys
+                if !character.is_control()
+                {
+                    self.input.events.push(Event::Text(character.to_string()));
+                }
+                self.wants_keyboard_input()
+            }
+            ProspectEvent::ModifiersChanged(modifiers) =>
+            {
+                self.modifiers = egui_modifiers(modifiers);
+                false
+            }
+            ProspectEvent::Focused(focused) =>
+            {
+                self.input.focused = focused;
+                self.input.events.push(Event::WindowFocused(focused));
+                false
+            }
+        }
+    }
+
+    /// Lets egui show any `BindableTexture` through the returned id, e.g with `egui::Image`
+    pub fn register_texture<T : BindableTexture + ?Sized>(&mut self, window : &ProspectWindow, texture : &T) -> TextureId
+    {
+        let id = TextureId::User(self.next_user_texture);
+        self.next_user_texture += 1;
+        self.textures.insert(id, EguiTexture { texture : None, bind_group : self.texture_layout.bind_prospect_texture(texture, window) });
+        id
+    }
+
+    pub fn unregister_texture(&mut self, id : TextureId)
+    {
+        self.textures.remove(&id);
+    }
+
+    fn update_texture(&mut self, window : &ProspectWindow, id : TextureId, delta : &ImageDelta)
+    {
+        let (size, pixels) : ([usize; 2], Vec<u8>) = match &delta.image
+        {
+            ImageData::Color(image) => (image.size, image.pixels.iter().flat_map(|pixel| pixel.to_array()).collect()),
+            ImageData::Font(image) => (image.size, image.srgba_pixels(None).flat_map(|pixel| pixel.to_array()).collect()),
+        };
+
+        let image = match RgbaImage::from_raw(size[0] as u32, size[1] as u32, pixels)
+        {
+            Some(image) => image,
+            None =>
+            {
+                log::error!("Egui texture {:?} has the wrong number of pixels", id);
+                return;
+            }
+        };
+
+        if let Some(position) = delta.pos
+        {
+            match self.textures.get(&id).and_then(|texture| texture.texture.as_ref())
+            {
+                Some(texture) => GraphicsContext::write_image_to_texture(window.get_queue(), texture, &image, position[0] as u32, position[1] as u32),
+                None => log::error!("Egui tried to update texture {:?} before creating it", id),
+            }
+            return;
+        }
+
+        let name = format!("Egui Texture {:?}", id);
+        let device = window.get_device();
+        let texture = GraphicsContext::create_texture_from_image_with_mipmaps(&name, image, device, window.get_queue(), TextureFormat::Rgba8UnormSrgb, None, None);
+        let view = GraphicsContext::create_texture_view(&texture);
+
+        let filter = |filter : TextureFilter| match filter
+        {
+            TextureFilter::Nearest => FilterMode::Nearest,
+            TextureFilter::Linear => FilterMode::Linear,
+        };
+        let sampler = GraphicsContext::create_sampler(&name, device, Some(filter(delta.options.magnification)), Some(filter(delta.options.minification)));
+
+        let bind_group = self.texture_layout.create_texture_with_sampler(window, &view, &sampler, &name);
+        self.textures.insert(id, EguiTexture { texture : Some(texture), bind_group : bind_group.into() });
+    }
+
+    /// Builds this frame's UI with `ui` and uploads the result for `draw`
+    pub fn run<F : FnOnce(&Context)>(&mut self, window : &ProspectWindow, ui : F)
+    {
+        let viewport_id = self.input.viewport_id;
+        self.input.viewports.entry(viewport_id).or_default().native_pixels_per_point = Some(window.get_scale_factor() as f32);
+
+        let screen_size = Vec2::new(window.size.0 as f32, window.size.1 as f32);
+        self.input.screen_rect = Some(Rect::from_min_size(Pos2::ZERO, screen_size / self.pixels_per_point));
+        self.input.time = Some(SystemTime::now().duration_since(self.start).map(|time| time.as_secs_f64()).unwrap_or(0.));
+        self.input.max_texture_side = Some(window.get_device().limits().max_texture_dimension_2d as usize);
+        self.input.modifiers = self.modifiers;
+
+        let output = self.context.run(self.input.take(), ui);
+        self.pixels_per_point = output.pixels_per_point;
+
+        for id in self.to_free.drain(..)
+        {
+            self.textures.remove(&id);
+        }
+        for (id, delta) in &output.textures_delta.set
+        {
+            self.update_texture(window, *id, delta);
+        }
+        self.to_free = output.textures_delta.free;
+
+        let points = screen_size / self.pixels_per_point;
+        GraphicsContext::update_buffer(window.get_queue(), &self.screen_buffer, 0, &[ScreenUniform { size : [points.x, points.y], padding : [0.; 2] }]);
+
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        self.draws.clear();
+
+        for primitive in self.context.tessellate(output.shapes, self.pixels_per_point)
+        {
+            let mesh = match primitive.primitive
+            {
+                Primitive::Mesh(mesh) => mesh,
+                Primitive::Callback(_) =>
+                {
+                    log::warn!("Egui paint callbacks aren't supported");
+                    continue;
+                }
+            };
+
+            // Clip rectangles are in points, scissors are in pixels and have to stay on screen
+            let clip = primitive.clip_rect;
+            let min_x = (clip.min.x * self.pixels_per_point).round().clamp(0., screen_size.x) as u32;
+            let min_y = (clip.min.y * self.pixels_per_point).round().clamp(0., screen_size.y) as u32;
+            let max_x = (clip.max.x * self.pixels_per_point).round().clamp(0., screen_size.x) as u32;
+            let max_y = (clip.max.y * self.pixels_per_point).round().clamp(0., screen_size.y) as u32;
+            if max_x <= min_x || max_y <= min_y || mesh.indices.is_empty()
+            {
+                continue;
+            }
+
+            let first_index = indices.len() as u32;
+            self.draws.push(EguiDraw
+            {
+                texture : mesh.texture_id,
+                scissor : (min_x, min_y, max_x - min_x, max_y - min_y),
+                indices : first_index..first_index + mesh.indices.len() as u32,
+                base_vertex : vertices.len() as i32,
+            });
+            vertices.extend(mesh.vertices);
+            indices.extend(mesh.indices);
+        }
+
+        let device = window.get_device();
+        if vertices.len() > self.vertex_capacity
+        {
+            self.vertex_capacity = vertices.len().next_power_of_two();
+            self.vertex_buffer = Self::create_vertex_buffer(device, self.vertex_capacity);
+        }
+        if indices.len() > self.index_capacity
+        {
+            self.index_capacity = indices.len().next_power_of_two();
+            self.index_buffer = Self::create_index_buffer(device, self.index_capacity);
+        }
+
+        if !indices.is_empty()
+        {
+            GraphicsContext::update_buffer(window.get_queue(), &self.vertex_buffer, 0, &vertices);
+            GraphicsContext::update_buffer(window.get_queue(), &self.index_buffer, 0, &indices);
+        }
+    }
+
+    /// Draws the last `run` over `view` in its own pass, keeping what's already there
+    pub fn draw(&self, view : &TextureView, command_encoder : &mut CommandEncoder)
+    {
+        if self.draws.is_empty()
+        {
+            return;
+        }
+
+        let mut render_pass = GraphicsContext::begin_render_pass_colour_only(None, "Egui Render Pass", view, command_encoder);
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.screen_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), IndexFormat::Uint32);
+
+        for draw in &self.draws
+        {
+            let texture = match self.textures.get(&draw.texture)
+            {
+                Some(texture) => texture,
+                None => continue,
+            };
+
+            render_pass.set_scissor_rect(draw.scissor.0, draw.scissor.1, draw.scissor.2, draw.scissor.3);
+            texture.bind_group.set_bind_group(&mut render_pass, 1, &[]);
+            render_pass.draw_indexed(draw.indices.clone(), draw.base_vertex, 0..1);
+        }
+    }
+}
@@ -0,0 +1,277 @@
+use image::{ImageFormat, Rgba32FImage};
+use wgpu::*;
+
+use crate::{abstraction::{graphics_context::GraphicsContext, mipmaps::mip_level_count, prospect_window::ProspectWindow}, prospect_cubemap::ProspectCubemap, prospect_light::ProspectPointLight, smart::SmartBindGroup};
+
+const ENVIRONMENT_SIZE : u32 = 512;
+const IRRADIANCE_SIZE : u32 = 32;
+const PREFILTERED_SIZE : u32 = 128;
+/// Roughness 0 to 1 is spread across these levels of the prefiltered cubemap
+const PREFILTERED_MIP_LEVELS : u32 = 5;
+const BRDF_LUT_SIZE : u32 = 256;
+
+const HDR_FORMAT : TextureFormat = TextureFormat::Rgba16Float;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable, Default)]
+struct EnvironmentPassUniform
+{
+    face : u32,
+    roughness : f32,
+    source_size : f32,
+    // Due to uniforms requiring 16 byte (4 float) spacing, we need to use a padding field here
+    _padding : u32,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable, Default)]
+struct EnvironmentUniform
+{
+    intensity : f32,
+    max_lod : f32,
+    // Due to uniforms requiring 16 byte (4 float) spacing, we need to use a padding field here
+    _padding : [f32; 2],
+}
+
+/// Image based lighting from an equirectangular panorama, converted to a cubemap on the GPU along with
+/// a diffuse irradiance map, a specular map prefiltered per roughness and a BRDF lookup table.
+///
+/// It replaces `ProspectPointLight`'s bind group at slot 2, pass `get_layout` when building the pipeline
+/// of a shader made `with_environment` and bind `create_bind_group`. The point light is still used for direct lighting
+pub struct ProspectEnvironment
+{
+    name : String,
+    /// Scales the light coming from the environment, call `process_frame` after changing it
+    pub intensity : f32,
+    environment : ProspectCubemap,
+    irradiance : TextureView,
+    prefiltered : TextureView,
+    brdf_lut : TextureView,
+    sampler : Sampler,
+    buffer : Buffer,
+    layout : BindGroupLayout,
+}
+
+impl ProspectEnvironment
+{
+    /// Loads a Radiance `.hdr` panorama
+    pub fn from_hdr_bytes(name : &str, bytes : &[u8], window : &ProspectWindow) -> Result<Self, ()>
+    {
+        let image = image::load_from_memory_with_format(bytes, ImageFormat::Hdr).map_err(|e|
+        {
+            log::error!("Failed to load HDR environment \"{}\": {}", name, e);
+        })?;
+
+        Ok(Self::from_equirectangular(name, &image.to_rgba32f(), window))
+    }
+
+    /// `image` is a 2:1 longitude/latitude panorama in linear colour
+    pub fn from_equirectangular(name : &str, image : &Rgba32FImage, window : &ProspectWindow) -> Self
+    {
+        let device = window.get_device();
+        let queue = window.get_queue();
+
+        let pass_entries = vec![
+            GraphicsContext::create_bind_group_layout_entry(0, ShaderStages::VERTEX_FRAGMENT, GraphicsContext::create_uniform_binding_type()),
+        ];
+        let pass_layout = GraphicsContext::create_bind_group_layout(device, "Environment Pass Bind Group Layout", &pass_entries);
+
+        let cube_entries = vec![
+            GraphicsContext::create_bind_group_layout_entry(0, ShaderStages::FRAGMENT, GraphicsContext::create_texture_binding_type(false, TextureViewDimension::Cube, TextureSampleType::Float { filterable: true })),
+            GraphicsContext::create_bind_group_layout_entry(1, ShaderStages::FRAGMENT, GraphicsContext::create_sample_binding_type(SamplerBindingType::Filtering)),
+        ];
+        let cube_layout = GraphicsContext::create_bind_group_layout(device, "Environment Cube Bind Group Layout", &cube_entries);
+
+        let sampler = GraphicsContext::create_sampler_advanced("Environment Sampler", device, Some(FilterMode::Linear), Some(FilterMode::Linear), Some(FilterMode::Linear), None, None, None);
+
+        // Equirectangular to cubemap, the panorama is Rgba32Float which can only be loaded, not sampled
+        let equirectangular = GraphicsContext::create_texture_from_hdr_image("Environment Equirectangular", image, device, queue);
+        let equirectangular_entries = vec![
+            GraphicsContext::create_bind_group_layout_entry(0, ShaderStages::FRAGMENT, GraphicsContext::create_texture_binding_type(false, TextureViewDimension::D2, TextureSampleType::Float { filterable: false })),
+        ];
+        let equirectangular_layout = GraphicsContext::create_bind_group_layout(device, "Environment Equirectangular Bind Group Layout", &equirectangular_entries);
+        let equirectangular_view = GraphicsContext::create_texture_view(&equirectangular);
+        let equirectangular_bind_group = GraphicsContext::create_bind_group(device, "Environment Equirectangular Bind Group", &equirectangular_layout, &vec![
+            GraphicsContext::create_texture_view_resource(0, &equirectangular_view),
+        ]);
+
+        let environment = GraphicsContext::create_render_target_cubemap(name, ENVIRONMENT_SIZE, mip_level_count(ENVIRONMENT_SIZE, ENVIRONMENT_SIZE, None), HDR_FORMAT, device);
+        let pipeline = Self::create_pass_pipeline(device, "Equirectangular", include_str!("shaders/environment/equirectangular.wgsl"), &[&pass_layout, &equirectangular_layout], HDR_FORMAT);
+        Self::render_cube(device, queue, &pipeline, &pass_layout, Some(&equirectangular_bind_group), &environment, 0, EnvironmentPassUniform::default());
+
+        // Filling in the lower levels lets the convolutions below sample a blurrier environment instead of thousands of texels
+        window.get_mipmap_generator().generate(device, queue, &environment, None);
+
+        let environment_view = GraphicsContext::create_cube_texture_view(&environment);
+        let environment_bind_group = GraphicsContext::create_bind_group(device, "Environment Cube Bind Group", &cube_layout, &vec![
+            GraphicsContext::create_texture_view_resource(0, &environment_view),
+            GraphicsContext::create_sampler_resource(1, &sampler),
+        ]);
+
+        let irradiance = GraphicsContext::create_render_target_cubemap("Environment Irradiance", IRRADIANCE_SIZE, 1, HDR_FORMAT, device);
+        let pipeline = Self::create_pass_pipeline(device, "Irradiance", include_str!("shaders/environment/irradiance.wgsl"), &[&pass_layout, &cube_layout], HDR_FORMAT);
+        Self::render_cube(device, queue, &pipeline, &pass_layout, Some(&environment_bind_group), &irradiance, 0, EnvironmentPassUniform
+        {
+            source_size : ENVIRONMENT_SIZE as f32,
+            ..Default::default()
+        });
+
+        let prefiltered = GraphicsContext::create_render_target_cubemap("Environment Prefiltered", PREFILTERED_SIZE, PREFILTERED_MIP_LEVELS, HDR_FORMAT, device);
+        let pipeline = Self::create_pass_pipeline(device, "Prefilter", include_str!("shaders/environment/prefilter.wgsl"), &[&pass_layout, &cube_layout], HDR_FORMAT);
+        for level in 0..PREFILTERED_MIP_LEVELS
+        {
+            Self::render_cube(device, queue, &pipeline, &pass_layout, Some(&environment_bind_group), &prefiltered, level, EnvironmentPassUniform
+            {
+                roughness : level as f32 / (PREFILTERED_MIP_LEVELS - 1) as f32,
+                source_size : ENVIRONMENT_SIZE as f32,
+                ..Default::default()
+            });
+        }
+
+        let (_, brdf_lut_view, _) = GraphicsContext::create_framebuffer_with_format(device, "Environment BRDF LUT", BRDF_LUT_SIZE, BRDF_LUT_SIZE, TextureFormat::Rg16Float);
+        let pipeline = Self::create_pass_pipeline(device, "BRDF LUT", include_str!("shaders/environment/brdf_lut.wgsl"), &[&pass_layout], TextureFormat::Rg16Float);
+        Self::render_face(device, queue, &pipeline, &pass_layout, None, &brdf_lut_view, EnvironmentPassUniform::default());
+
+        let buffer = GraphicsContext::create_buffer(device, "Environment Uniform Buffer", &[EnvironmentUniform::default()], BufferUsages::UNIFORM | BufferUsages::COPY_DST);
+
+        let cube_binding = GraphicsContext::create_texture_binding_type(false, TextureViewDimension::Cube, TextureSampleType::Float { filterable: true });
+        let entries = vec![
+            GraphicsContext::create_bind_group_layout_entry(0, ShaderStages::VERTEX_FRAGMENT, GraphicsContext::create_uniform_binding_type()),
+            GraphicsContext::create_bind_group_layout_entry(1, ShaderStages::FRAGMENT, cube_binding),
+            GraphicsContext::create_bind_group_layout_entry(2, ShaderStages::FRAGMENT, cube_binding),
+            GraphicsContext::create_bind_group_layout_entry(3, ShaderStages::FRAGMENT, GraphicsContext::create_texture_binding_type(false, TextureViewDimension::D2, TextureSampleType::Float { filterable: true })),
+            GraphicsContext::create_bind_group_layout_entry(4, ShaderStages::FRAGMENT, GraphicsContext::create_sample_binding_type(SamplerBindingType::Filtering)),
+            GraphicsContext::create_bind_group_layout_entry(5, ShaderStages::FRAGMENT, GraphicsContext::create_uniform_binding_type()),
+        ];
+        let layout = GraphicsContext::create_bind_group_layout(device, "Environment Bind Group Layout", &entries);
+
+        let environment = Self
+        {
+            name : name.to_string(),
+            intensity : 1.,
+            environment : ProspectCubemap::from_texture(name, environment),
+            irradiance : GraphicsContext::create_cube_texture_view(&irradiance),
+            prefiltered : GraphicsContext::create_cube_texture_view(&prefiltered),
+            brdf_lut : brdf_lut_view,
+            sampler,
+            buffer,
+            layout,
+        };
+        environment.process_frame(window);
+        environment
+    }
+
+    fn create_pass_pipeline(device : &Device, name : &str, src : &str, layouts : &[&BindGroupLayout], format : TextureFormat) -> RenderPipeline
+    {
+        let src = format!("{}\n{}", include_str!("shaders/environment/common.wgsl"), src);
+        let module = GraphicsContext::load_shader(&format!("Environment {} Shader", name), &src, device);
+        let pipeline_layout = GraphicsContext::create_pipeline_layout(&format!("Environment {} Pipeline Layout", name), device, &layouts.to_vec());
+
+        let targets = [Some(ColorTargetState {
+            format,
+            blend: Some(BlendState::REPLACE),
+            write_mask: ColorWrites::ALL,
+        })];
+
+        let fragment_state = FragmentState {
+            module: &module,
+            entry_point: "fs_main",
+            targets: &targets,
+        };
+
+        let vertex_state = VertexState {
+            module: &module,
+            entry_point: "vs_main",
+            buffers: &[],
+        };
+
+        let mut state = GraphicsContext::DEFAULT_PRIMITIVE_STATE;
+        state.cull_mode = None;
+
+        GraphicsContext::create_render_pipeline_without_depth(&format!("Environment {} Pipeline", name), &pipeline_layout, fragment_state, vertex_state, device, state)
+    }
+
+    /// Renders all six faces of `level` of `target`
+    fn render_cube(device : &Device, queue : &Queue, pipeline : &RenderPipeline, pass_layout : &BindGroupLayout, source : Option<&BindGroup>, target : &Texture, level : u32, uniform : EnvironmentPassUniform)
+    {
+        for face in 0..6
+        {
+            let view = target.create_view(&TextureViewDescriptor {
+                label: Some("Environment Face"),
+                dimension: Some(TextureViewDimension::D2),
+                base_mip_level: level,
+                mip_level_count: Some(1),
+                base_array_layer: face,
+                array_layer_count: Some(1),
+                ..Default::default()
+            });
+
+            Self::render_face(device, queue, pipeline, pass_layout, source, &view, EnvironmentPassUniform { face, ..uniform });
+        }
+    }
+
+    fn render_face(device : &Device, queue : &Queue, pipeline : &RenderPipeline, pass_layout : &BindGroupLayout, source : Option<&BindGroup>, view : &TextureView, uniform : EnvironmentPassUniform)
+    {
+        let buffer = GraphicsContext::create_buffer(device, "Environment Pass Uniform", &[uniform], BufferUsages::UNIFORM);
+        let bind_group = GraphicsContext::create_bind_group(device, "Environment Pass Bind Group", pass_layout, &vec![
+            GraphicsContext::create_bind_group_entry(0, buffer.as_entire_binding()),
+        ]);
+
+        let mut encoder = GraphicsContext::create_command_encoder(device, "Environment Encoder");
+        {
+            let mut render_pass = GraphicsContext::begin_render_pass_colour_only(None, "Environment Pass", view, &mut encoder);
+            render_pass.set_pipeline(pipeline);
+            render_pass.set_bind_group(0, &bind_group, &[]);
+            if let Some(source) = source
+            {
+                render_pass.set_bind_group(1, source, &[]);
+            }
+            render_pass.draw(0..3, 0..1);
+        }
+        // Submitted a face at a time, the convolutions are heavy enough to trip GPU timeouts when batched
+        queue.submit(std::iter::once(encoder.finish()));
+    }
+
+    /// Uploads `intensity`, call after changing it
+    pub fn process_frame(&self, window : &ProspectWindow)
+    {
+        let uniform = EnvironmentUniform
+        {
+            intensity : self.intensity,
+            max_lod : (PREFILTERED_MIP_LEVELS - 1) as f32,
+            _padding : [0.; 2],
+        };
+        GraphicsContext::update_buffer(window.get_queue(), &self.buffer, 0, &[uniform]);
+    }
+
+    /// Layout for bind group slot 2, in place of `ProspectPointLight::get_layout`
+    pub fn get_layout(&self) -> &BindGroupLayout
+    {
+        &self.layout
+    }
+
+    /// Binds the environment maps together with `light`, which still provides the direct lighting
+    pub fn create_bind_group(&self, window : &ProspectWindow, light : &ProspectPointLight) -> SmartBindGroup
+    {
+        let entries = vec![
+            GraphicsContext::create_bind_group_entry(0, light.get_buffer().as_entire_binding()),
+            GraphicsContext::create_texture_view_resource(1, &self.irradiance),
+            GraphicsContext::create_texture_view_resource(2, &self.prefiltered),
+            GraphicsContext::create_texture_view_resource(3, &self.brdf_lut),
+            GraphicsContext::create_sampler_resource(4, &self.sampler),
+            GraphicsContext::create_bind_group_entry(5, self.buffer.as_entire_binding()),
+        ];
+        GraphicsContext::create_bind_group(window.get_device(), &self.name, &self.layout, &entries).into()
+    }
+
+    /// The environment as a cubemap, e.g for `ProspectSkybox`
+    pub fn get_environment(&self) -> &ProspectCubemap
+    {
+        &self.environment
+    }
+
+    pub fn get_name(&self) -> String
+    {
+        self.name.clone()
+    }
+}
@@ -1,4 +1,4 @@
-use wgpu::{TextureView, Texture, Sampler, Device};
+use wgpu::{TextureView, Texture, Sampler, Device, TextureFormat};
 
 use crate::{abstraction::{high_level_abstraction::HighLevelGraphicsContext, graphics_context::GraphicsContext}, prospect_texture::BindableTexture};
 
@@ -6,7 +6,9 @@ pub struct ProspectFramebuffer
 {
     view : TextureView,
     texture : Texture,
-    sampler : Sampler
+    sampler : Sampler,
+    width : u32,
+    height : u32,
 }
 
 impl ProspectFramebuffer
@@ -19,7 +21,24 @@ impl ProspectFramebuffer
         {
             texture,
             view,
-            sampler
+            sampler,
+            width,
+            height
+        }
+    }
+
+    /// Use the surface format here if the framebuffer is drawn to with pipelines built for the window
+    pub fn new_with_format(device : &Device, width : u32, height : u32, format : TextureFormat) -> Self
+    {
+        let (texture, view, sampler) = GraphicsContext::create_framebuffer_with_format(device, "Framebuffer", width, height, format);
+
+        Self
+        {
+            texture,
+            view,
+            sampler,
+            width,
+            height
         }
     }
 
@@ -31,9 +50,26 @@ impl ProspectFramebuffer
         {
             texture,
             view,
-            sampler
+            sampler,
+            width,
+            height
         }
     }
+
+    pub fn get_texture(&self) -> &Texture
+    {
+        &self.texture
+    }
+
+    pub fn get_sampler(&self) -> &Sampler
+    {
+        &self.sampler
+    }
+
+    pub fn get_size(&self) -> (u32, u32)
+    {
+        (self.width, self.height)
+    }
 }
 
 impl BindableTexture for ProspectFramebuffer
@@ -45,4 +81,4 @@ impl BindableTexture for ProspectFramebuffer
     fn get_name(&self) -> String {
         "Framebuffer".to_string()
     }
-}
\ No newline at end of file
+}
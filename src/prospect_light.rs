@@ -58,6 +58,12 @@ impl ProspectPointLight
         &self.layout
     }
 
+    /// The uniform buffer, for binding the light alongside other resources (see `ProspectEnvironment::create_bind_group`)
+    pub fn get_buffer(&self) -> &Buffer
+    {
+        &self.buffer
+    }
+
     fn build_uniform(window : &ProspectWindow, buffer : &Buffer) -> (BindGroupLayout, BindGroup)
     {
         HighLevelGraphicsContext::create_uniform_and_bind_group(window.get_device(), "Point Light Uniform", ShaderStages::VERTEX_FRAGMENT, &buffer)
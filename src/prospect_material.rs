@@ -0,0 +1,147 @@
+use std::{collections::HashMap, rc::Rc};
+
+use bytemuck::NoUninit;
+use wgpu::*;
+
+use crate::{abstraction::{graphics_context::GraphicsContext, high_level_abstraction::HighLevelGraphicsContext, prospect_window::ProspectWindow}, prospect_texture::{BindableTexture, TextureBindLayout}, smart::{SmartBindGroup, SmartRenderPipeline, Versions}};
+
+#[derive(Debug, Clone)]
+struct MaterialData
+{
+    pipeline : SmartRenderPipeline,
+    bind_groups : HashMap<u32, SmartBindGroup>,
+    uniforms : HashMap<u32, Rc<Buffer>>,
+}
+
+/// A pipeline together with the bind groups (textures, samplers and uniform parameters) it is drawn with.
+///
+/// Clones are handles to the same material, so one material can be handed to many meshes and any change made through one of them
+/// (`set_texture`, `set_pipeline`, `update_uniform`...) reaches all of them. Use `duplicate` for a copy that can be changed on its own
+#[derive(Debug, Clone)]
+pub struct Material
+{
+    name : String,
+    data : Versions<MaterialData>,
+}
+
+impl Material
+{
+    pub fn new(name : &str, pipeline : &SmartRenderPipeline) -> Self
+    {
+        Self
+        {
+            name : name.to_string(),
+            data : Versions::new(MaterialData
+            {
+                pipeline : pipeline.clone(),
+                bind_groups : HashMap::new(),
+                uniforms : HashMap::new(),
+            }),
+        }
+    }
+
+    /// A separate material starting with the same pipeline and bind groups, changes to it don't affect this one
+    pub fn duplicate(&self, name : &str) -> Self
+    {
+        Self
+        {
+            name : name.to_string(),
+            data : Versions::new(self.data.latest().clone()),
+        }
+    }
+
+    pub fn get_name(&self) -> &str
+    {
+        &self.name
+    }
+
+    pub fn get_pipeline(&self) -> &SmartRenderPipeline
+    {
+        &self.data.latest().pipeline
+    }
+
+    /// Swaps in a changed copy for every handle, other handles keep the old one alive until they're next changed, cloned or `trim`med
+    fn modify(&mut self, change : impl FnOnce(&mut MaterialData))
+    {
+        let mut data = self.data.latest().clone();
+        change(&mut data);
+        self.data.push(data);
+    }
+
+    /// Swap the pipeline, the bind groups are kept so the new pipeline must use compatible layouts
+    pub fn set_pipeline(&mut self, pipeline : &SmartRenderPipeline) -> &mut Self
+    {
+        self.modify(|data| data.pipeline = pipeline.clone());
+        self
+    }
+
+    pub fn set_bind_group(&mut self, loc : u32, bind_group : &SmartBindGroup) -> &mut Self
+    {
+        self.modify(|data| { data.bind_groups.insert(loc, bind_group.clone()); });
+        self
+    }
+
+    pub fn get_bind_group(&self, loc : u32) -> Option<SmartBindGroup>
+    {
+        self.data.latest().bind_groups.get(&loc).cloned()
+    }
+
+    /// Binds a texture using the layout and sampler of a shader, e.g `Default3D::get_texture_layout`
+    pub fn set_texture<T : BindableTexture + ?Sized>(&mut self, loc : u32, layout : &TextureBindLayout, texture : &T, window : &ProspectWindow) -> &mut Self
+    {
+        let bind_group = layout.bind_prospect_texture(texture, window);
+        self.set_bind_group(loc, &bind_group)
+    }
+
+    pub fn set_texture_with_sampler<T : BindableTexture + ?Sized>(&mut self, loc : u32, layout : &TextureBindLayout, texture : &T, sampler : &Sampler, window : &ProspectWindow) -> &mut Self
+    {
+        let bind_group : SmartBindGroup = layout.create_texture_with_sampler(window, texture.get_texture_view(), sampler, &texture.get_name()).into();
+        self.set_bind_group(loc, &bind_group)
+    }
+
+    /// Creates a uniform buffer for `data` bound at binding 0 of `layout`
+    pub fn set_uniform<A : NoUninit>(&mut self, loc : u32, layout : &BindGroupLayout, data : &A, window : &ProspectWindow) -> &mut Self
+    {
+        let label = format!("{} Uniform {}", self.name, loc);
+        let buffer = GraphicsContext::create_buffer(window.get_device(), &label, &[*data], BufferUsages::UNIFORM | BufferUsages::COPY_DST);
+        let bind_group : SmartBindGroup = HighLevelGraphicsContext::create_uniform_with_bind_group(window.get_device(), &label, &buffer, layout).into();
+
+        self.modify(|material|
+        {
+            material.uniforms.insert(loc, Rc::new(buffer));
+            material.bind_groups.insert(loc, bind_group);
+        });
+        self
+    }
+
+    /// Writes new parameters into a uniform created with `set_uniform`
+    pub fn update_uniform<A : NoUninit>(&self, loc : u32, data : &A, window : &ProspectWindow) -> Result<(), ()>
+    {
+        let buffer = self.data.latest().uniforms.get(&loc).ok_or(())?;
+        GraphicsContext::update_buffer(window.get_queue(), buffer, 0, &[*data]);
+        Ok(())
+    }
+
+    /// Lets go of earlier versions of the material and of pipelines and bind groups that hot reloading has replaced, see `SmartBindGroup::trim`
+    pub fn trim(&mut self)
+    {
+        let latest = self.data.latest();
+        if latest.pipeline.is_stale() || latest.bind_groups.values().any(SmartBindGroup::is_stale)
+        {
+            // Clones of the handles start at their latest versions
+            self.modify(|_| ());
+        }
+        self.data.trim();
+    }
+
+    pub fn apply<'a>(&'a self, render_pass : &mut RenderPass<'a>)
+    {
+        let data = self.data.latest();
+        data.pipeline.apply(render_pass);
+
+        for (loc, bind_group) in &data.bind_groups
+        {
+            bind_group.set_bind_group(render_pass, *loc, &[]);
+        }
+    }
+}
@@ -0,0 +1,356 @@
+use std::{collections::HashMap, f32::consts::{PI, TAU}};
+
+use crate::{abstraction::{mesh_processing::weld_vertices, vertex::Vertex}, prospect_shape::ProspectShape, utils::math::{add, cross, dot, normalize, scale, sub}};
+
+#[derive(Default)]
+struct ShapeBuilder
+{
+    vertices : Vec<Vertex>,
+    indices : Vec<u32>,
+}
+
+impl ShapeBuilder
+{
+    fn vertex(&mut self, position : [f32; 3], uv : [f32; 2], normal : [f32; 3]) -> u32
+    {
+        self.vertices.push(Vertex { position, uv, normal });
+        (self.vertices.len() - 1) as u32
+    }
+
+    /// Wound so the face points the same way as its vertex normals, skipping triangles with no area (e.g at the poles of a sphere)
+    fn triangle(&mut self, a : u32, b : u32, c : u32)
+    {
+        let [pa, pb, pc] = [a, b, c].map(|index| self.vertices[index as usize].position);
+        // The way `Mesh` draws them, see `mesh_processing::face_normal`
+        let face = cross(sub(pc, pa), sub(pb, pa));
+        if dot(face, face) <= f32::EPSILON * f32::EPSILON
+        {
+            return;
+        }
+
+        let normals = [a, b, c].iter().fold([0.; 3], |sum, index| add(sum, self.vertices[*index as usize].normal));
+        if dot(face, normals) >= 0.
+        {
+            self.indices.extend([a, b, c]);
+        } else
+        {
+            self.indices.extend([a, c, b]);
+        }
+    }
+
+    /// A `columns` by `rows` grid of quads, `point` gives the position and normal at a uv from 0 to 1. V is 0 at the top like wgpu's
+    fn surface<F>(&mut self, columns : u32, rows : u32, point : F)
+        where   F : Fn(f32, f32) -> ([f32; 3], [f32; 3])
+    {
+        let start = self.vertices.len() as u32;
+        for row in 0..=rows
+        {
+            for column in 0..=columns
+            {
+                let uv = [column as f32 / columns as f32, row as f32 / rows as f32];
+                let (position, normal) = point(uv[0], uv[1]);
+                self.vertex(position, uv, normal);
+            }
+        }
+
+        let stride = columns + 1;
+        for row in 0..rows
+        {
+            for column in 0..columns
+            {
+                let top_left = start + row * stride + column;
+                let bottom_left = top_left + stride;
+                self.triangle(top_left, bottom_left, top_left + 1);
+                self.triangle(top_left + 1, bottom_left, bottom_left + 1);
+            }
+        }
+    }
+
+    /// Spins a profile of `(radius, y, normal radius, normal y)` from top to bottom around the Y axis
+    fn lathe(&mut self, profile : &[(f32, f32, f32, f32)], segments : u32)
+    {
+        // V follows the distance along the profile so textures aren't squashed where points are closer together
+        let mut lengths = vec![0.];
+        for pair in profile.windows(2)
+        {
+            let step = ((pair[1].0 - pair[0].0).powi(2) + (pair[1].1 - pair[0].1).powi(2)).sqrt();
+            lengths.push(lengths[lengths.len() - 1] + step);
+        }
+        let total = lengths[lengths.len() - 1].max(f32::EPSILON);
+
+        let start = self.vertices.len() as u32;
+        for ((radius, y, normal_radius, normal_y), length) in profile.iter().zip(&lengths)
+        {
+            for segment in 0..=segments
+            {
+                let u = segment as f32 / segments as f32;
+                let (sin, cos) = (u * TAU).sin_cos();
+                self.vertex([radius * sin, *y, radius * cos], [u, length / total], normalize([normal_radius * sin, *normal_y, normal_radius * cos]).unwrap_or([0., 1., 0.]));
+            }
+        }
+
+        let stride = segments + 1;
+        for row in 0..profile.len() as u32 - 1
+        {
+            for column in 0..segments
+            {
+                let top_left = start + row * stride + column;
+                let bottom_left = top_left + stride;
+                self.triangle(top_left, bottom_left, top_left + 1);
+                self.triangle(top_left + 1, bottom_left, bottom_left + 1);
+            }
+        }
+    }
+
+    /// A flat circle at `y` facing up or down, with the texture mapped straight down onto it
+    fn disc(&mut self, radius : f32, y : f32, up : bool, segments : u32)
+    {
+        let normal = [0., if up {1.} else {-1.}, 0.];
+        let centre = self.vertex([0., y, 0.], [0.5, 0.5], normal);
+        let start = self.vertices.len() as u32;
+        for segment in 0..=segments
+        {
+            let (sin, cos) = (segment as f32 / segments as f32 * TAU).sin_cos();
+            self.vertex([radius * sin, y, radius * cos], [0.5 + sin * 0.5, 0.5 + cos * 0.5], normal);
+        }
+        for segment in 0..segments
+        {
+            self.triangle(centre, start + segment, start + segment + 1);
+        }
+    }
+
+    fn build(self) -> ProspectShape<Vec<Vertex>, Vec<u32>>
+    {
+        ProspectShape { vertices : self.vertices, indices : Some(self.indices) }
+    }
+}
+
+/// A box centred on the origin, each face split into `segments` by `segments` quads with the whole texture on it, upright on the sides
+pub fn cuboid(size : [f32; 3], segments : u32) -> ProspectShape<Vec<Vertex>, Vec<u32>>
+{
+    let segments = segments.max(1);
+    let half = scale(size, 0.5);
+    // Normal, then the directions of U and V seen from outside
+    let faces : [([f32; 3], [f32; 3], [f32; 3]); 6] = [
+        ([1., 0., 0.], [0., 0., -1.], [0., -1., 0.]),
+        ([-1., 0., 0.], [0., 0., 1.], [0., -1., 0.]),
+        ([0., 0., 1.], [1., 0., 0.], [0., -1., 0.]),
+        ([0., 0., -1.], [-1., 0., 0.], [0., -1., 0.]),
+        ([0., 1., 0.], [1., 0., 0.], [0., 0., 1.]),
+        ([0., -1., 0.], [1., 0., 0.], [0., 0., -1.]),
+    ];
+
+    let mut builder = ShapeBuilder::default();
+    for (normal, u_axis, v_axis) in faces
+    {
+        let extent = |axis : [f32; 3]| dot(axis.map(f32::abs), half);
+        let centre = scale(normal, extent(normal));
+        let (u_extent, v_extent) = (extent(u_axis), extent(v_axis));
+        builder.surface(segments, segments, |u, v|
+        {
+            let position = add(centre, add(scale(u_axis, (u * 2. - 1.) * u_extent), scale(v_axis, (v * 2. - 1.) * v_extent)));
+            (position, normal)
+        });
+    }
+    builder.build()
+}
+
+/// A cube `size` across, see `cuboid`
+pub fn cube(size : f32, segments : u32) -> ProspectShape<Vec<Vertex>, Vec<u32>>
+{
+    cuboid([size; 3], segments)
+}
+
+/// A flat plane on X and Z facing up, split into `subdivisions` quads along each and stretching the texture across all of them
+pub fn plane(size : [f32; 2], subdivisions : [u32; 2]) -> ProspectShape<Vec<Vertex>, Vec<u32>>
+{
+    let mut builder = ShapeBuilder::default();
+    builder.surface(subdivisions[0].max(1), subdivisions[1].max(1), |u, v|
+    {
+        ([(u - 0.5) * size[0], 0., (v - 0.5) * size[1]], [0., 1., 0.])
+    });
+    builder.build()
+}
+
+/// A sphere made of `segments` slices around Y and `rings` from pole to pole, with the texture wrapped around it equirectangularly
+pub fn uv_sphere(radius : f32, segments : u32, rings : u32) -> ProspectShape<Vec<Vertex>, Vec<u32>>
+{
+    let rings = rings.max(2);
+    let profile : Vec<_> = (0..=rings).map(|ring|
+    {
+        let (sin, cos) = (ring as f32 / rings as f32 * PI).sin_cos();
+        (radius * sin, radius * cos, sin, cos)
+    }).collect();
+
+    let mut builder = ShapeBuilder::default();
+    builder.lathe(&profile, segments.max(3));
+    builder.build()
+}
+
+/// The direction's place on an equirectangular texture, matching `uv_sphere`
+fn sphere_uv(direction : [f32; 3]) -> [f32; 2]
+{
+    let u = direction[0].atan2(direction[2]) / TAU;
+    [if u < 0. {u + 1.} else {u}, direction[1].clamp(-1., 1.).acos() / PI]
+}
+
+/// A sphere from an icosahedron with each triangle split into 4, `subdivisions` times. Triangles are much more even than a `uv_sphere`'s
+/// with the same texture mapping, vertices along the seam and at the poles are split so it doesn't smear
+pub fn icosphere(radius : f32, subdivisions : u32) -> ProspectShape<Vec<Vertex>, Vec<u32>>
+{
+    let t = (1. + 5f32.sqrt()) / 2.;
+    let mut directions : Vec<[f32; 3]> = [
+        [-1., t, 0.], [1., t, 0.], [-1., -t, 0.], [1., -t, 0.],
+        [0., -1., t], [0., 1., t], [0., -1., -t], [0., 1., -t],
+        [t, 0., -1.], [t, 0., 1.], [-t, 0., -1.], [-t, 0., 1.],
+    ].map(|direction| normalize(direction).unwrap_or(direction)).to_vec();
+    let mut triangles : Vec<[u32; 3]> = vec![
+        [0, 11, 5], [0, 5, 1], [0, 1, 7], [0, 7, 10], [0, 10, 11],
+        [1, 5, 9], [5, 11, 4], [11, 10, 2], [10, 7, 6], [7, 1, 8],
+        [3, 9, 4], [3, 4, 2], [3, 2, 6], [3, 6, 8], [3, 8, 9],
+        [4, 9, 5], [2, 4, 11], [6, 2, 10], [8, 6, 7], [9, 8, 1],
+    ];
+
+    for _ in 0..subdivisions
+    {
+        let mut midpoints : HashMap<(u32, u32), u32> = HashMap::new();
+        let mut midpoint = |a : u32, b : u32, directions : &mut Vec<[f32; 3]>|
+        {
+            *midpoints.entry((a.min(b), a.max(b))).or_insert_with(||
+            {
+                let sum = add(directions[a as usize], directions[b as usize]);
+                directions.push(normalize(sum).unwrap_or(sum));
+                (directions.len() - 1) as u32
+            })
+        };
+
+        triangles = triangles.into_iter().flat_map(|[a, b, c]|
+        {
+            let ab = midpoint(a, b, &mut directions);
+            let bc = midpoint(b, c, &mut directions);
+            let ca = midpoint(c, a, &mut directions);
+            [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+        }).collect();
+    }
+
+    let mut builder = ShapeBuilder::default();
+    for triangle in triangles
+    {
+        let mut uvs = triangle.map(|index| sphere_uv(directions[index as usize]));
+
+        // Triangles across the seam would otherwise stretch back over the whole texture
+        let max_u = uvs.iter().map(|uv| uv[0]).fold(0., f32::max);
+        if max_u > 0.75
+        {
+            for uv in uvs.iter_mut().filter(|uv| uv[0] < 0.25)
+            {
+                uv[0] += 1.;
+            }
+        }
+
+        // U means nothing at a pole, so it takes the middle of the other two
+        for corner in 0..3
+        {
+            let direction = directions[triangle[corner] as usize];
+            if direction[0].abs() < 1e-6 && direction[2].abs() < 1e-6
+            {
+                uvs[corner][0] = (uvs[(corner + 1) % 3][0] + uvs[(corner + 2) % 3][0]) / 2.;
+            }
+        }
+
+        let [a, b, c] = [0, 1, 2].map(|corner|
+        {
+            let direction = directions[triangle[corner] as usize];
+            builder.vertex(scale(direction, radius), uvs[corner], direction)
+        });
+        builder.triangle(a, b, c);
+    }
+
+    // Every triangle has its own vertices so far, share the ones that ended up the same
+    let shape = builder.build();
+    let indices = shape.indices.unwrap_or_default();
+    let (vertices, indices) = weld_vertices(&shape.vertices, &indices, 0.);
+    ProspectShape { vertices, indices : Some(indices) }
+}
+
+/// A cylinder along Y centred on the origin, `segments` around and `rings` along its height, with optional caps at either end
+pub fn cylinder(radius : f32, height : f32, segments : u32, rings : u32, caps : bool) -> ProspectShape<Vec<Vertex>, Vec<u32>>
+{
+    let (segments, rings) = (segments.max(3), rings.max(1));
+    let profile : Vec<_> = (0..=rings).map(|ring| (radius, height * (0.5 - ring as f32 / rings as f32), 1., 0.)).collect();
+
+    let mut builder = ShapeBuilder::default();
+    builder.lathe(&profile, segments);
+    if caps
+    {
+        builder.disc(radius, height / 2., true, segments);
+        builder.disc(radius, -height / 2., false, segments);
+    }
+    builder.build()
+}
+
+/// A cone along Y with its point at the top and its base at the bottom, centred on the origin. The base is capped if `cap` is set
+pub fn cone(radius : f32, height : f32, segments : u32, rings : u32, cap : bool) -> ProspectShape<Vec<Vertex>, Vec<u32>>
+{
+    let (segments, rings) = (segments.max(3), rings.max(1));
+    // The side leans back by the same slope the cone narrows at
+    let profile : Vec<_> = (0..=rings).map(|ring|
+    {
+        let along = ring as f32 / rings as f32;
+        (radius * along, height * (0.5 - along), height, radius)
+    }).collect();
+
+    let mut builder = ShapeBuilder::default();
+    builder.lathe(&profile, segments);
+    if cap
+    {
+        builder.disc(radius, -height / 2., false, segments);
+    }
+    builder.build()
+}
+
+/// A cylinder along Y with hemispheres on each end, `height` is the length of the straight part between them.
+/// `rings` are per hemisphere, and the texture wraps the whole thing once
+pub fn capsule(radius : f32, height : f32, segments : u32, rings : u32) -> ProspectShape<Vec<Vertex>, Vec<u32>>
+{
+    let rings = rings.max(1);
+    let mut profile = Vec::with_capacity(rings as usize * 2 + 2);
+    for (offset, start, end) in [(height / 2., 0., PI / 2.), (-height / 2., PI / 2., PI)]
+    {
+        for ring in 0..=rings
+        {
+            let (sin, cos) = (start + (end - start) * ring as f32 / rings as f32).sin_cos();
+            profile.push((radius * sin, offset + radius * cos, sin, cos));
+        }
+    }
+
+    let mut builder = ShapeBuilder::default();
+    builder.lathe(&profile, segments.max(3));
+    builder.build()
+}
+
+/// A ring around Y, `major_radius` to the middle of the tube and `minor_radius` across the tube.
+/// `segments` go around the ring and `sides` around the tube
+pub fn torus(major_radius : f32, minor_radius : f32, segments : u32, sides : u32) -> ProspectShape<Vec<Vertex>, Vec<u32>>
+{
+    let sides = sides.max(3);
+    let profile : Vec<_> = (0..=sides).map(|side|
+    {
+        // Starting from the top of the tube, going outwards first
+        let (sin, cos) = (side as f32 / sides as f32 * TAU).sin_cos();
+        (major_radius + minor_radius * sin, minor_radius * cos, sin, cos)
+    }).collect();
+
+    let mut builder = ShapeBuilder::default();
+    builder.lathe(&profile, segments.max(3));
+    builder.build()
+}
+
+/// Two triangles covering clip space from -1 to 1, for shaders that output positions as they are. UV 0, 0 is the top left
+pub fn fullscreen_quad() -> ProspectShape<Vec<Vertex>, Vec<u32>>
+{
+    // Clip space looks along +Z, so facing the screen is facing -Z
+    let mut builder = ShapeBuilder::default();
+    builder.surface(1, 1, |u, v| ([u * 2. - 1., 1. - v * 2., 0.], [0., 0., -1.]));
+    builder.build()
+}
@@ -1,11 +1,125 @@
+use crate::abstraction::{vertex::{Vertex, TangentVertex}, tangents::generate_tangents, mesh_processing};
 
-
-use crate::abstraction::vertex::Vertex;
-
+/// `VecList` can be anything that converts into a `Vec` of a `VertexLayout`, such as `Vec<Vertex>` or `&[ColourVertex]`
 pub struct ProspectShape<VecList, IndexList>
-    where   VecList : Into<Vec<Vertex>>,
-            IndexList : Into<Vec<u32>>
 {
     pub vertices : VecList,
     pub indices : Option<IndexList>,
-}
\ No newline at end of file
+}
+
+impl<VecList, IndexList> ProspectShape<VecList, IndexList>
+    where   IndexList : Into<Vec<u32>> + Clone
+{
+    pub fn get_vertices<V>(&self) -> Vec<V>
+        where   VecList : Into<Vec<V>> + Clone
+    {
+        self.vertices.clone().into()
+    }
+
+    /// The indices, or generated ones if the shape has none
+    pub fn get_indices<V>(&self) -> Vec<u32>
+        where   VecList : Into<Vec<V>> + Clone
+    {
+        match &self.indices
+        {
+            Some(indices) => indices.clone().into(),
+            None =>
+            {
+                // Auto Generate indices
+                let l = self.get_vertices::<V>().len() as u32;
+                (0..l).map(|i| l - i - 1).collect()
+            }
+        }
+    }
+
+    /// Vertices with tangents for normal mapping and the indices to draw them with, see `generate_tangents`
+    pub fn with_tangents(&self) -> (Vec<TangentVertex>, Vec<u32>)
+        where   VecList : Into<Vec<Vertex>> + Clone
+    {
+        generate_tangents(&self.get_vertices::<Vertex>(), &self.get_indices::<Vertex>())
+    }
+
+    fn processed<F>(&self, process : F) -> ProspectShape<Vec<Vertex>, Vec<u32>>
+        where   VecList : Into<Vec<Vertex>> + Clone,
+                F : FnOnce(&[Vertex], &[u32]) -> (Vec<Vertex>, Vec<u32>)
+    {
+        let (vertices, indices) = process(&self.get_vertices::<Vertex>(), &self.get_indices::<Vertex>());
+        ProspectShape { vertices, indices : Some(indices) }
+    }
+
+    /// A copy with one normal per face, see `generate_flat_normals`
+    pub fn with_flat_normals(&self) -> ProspectShape<Vec<Vertex>, Vec<u32>>
+        where   VecList : Into<Vec<Vertex>> + Clone
+    {
+        self.processed(mesh_processing::generate_flat_normals)
+    }
+
+    /// A copy with faces less than `angle_threshold` degrees apart smoothed together, see `generate_smooth_normals`
+    pub fn with_smooth_normals(&self, angle_threshold : f32) -> ProspectShape<Vec<Vertex>, Vec<u32>>
+        where   VecList : Into<Vec<Vertex>> + Clone
+    {
+        self.processed(|vertices, indices| mesh_processing::generate_smooth_normals(vertices, indices, angle_threshold))
+    }
+
+    /// A copy with duplicate vertices merged, see `weld_vertices`
+    pub fn welded(&self, tolerance : f32) -> ProspectShape<Vec<Vertex>, Vec<u32>>
+        where   VecList : Into<Vec<Vertex>> + Clone
+    {
+        self.processed(|vertices, indices| mesh_processing::weld_vertices(vertices, indices, tolerance))
+    }
+
+    /// A copy ordered for the vertex cache, see `optimise_vertex_cache`
+    pub fn optimised(&self) -> ProspectShape<Vec<Vertex>, Vec<u32>>
+        where   VecList : Into<Vec<Vertex>> + Clone
+    {
+        self.processed(mesh_processing::optimise_vertex_cache)
+    }
+
+    /// A copy with about `ratio` of the triangles, see `simplify`
+    pub fn simplified(&self, ratio : f32) -> ProspectShape<Vec<Vertex>, Vec<u32>>
+        where   VecList : Into<Vec<Vertex>> + Clone
+    {
+        self.processed(|vertices, indices|
+        {
+            let target = (indices.len() / 3) as f32 * ratio.clamp(0., 1.);
+            mesh_processing::simplify(vertices, indices, target as usize * 3)
+        })
+    }
+
+    /// `levels` shapes for level of detail, the first is this one optimised and each after has about `ratio` of the triangles of the one before.
+    /// Levels are optimised for the vertex cache, and the chain stops early if a level can't be simplified any further
+    pub fn lod_chain(&self, levels : usize, ratio : f32) -> Vec<ProspectShape<Vec<Vertex>, Vec<u32>>>
+        where   VecList : Into<Vec<Vertex>> + Clone
+    {
+        let mut chain : Vec<ProspectShape<Vec<Vertex>, Vec<u32>>> = Vec::with_capacity(levels);
+        for _ in 0..levels
+        {
+            let level = match chain.last()
+            {
+                Some(previous) => previous.simplified(ratio).optimised(),
+                None => self.optimised(),
+            };
+
+            if chain.last().is_some_and(|previous| previous.indices.as_ref().map(Vec::len) == level.indices.as_ref().map(Vec::len))
+            {
+                break;
+            }
+            chain.push(level);
+        }
+        chain
+    }
+}
+
+impl ProspectShape<Vec<Vertex>, Vec<u32>>
+{
+    /// Joins shapes into one, each moved by its column major matrix first. See `merge_meshes`
+    pub fn merge<'a, VecList, IndexList, I>(parts : I) -> Self
+        where   VecList : Into<Vec<Vertex>> + Clone + 'a,
+                IndexList : Into<Vec<u32>> + Clone + 'a,
+                I : IntoIterator<Item = (&'a ProspectShape<VecList, IndexList>, [[f32; 4]; 4])>
+    {
+        let geometry : Vec<_> = parts.into_iter().map(|(shape, matrix)| (shape.get_vertices::<Vertex>(), shape.get_indices::<Vertex>(), matrix)).collect();
+        let (vertices, indices) = mesh_processing::merge_meshes(geometry.iter().map(|(vertices, indices, matrix)| (vertices.as_slice(), indices.as_slice(), *matrix)));
+        ProspectShape { vertices, indices : Some(indices) }
+    }
+}
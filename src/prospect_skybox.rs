@@ -0,0 +1,116 @@
+use wgpu::*;
+
+use crate::{abstraction::{graphics_context::GraphicsContext, prospect_window::ProspectWindow}, prospect_camera::ProspectCamera, prospect_texture::BindableTexture};
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct SkyboxUniform
+{
+    view_proj : [f32; 16],
+}
+
+/// Draws a cubemap behind everything else, only following the camera's rotation.
+///
+/// Draw it inside the main render pass after the opaque meshes, so it only fills the pixels they didn't cover
+pub struct ProspectSkybox
+{
+    pipeline : RenderPipeline,
+    layout : BindGroupLayout,
+    sampler : Sampler,
+    buffer : Buffer,
+    bind_group : BindGroup,
+}
+
+impl ProspectSkybox
+{
+    /// `cubemap` must have a cube view, e.g `ProspectCubemap`
+    pub fn new<T : BindableTexture + ?Sized>(window : &ProspectWindow, cubemap : &T) -> Self
+    {
+        let device = window.get_device();
+        let module = GraphicsContext::load_shader("Skybox Shader", include_str!("shaders/skybox.wgsl"), device);
+
+        let entries = vec![
+            GraphicsContext::create_bind_group_layout_entry(0, ShaderStages::FRAGMENT, GraphicsContext::create_texture_binding_type(false, TextureViewDimension::Cube, TextureSampleType::Float { filterable: true })),
+            GraphicsContext::create_bind_group_layout_entry(1, ShaderStages::FRAGMENT, GraphicsContext::create_sample_binding_type(SamplerBindingType::Filtering)),
+            GraphicsContext::create_bind_group_layout_entry(2, ShaderStages::VERTEX, GraphicsContext::create_uniform_binding_type()),
+        ];
+        let layout = GraphicsContext::create_bind_group_layout(device, "Skybox Bind Group Layout", &entries);
+        let pipeline_layout = GraphicsContext::create_pipeline_layout("Skybox Pipeline Layout", device, &vec![&layout]);
+
+        let targets = [Some(ColorTargetState {
+            format: window.get_surface_config().format,
+            blend: Some(BlendState::REPLACE),
+            write_mask: ColorWrites::ALL,
+        })];
+
+        let fragment_state = FragmentState {
+            module: &module,
+            entry_point: "fs_main",
+            targets: &targets,
+        };
+
+        let vertex_state = VertexState {
+            module: &module,
+            entry_point: "vs_main",
+            buffers: &[],
+        };
+
+        // The camera is inside the cube, so draw both sides
+        let mut state = GraphicsContext::DEFAULT_PRIMITIVE_STATE;
+        state.cull_mode = None;
+
+        // Everything is at the far plane, so pass where nothing has been drawn and never write depth
+        let depth_stencil = DepthStencilState {
+            format: GraphicsContext::DEPTH_FORMAT,
+            depth_write_enabled: false,
+            depth_compare: CompareFunction::LessEqual,
+            stencil: StencilState::default(),
+            bias: DepthBiasState::default(),
+        };
+
+        let pipeline = GraphicsContext::create_render_pipeline_with_depth_stencil("Skybox Pipeline", &pipeline_layout, fragment_state, vertex_state, device, state, depth_stencil);
+
+        let sampler = GraphicsContext::create_sampler_advanced("Skybox Sampler", device, Some(FilterMode::Linear), Some(FilterMode::Linear), Some(FilterMode::Linear), None, None, None);
+        let buffer = GraphicsContext::create_buffer(device, "Skybox Uniform Buffer", &[SkyboxUniform { view_proj : [0.; 16] }], BufferUsages::UNIFORM | BufferUsages::COPY_DST);
+        let bind_group = Self::create_bind_group(device, &layout, &sampler, &buffer, cubemap.get_texture_view());
+
+        Self
+        {
+            pipeline,
+            layout,
+            sampler,
+            buffer,
+            bind_group,
+        }
+    }
+
+    fn create_bind_group(device : &Device, layout : &BindGroupLayout, sampler : &Sampler, buffer : &Buffer, view : &TextureView) -> BindGroup
+    {
+        let entries = vec![
+            GraphicsContext::create_texture_view_resource(0, view),
+            GraphicsContext::create_sampler_resource(1, sampler),
+            GraphicsContext::create_bind_group_entry(2, buffer.as_entire_binding()),
+        ];
+        GraphicsContext::create_bind_group(device, "Skybox Bind Group", layout, &entries)
+    }
+
+    pub fn set_cubemap<T : BindableTexture + ?Sized>(&mut self, window : &ProspectWindow, cubemap : &T)
+    {
+        self.bind_group = Self::create_bind_group(window.get_device(), &self.layout, &self.sampler, &self.buffer, cubemap.get_texture_view());
+    }
+
+    /// Call after `ProspectCamera::process_frame`
+    pub fn process_frame(&self, window : &ProspectWindow, camera : &ProspectCamera)
+    {
+        let matrix = camera.generate_rotation_matrix(window.size.0 as f32, window.size.1 as f32);
+        let uniform = SkyboxUniform { view_proj : matrix.get_column_major().get_contents() };
+        GraphicsContext::update_buffer(window.get_queue(), &self.buffer, 0, &[uniform]);
+    }
+
+    pub fn draw<'a>(&'a self, render_pass : &mut RenderPass<'a>)
+    {
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.draw(0..36, 0..1);
+    }
+}
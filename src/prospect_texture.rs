@@ -1,17 +1,137 @@
+use std::rc::Rc;
+
+use image::RgbaImage;
 use wgpu::*;
 
-use crate::{utils::prospect_fs::read_file_option, abstraction::{high_level_abstraction::HighLevelGraphicsContext, prospect_window::ProspectWindow}};
+use crate::{utils::prospect_fs::read_file_option, abstraction::{high_level_abstraction::HighLevelGraphicsContext, prospect_window::ProspectWindow, graphics_context::GraphicsContext, mipmaps::AtlasLayout}, smart::SmartBindGroup};
 
 pub trait BindableTexture
 {
     fn get_texture_view(&self) -> &TextureView;
     fn get_name(&self) -> String;
+
+    /// A sampler that should be used instead of the shader's own one
+    fn get_sampler(&self) -> Option<&Sampler> {None}
+}
+
+/// Everything needed to create a `Sampler`, defaults to trilinear filtering with clamped edges
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SamplerOptions
+{
+    pub address_mode_u : AddressMode,
+    pub address_mode_v : AddressMode,
+    pub address_mode_w : AddressMode,
+    pub mag_filter : FilterMode,
+    pub min_filter : FilterMode,
+    pub mipmap_filter : FilterMode,
+    /// 1 turns it off, anything higher (up to 16) requires every filter to be `Linear`
+    pub anisotropy : u16,
+    /// wgpu samplers have no bias, so this is rounded to whole mip levels and applied by skipping the largest levels of the texture view. Only positive values do anything
+    pub lod_bias : f32,
+    pub lod_min_clamp : f32,
+    pub lod_max_clamp : f32,
+}
+
+impl Default for SamplerOptions
+{
+    fn default() -> Self
+    {
+        Self
+        {
+            address_mode_u : AddressMode::ClampToEdge,
+            address_mode_v : AddressMode::ClampToEdge,
+            address_mode_w : AddressMode::ClampToEdge,
+            mag_filter : FilterMode::Linear,
+            min_filter : FilterMode::Linear,
+            mipmap_filter : FilterMode::Linear,
+            anisotropy : 1,
+            lod_bias : 0.,
+            lod_min_clamp : 0.,
+            lod_max_clamp : 32.,
+        }
+    }
+}
+
+impl SamplerOptions
+{
+    /// Nearest filtering up close for pixel art, still blending between mip levels in the distance
+    pub fn pixelated() -> Self
+    {
+        Self
+        {
+            mag_filter : FilterMode::Nearest,
+            min_filter : FilterMode::Nearest,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_address_mode(mut self, address_mode : AddressMode) -> Self
+    {
+        self.address_mode_u = address_mode;
+        self.address_mode_v = address_mode;
+        self.address_mode_w = address_mode;
+        self
+    }
+
+    pub fn create_sampler(&self, label : &str, device : &Device) -> Sampler
+    {
+        let linear = self.mag_filter == FilterMode::Linear && self.min_filter == FilterMode::Linear && self.mipmap_filter == FilterMode::Linear;
+        let anisotropy = if !linear && self.anisotropy > 1
+        {
+            log::warn!("Anisotropic filtering on sampler \"{}\" needs linear filtering, disabling it", label);
+            1
+        } else
+        {
+            self.anisotropy.clamp(1, 16)
+        };
+
+        device.create_sampler(&SamplerDescriptor {
+            label: Some(label),
+            address_mode_u: self.address_mode_u,
+            address_mode_v: self.address_mode_v,
+            address_mode_w: self.address_mode_w,
+            mag_filter: self.mag_filter,
+            min_filter: self.min_filter,
+            mipmap_filter: self.mipmap_filter,
+            lod_min_clamp: self.lod_min_clamp,
+            lod_max_clamp: self.lod_max_clamp,
+            anisotropy_clamp: anisotropy,
+            ..Default::default()
+        })
+    }
+}
+
+/// How `ProspectTexture::from_image_with_options` uploads an image
+#[derive(Debug, Clone, Copy)]
+pub struct TextureOptions
+{
+    /// Use `TextureFormat::Rgba8Unorm` for normal and other data maps
+    pub format : TextureFormat,
+    pub mipmaps : bool,
+    /// Keeps the tiles of an atlas separate when generating mipmaps
+    pub atlas : Option<AtlasLayout>,
+    pub sampler : SamplerOptions,
+}
+
+impl Default for TextureOptions
+{
+    fn default() -> Self
+    {
+        Self
+        {
+            format : TextureFormat::Rgba8UnormSrgb,
+            mipmaps : true,
+            atlas : None,
+            sampler : SamplerOptions::default(),
+        }
+    }
 }
 
 pub struct ProspectTexture
 {
     name : String,
     view : TextureView,
+    sampler : Option<Sampler>,
 }
 
 impl ProspectTexture
@@ -30,10 +150,12 @@ impl ProspectTexture
         Ok(Self
         {
             name : name.to_string(),
-            view : HighLevelGraphicsContext::create_texture_from_file(name, contents, window),
+            view : HighLevelGraphicsContext::create_texture_from_file(name, contents, window).map_err(|error| log::error!("Unable to decode {}: {}", name, error))?,
+            sampler : None,
         })
     }
 
+    /// Panics if `contents` isn't a valid image
     pub fn from_string<S : AsRef<str>>(name : &str, contents : S, window : &mut ProspectWindow) -> Self
     {
         let contents = contents.as_ref();
@@ -42,26 +164,76 @@ impl ProspectTexture
         Self
         {
             name : name.to_string(),
-            view : HighLevelGraphicsContext::create_texture_from_file(name, contents, window),
+            view : HighLevelGraphicsContext::create_texture_from_file(name, contents, window).unwrap_or_else(|error| panic!("{} is not a valid image: {}", name, error)),
+            sampler : None,
         }
     }
 
-    pub fn from_bytes(name : &str, width : u32, height : u32,bytes : Vec<u8>, window : &mut ProspectWindow) -> Self
+    /// `bytes` are RGBA texels, fails if there aren't exactly `width` x `height` of them
+    pub fn from_bytes(name : &str, width : u32, height : u32,bytes : Vec<u8>, window : &mut ProspectWindow) -> Result<Self, ()>
+    {
+        Ok(Self
+        {
+            name : name.to_string(),
+            view : HighLevelGraphicsContext::create_texture_from_bytes(name, width, height, bytes, window)?,
+            sampler : None,
+        })
+    }
+
+    /// Still Requires a valid image, panics otherwise
+    pub fn image_file_from_bytes(name : &str, contents : &[u8], window : &mut ProspectWindow) -> Self
     {
         Self
         {
             name : name.to_string(),
-            view : HighLevelGraphicsContext::create_texture_from_bytes(name, width, height, bytes, window).unwrap()
+            view : HighLevelGraphicsContext::create_texture_from_file(name, contents, window).unwrap_or_else(|error| panic!("{} is not a valid image: {}", name, error)),
+            sampler : None,
         }
     }
 
+    /// Same as `image_file_from_bytes` but the texels are not treated as sRGB, use this for normal, roughness or other data maps.
+    /// Fails if `contents` isn't a valid image
+    pub fn image_file_from_bytes_linear(name : &str, contents : &[u8], window : &mut ProspectWindow) -> Result<Self, image::ImageError>
+    {
+        Ok(Self
+        {
+            name : name.to_string(),
+            view : HighLevelGraphicsContext::create_texture_from_file_with_format(name, contents, TextureFormat::Rgba8Unorm, window)?,
+            sampler : None,
+        })
+    }
+
     /// Still Requires a valid image
-    pub fn image_file_from_bytes(name : &str, contents : &[u8], window : &mut ProspectWindow) -> Self
+    pub fn image_file_from_bytes_with_options(name : &str, contents : &[u8], options : &TextureOptions, window : &mut ProspectWindow) -> Result<Self, ()>
+    {
+        let image = image::load_from_memory(contents).map_err(|_| ())?;
+        Ok(Self::from_image_with_options(name, image.to_rgba8(), options, window))
+    }
+
+    /// Uploads `image` with its own sampler, which `TextureBindLayout::bind_prospect_texture` uses instead of the shader's
+    pub fn from_image_with_options(name : &str, image : RgbaImage, options : &TextureOptions, window : &mut ProspectWindow) -> Self
     {
+        let texture = GraphicsContext::create_texture_from_image_with_mipmaps(name, image, window.get_device(), window.get_queue(), options.format, options.mipmaps.then_some(window.get_mipmap_generator()), options.atlas.as_ref());
+
+        let base_mip_level = if options.sampler.lod_bias > 0.
+        {
+            (options.sampler.lod_bias.round() as u32).min(texture.mip_level_count() - 1)
+        } else
+        {
+            0
+        };
+
+        let view = texture.create_view(&TextureViewDescriptor {
+            label: Some(name),
+            base_mip_level,
+            ..Default::default()
+        });
+
         Self
         {
             name : name.to_string(),
-            view : HighLevelGraphicsContext::create_texture_from_file(name, contents, window),
+            view,
+            sampler : Some(options.sampler.create_sampler(name, window.get_device())),
         }
     }
 
@@ -82,4 +254,83 @@ impl BindableTexture for ProspectTexture
     {
         self.name.clone()
     }
-}
\ No newline at end of file
+
+    fn get_sampler(&self) -> Option<&Sampler>
+    {
+        self.sampler.as_ref()
+    }
+}
+
+/// A bind group layout with a texture at binding 0 and a sampler at binding 1, plus the sampler used when binding textures through it.
+/// Clones share the same layout
+#[derive(Clone)]
+pub struct TextureBindLayout
+{
+    layout : Rc<BindGroupLayout>,
+    sampler : Rc<Sampler>,
+}
+
+impl TextureBindLayout
+{
+    pub fn new(device : &Device, label : &str, sampler : Sampler) -> Self
+    {
+        Self::new_with_dimension(device, label, sampler, TextureViewDimension::D2)
+    }
+
+    /// Use `TextureViewDimension::D2Array` for `ProspectTextureArray` or `Cube` for `ProspectCubemap`
+    pub fn new_with_dimension(device : &Device, label : &str, sampler : Sampler, dimension : TextureViewDimension) -> Self
+    {
+        let entries = vec![
+            GraphicsContext::create_bind_group_layout_entry(0, ShaderStages::FRAGMENT, GraphicsContext::create_texture_binding_type(false, dimension, TextureSampleType::Float { filterable: true })),
+            GraphicsContext::create_bind_group_layout_entry(1, ShaderStages::FRAGMENT, GraphicsContext::create_sample_binding_type(SamplerBindingType::Filtering))
+        ];
+
+        Self
+        {
+            layout : Rc::new(GraphicsContext::create_bind_group_layout(device, label, &entries)),
+            sampler : Rc::new(sampler),
+        }
+    }
+
+    pub fn get_layout(&self) -> &BindGroupLayout
+    {
+        &self.layout
+    }
+
+    pub fn get_sampler(&self) -> &Sampler
+    {
+        &self.sampler
+    }
+
+    pub fn create_texture(&self, window : &ProspectWindow, texture : &TextureView, name : &str) -> BindGroup
+    {
+        self.create_texture_with_sampler(window, texture, &self.sampler, name)
+    }
+
+    pub fn create_texture_with_sampler(&self, window : &ProspectWindow, texture : &TextureView, sampler : &Sampler, name : &str) -> BindGroup
+    {
+        let view_resource = GraphicsContext::create_texture_view_resource(0, texture);
+        let sampler_resource = GraphicsContext::create_sampler_resource(1, sampler);
+        GraphicsContext::create_bind_group(window.get_device(), name, &self.layout, &vec![view_resource, sampler_resource])
+    }
+
+    /// Panics if `bytes` isn't a valid image
+    pub fn register_texture(&self, name: &str, bytes : &[u8], window: &mut ProspectWindow) -> SmartBindGroup
+    {
+        let texture_view = HighLevelGraphicsContext::create_texture_from_file(name, bytes, window).unwrap_or_else(|error| panic!("{} is not a valid image: {}", name, error));
+        let bind_group = self.create_texture(window, &texture_view, name);
+        bind_group.into()
+    }
+
+    pub fn bind_prospect_texture<T : BindableTexture + ?Sized>(&self, prospect_texture : &T, window: &ProspectWindow) -> SmartBindGroup
+    {
+        self.create_prospect_texture(prospect_texture, window).into()
+    }
+
+    /// Same as `bind_prospect_texture` without wrapping the result, e.g to `SmartBindGroup::replace` an existing one
+    pub fn create_prospect_texture<T : BindableTexture + ?Sized>(&self, prospect_texture : &T, window: &ProspectWindow) -> BindGroup
+    {
+        let sampler = prospect_texture.get_sampler().unwrap_or(&self.sampler);
+        self.create_texture_with_sampler(window, prospect_texture.get_texture_view(), sampler, &prospect_texture.get_name())
+    }
+}
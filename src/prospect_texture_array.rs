@@ -0,0 +1,103 @@
+use image::{RgbaImage, imageops};
+use wgpu::*;
+
+use crate::{abstraction::{graphics_context::GraphicsContext, prospect_window::ProspectWindow}, prospect_texture::{BindableTexture, TextureOptions}};
+
+/// Equally sized images stacked as layers of one texture, sampled in WGSL with a `texture_2d_array` and a layer index.
+///
+/// Unlike an atlas every layer has its own edges, so there's no bleeding between neighbours at any mip level.
+/// Bind it through a `TextureBindLayout::new_with_dimension` layout using `TextureViewDimension::D2Array`
+pub struct ProspectTextureArray
+{
+    name : String,
+    texture : Texture,
+    view : TextureView,
+    sampler : Option<Sampler>,
+}
+
+impl ProspectTextureArray
+{
+    pub fn from_images(name : &str, layers : &[RgbaImage], window : &ProspectWindow) -> Result<Self, ()>
+    {
+        Self::from_images_with_options(name, layers, &TextureOptions::default(), window)
+    }
+
+    /// `options.atlas` is ignored, layers never share mip levels
+    pub fn from_images_with_options(name : &str, layers : &[RgbaImage], options : &TextureOptions, window : &ProspectWindow) -> Result<Self, ()>
+    {
+        let texture = GraphicsContext::create_texture_array_from_images(name, layers, window.get_device(), window.get_queue(), options.format, options.mipmaps.then_some(window.get_mipmap_generator()))?;
+
+        Ok(Self
+        {
+            name : name.to_string(),
+            view : GraphicsContext::create_array_texture_view(&texture),
+            sampler : Some(options.sampler.create_sampler(name, window.get_device())),
+            texture,
+        })
+    }
+
+    /// Encoded images (png, jpeg), one per layer
+    pub fn from_image_bytes(name : &str, layers : &[&[u8]], options : &TextureOptions, window : &ProspectWindow) -> Result<Self, ()>
+    {
+        let mut images = Vec::with_capacity(layers.len());
+        for (index, layer) in layers.iter().enumerate()
+        {
+            let image = image::load_from_memory(layer).map_err(|error| log::error!("Unable to decode layer {} of {}: {}", index, name, error))?;
+            images.push(image.to_rgba8());
+        }
+
+        Self::from_images_with_options(name, &images, options, window)
+    }
+
+    /// Splits a grid of `tile_size` tiles into layers, row by row from the top left, so an existing atlas image can be used as-is.
+    /// Layer `column + row * columns` is the tile at (`column`, `row`)
+    pub fn from_tiled_image(name : &str, image : &RgbaImage, tile_size : (u32, u32), options : &TextureOptions, window : &ProspectWindow) -> Result<Self, ()>
+    {
+        let columns = image.width() / tile_size.0.max(1);
+        let rows = image.height() / tile_size.1.max(1);
+
+        let mut layers = Vec::with_capacity((columns * rows) as usize);
+        for row in 0..rows
+        {
+            for column in 0..columns
+            {
+                layers.push(imageops::crop_imm(image, column * tile_size.0, row * tile_size.1, tile_size.0, tile_size.1).to_image());
+            }
+        }
+
+        Self::from_images_with_options(name, &layers, options, window)
+    }
+
+    pub fn get_layer_count(&self) -> u32
+    {
+        self.texture.depth_or_array_layers()
+    }
+
+    pub fn get_texture(&self) -> &Texture
+    {
+        &self.texture
+    }
+
+    pub fn get_name(&self) -> String
+    {
+        self.name.clone()
+    }
+}
+
+impl BindableTexture for ProspectTextureArray
+{
+    fn get_texture_view(&self) -> &TextureView
+    {
+        &self.view
+    }
+
+    fn get_name(&self) -> String
+    {
+        self.name.clone()
+    }
+
+    fn get_sampler(&self) -> Option<&Sampler>
+    {
+        self.sampler.as_ref()
+    }
+}
@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+
+use image::RgbaImage;
+use wgpu::*;
+
+use crate::{abstraction::prospect_window::ProspectWindow, prospect_texture::{BindableTexture, ProspectTexture, TextureOptions}};
+
+/// Where an image ended up inside a packed atlas
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AtlasRegion
+{
+    /// Position and size in texels, without padding
+    pub x : u32,
+    pub y : u32,
+    pub width : u32,
+    pub height : u32,
+    /// The UV rectangle (min u, min v, max u, max v), the same layout as `AtlasLayout::get_tile_uv`
+    pub uv : [f32; 4],
+}
+
+/// Packs many images of any size into one texture at runtime.
+///
+/// Each image is surrounded by `padding` texels of its own edges so linear filtering doesn't pick up its neighbours.
+/// Mip levels still blend neighbours once the padding shrinks below a texel, so use enough padding for the distance
+/// things are viewed at or turn mipmaps off in the `TextureOptions`
+pub struct AtlasBuilder
+{
+    padding : u32,
+    max_size : u32,
+    images : Vec<(String, RgbaImage)>,
+}
+
+impl AtlasBuilder
+{
+    pub fn new(padding : u32) -> Self
+    {
+        Self
+        {
+            padding,
+            max_size : 8192,
+            images : Vec::new(),
+        }
+    }
+
+    /// The largest width or height the atlas may grow to, defaults to 8192 which every wgpu device supports
+    pub fn with_max_size(mut self, max_size : u32) -> Self
+    {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Adding a name twice replaces the first image
+    pub fn add_image(&mut self, name : &str, image : RgbaImage)
+    {
+        self.images.retain(|(existing, _)| existing != name);
+        self.images.push((name.to_string(), image));
+    }
+
+    /// An encoded image (png, jpeg)
+    pub fn add_image_bytes(&mut self, name : &str, bytes : &[u8]) -> Result<(), ()>
+    {
+        let image = image::load_from_memory(bytes).map_err(|_| ())?;
+        self.add_image(name, image.to_rgba8());
+        Ok(())
+    }
+
+    /// Packs every image on the CPU without uploading anything
+    pub fn pack(&self) -> Result<(RgbaImage, HashMap<String, AtlasRegion>), ()>
+    {
+        let padded : Vec<(u32, u32)> = self.images.iter().map(|(_, image)| (image.width() + self.padding * 2, image.height() + self.padding * 2)).collect();
+
+        // Tallest first keeps the shelves tight
+        let mut order : Vec<usize> = (0..self.images.len()).collect();
+        order.sort_by(|a, b| padded[*b].1.cmp(&padded[*a].1).then(padded[*b].0.cmp(&padded[*a].0)));
+
+        let area : u64 = padded.iter().map(|(width, height)| *width as u64 * *height as u64).sum();
+        let widest = padded.iter().map(|(width, _)| *width).max().unwrap_or(1);
+        let mut width = ((area as f64).sqrt().ceil() as u32).max(widest).max(1).next_power_of_two();
+
+        while width <= self.max_size
+        {
+            if let Some((positions, height)) = Self::pack_shelves(&order, &padded, width, self.max_size)
+            {
+                return Ok(self.build_image(&positions, width, height));
+            }
+            width *= 2;
+        }
+
+        log::error!("Couldn't fit {} images into a {}x{} atlas", self.images.len(), self.max_size, self.max_size);
+        Err(())
+    }
+
+    /// Places images left to right along shelves as tall as their first image, returning each padded image's top left and the total height
+    fn pack_shelves(order : &[usize], padded : &[(u32, u32)], width : u32, max_height : u32) -> Option<(Vec<(u32, u32)>, u32)>
+    {
+        let mut positions = vec![(0, 0); padded.len()];
+        let mut x = 0;
+        let mut shelf_y = 0;
+        let mut shelf_height = 0;
+
+        for &index in order
+        {
+            let (image_width, image_height) = padded[index];
+            if x + image_width > width
+            {
+                shelf_y += shelf_height;
+                x = 0;
+                shelf_height = 0;
+            }
+
+            positions[index] = (x, shelf_y);
+            x += image_width;
+            shelf_height = shelf_height.max(image_height);
+        }
+
+        let height = (shelf_y + shelf_height).max(1).next_power_of_two();
+        if height > max_height
+        {
+            return None;
+        }
+        Some((positions, height))
+    }
+
+    fn build_image(&self, positions : &[(u32, u32)], width : u32, height : u32) -> (RgbaImage, HashMap<String, AtlasRegion>)
+    {
+        let mut atlas = RgbaImage::new(width, height);
+        let mut regions = HashMap::with_capacity(self.images.len());
+
+        for ((name, image), &(left, top)) in self.images.iter().zip(positions)
+        {
+            let padded = (image.width() + self.padding * 2, image.height() + self.padding * 2);
+            if image.width() == 0 || image.height() == 0
+            {
+                continue;
+            }
+
+            for y in 0..padded.1
+            {
+                for x in 0..padded.0
+                {
+                    let source_x = x.saturating_sub(self.padding).min(image.width() - 1);
+                    let source_y = y.saturating_sub(self.padding).min(image.height() - 1);
+                    atlas.put_pixel(left + x, top + y, *image.get_pixel(source_x, source_y));
+                }
+            }
+
+            let x = left + self.padding;
+            let y = top + self.padding;
+            regions.insert(name.clone(), AtlasRegion
+            {
+                x,
+                y,
+                width : image.width(),
+                height : image.height(),
+                uv : [
+                    x as f32 / width as f32,
+                    y as f32 / height as f32,
+                    (x + image.width()) as f32 / width as f32,
+                    (y + image.height()) as f32 / height as f32,
+                ],
+            });
+        }
+
+        (atlas, regions)
+    }
+
+    /// Packs and uploads the atlas
+    pub fn build(&self, name : &str, options : &TextureOptions, window : &mut ProspectWindow) -> Result<ProspectTextureAtlas, ()>
+    {
+        let (image, regions) = self.pack()?;
+        let size = image.dimensions();
+
+        Ok(ProspectTextureAtlas
+        {
+            texture : ProspectTexture::from_image_with_options(name, image, options, window),
+            regions,
+            size,
+        })
+    }
+}
+
+/// A texture made by `AtlasBuilder`, with the region of every image packed into it
+pub struct ProspectTextureAtlas
+{
+    texture : ProspectTexture,
+    regions : HashMap<String, AtlasRegion>,
+    size : (u32, u32),
+}
+
+impl ProspectTextureAtlas
+{
+    pub fn get_region(&self, name : &str) -> Option<&AtlasRegion>
+    {
+        self.regions.get(name)
+    }
+
+    /// The UV rectangle (min u, min v, max u, max v) of the image added as `name`
+    pub fn get_uv(&self, name : &str) -> Option<[f32; 4]>
+    {
+        self.regions.get(name).map(|region| region.uv)
+    }
+
+    pub fn get_regions(&self) -> &HashMap<String, AtlasRegion>
+    {
+        &self.regions
+    }
+
+    pub fn get_size(&self) -> (u32, u32)
+    {
+        self.size
+    }
+
+    pub fn get_texture(&self) -> &ProspectTexture
+    {
+        &self.texture
+    }
+}
+
+impl BindableTexture for ProspectTextureAtlas
+{
+    fn get_texture_view(&self) -> &TextureView
+    {
+        self.texture.get_texture_view()
+    }
+
+    fn get_name(&self) -> String
+    {
+        self.texture.get_name()
+    }
+
+    fn get_sampler(&self) -> Option<&Sampler>
+    {
+        self.texture.get_sampler()
+    }
+}
@@ -10,6 +10,41 @@ pub struct TransformUniform
     model_matrix : [f32; 4 * 4],
 }
 
+impl TransformUniform
+{
+    /// From a column major matrix, e.g a node's `GltfScene::world_matrix`
+    pub fn from_columns(columns : [[f32; 4]; 4]) -> Self
+    {
+        Self { model_matrix : bytemuck::cast(columns) }
+    }
+}
+
+/// A column major matrix that translates, rotates by the quaternion `[x, y, z, w]` and then scales, in the order glTF nodes are applied
+pub fn matrix_from_trs(translation : [f32; 3], rotation : [f32; 4], scale : [f32; 3]) -> [[f32; 4]; 4]
+{
+    let [x, y, z, w] = rotation;
+    [
+        [(1. - 2. * (y * y + z * z)) * scale[0], 2. * (x * y + z * w) * scale[0], 2. * (x * z - y * w) * scale[0], 0.],
+        [2. * (x * y - z * w) * scale[1], (1. - 2. * (x * x + z * z)) * scale[1], 2. * (y * z + x * w) * scale[1], 0.],
+        [2. * (x * z + y * w) * scale[2], 2. * (y * z - x * w) * scale[2], (1. - 2. * (x * x + y * y)) * scale[2], 0.],
+        [translation[0], translation[1], translation[2], 1.],
+    ]
+}
+
+/// `a * b` for column major matrices, so `b` is applied first
+pub fn multiply_matrices(a : &[[f32; 4]; 4], b : &[[f32; 4]; 4]) -> [[f32; 4]; 4]
+{
+    let mut result = [[0.; 4]; 4];
+    for (column, b_column) in result.iter_mut().zip(b)
+    {
+        for (row, value) in column.iter_mut().enumerate()
+        {
+            *value = (0..4).map(|i| a[i][row] * b_column[i]).sum();
+        }
+    }
+    result
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct Transform
 {
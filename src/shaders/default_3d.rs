@@ -1,16 +1,26 @@
 use wgpu::{
-    BlendState, ColorTargetState, ColorWrites, Device, FragmentState, ShaderModule, VertexState, RenderPipeline, ShaderStages, TextureViewDimension, TextureSampleType, BindGroupLayout, BindGroup, Sampler, TextureView, PrimitiveTopology,
+    BlendState, ColorTargetState, ColorWrites, Device, FragmentState, ShaderModule, VertexState, RenderPipeline, ShaderStages, BindGroupLayout, BindGroup, TextureView, PrimitiveTopology, TextureViewDimension, TextureSampleType, SamplerBindingType, TextureFormat, DownlevelFlags,
 };
 
-use crate::{abstraction::{shader::ProspectShader, vertex::Vertex, high_level_abstraction::HighLevelGraphicsContext, prospect_window::ProspectWindow, graphics_context::GraphicsContext}, prospect_texture::{ProspectTexture, BindableTexture}, smart::SmartBindGroup};
+use crate::{abstraction::{shader::ProspectShader, vertex::{Vertex, TangentVertex, SkinnedVertex, VertexLayout}, high_level_abstraction::HighLevelGraphicsContext, prospect_window::ProspectWindow, graphics_context::GraphicsContext}, prospect_texture::{BindableTexture, TextureBindLayout}, smart::SmartBindGroup};
 
 pub struct Default3D {
     module: ShaderModule,
     color_target_state: Vec<Option<ColorTargetState>>,
-    bind_layout : BindGroupLayout,
-    sampler : Sampler,
+    textures : TextureBindLayout,
     matrix_bind_group_layout : BindGroupLayout,
-    topology : PrimitiveTopology
+    topology : PrimitiveTopology,
+    normal_map : Option<NormalMapBindings>,
+    environment : bool,
+    skinned : bool,
+    /// Joint matrices come from a fixed size uniform array instead of a storage buffer
+    uniform_joints : bool,
+}
+
+struct NormalMapBindings
+{
+    layout : BindGroupLayout,
+    flat_normal : TextureView,
 }
 
 impl ProspectShader for Default3D {
@@ -25,16 +35,39 @@ impl ProspectShader for Default3D {
     fn fragment_state(&self) -> FragmentState {
         FragmentState {
             module: &self.module,
-            entry_point: "fs_main",
+            entry_point: match (self.normal_map.is_some(), self.environment)
+            {
+                (false, false) => "fs_main",
+                (false, true) => "fs_environment",
+                (true, false) => "fs_normal_mapped",
+                (true, true) => "fs_normal_mapped_environment",
+            },
             targets: &self.color_target_state,
         }
     }
 
     fn vertex_state(&self) -> VertexState {
-        VertexState {
-            module: &self.module,
-            entry_point: "vs_main",
-            buffers: &[Vertex::VERTEX_BUFFER_LAYOUT],
+        if self.skinned
+        {
+            VertexState {
+                module: &self.module,
+                entry_point: "vs_skinned",
+                buffers: &[SkinnedVertex::VERTEX_BUFFER_LAYOUT],
+            }
+        } else if self.normal_map.is_some()
+        {
+            VertexState {
+                module: &self.module,
+                entry_point: "vs_tangent",
+                buffers: &[TangentVertex::VERTEX_BUFFER_LAYOUT],
+            }
+        } else
+        {
+            VertexState {
+                module: &self.module,
+                entry_point: "vs_main",
+                buffers: &[Vertex::VERTEX_BUFFER_LAYOUT],
+            }
         }
     }
 
@@ -44,7 +77,11 @@ impl ProspectShader for Default3D {
 
     fn build_render_pipeline(&self, device: &Device, bind_groups : Vec<&BindGroupLayout>) -> RenderPipeline {
         let mut bind_groups = bind_groups;
-        bind_groups.insert(1, &self.bind_layout);
+        match &self.normal_map
+        {
+            Some(normal_map) => bind_groups.insert(1, &normal_map.layout),
+            None => bind_groups.insert(1, self.textures.get_layout()),
+        }
         bind_groups.insert(3, &self.matrix_bind_group_layout);
 
         let mut state = GraphicsContext::DEFAULT_PRIMITIVE_STATE;
@@ -61,6 +98,92 @@ impl Default3D {
         Self::new_with_custom_topology(window, GraphicsContext::DEFAULT_PRIMITIVE_STATE.topology)
     }
 
+    /// Samples a tangent space normal map, meshes need tangents (see `Mesh::from_shape_with_tangents`)
+    /// and textures are bound with `create_normal_mapped_texture`
+    pub fn new_normal_mapped(window : &ProspectWindow) -> Self
+    {
+        let mut shader = Self::new(window);
+        let device = window.get_device();
+
+        let texture_binding = GraphicsContext::create_texture_binding_type(false, TextureViewDimension::D2, TextureSampleType::Float { filterable: true });
+        let entries = vec![
+            GraphicsContext::create_bind_group_layout_entry(0, ShaderStages::FRAGMENT, texture_binding),
+            GraphicsContext::create_bind_group_layout_entry(1, ShaderStages::FRAGMENT, GraphicsContext::create_sample_binding_type(SamplerBindingType::Filtering)),
+            GraphicsContext::create_bind_group_layout_entry(2, ShaderStages::FRAGMENT, texture_binding),
+        ];
+        let layout = GraphicsContext::create_bind_group_layout(device, "Default3D Normal Mapped Bind Group", &entries);
+
+        let flat_normal = GraphicsContext::create_texture_from_image_with_format("Default3D Flat Normal", image::RgbaImage::from_pixel(1, 1, image::Rgba([128, 128, 255, 255])), device, window.get_queue(), TextureFormat::Rgba8Unorm);
+
+        shader.normal_map = Some(NormalMapBindings
+        {
+            layout,
+            flat_normal : GraphicsContext::create_texture_view(&flat_normal),
+        });
+        shader
+    }
+
+    pub fn is_normal_mapped(&self) -> bool
+    {
+        self.normal_map.is_some()
+    }
+
+    /// The most joints a skeleton can have when `new_skinned` falls back to a uniform array
+    pub const MAX_UNIFORM_JOINTS : usize = 128;
+
+    /// Deforms `SkinnedVertex` meshes by a `Skeleton`'s joints, which are read from a storage buffer next to the model matrix.
+    /// Devices without storage buffers in the vertex stage (e.g WebGL2) get a uniform array instead, limited to `MAX_UNIFORM_JOINTS`.
+    /// Draw with a `SkinnedModel3D` rather than a `Model3D`
+    pub fn new_skinned(window : &ProspectWindow) -> Self
+    {
+        let mut shader = Self::new(window);
+        shader.skinned = true;
+        // The device can also have been made with lower limits than the adapter allows (see `GraphicsContext::create_device`)
+        let vertex_storage = window.get_adapter().get_downlevel_capabilities().flags.contains(DownlevelFlags::VERTEX_STORAGE);
+        shader.uniform_joints = !vertex_storage || window.get_device().limits().max_storage_buffers_per_shader_stage == 0;
+
+        let (joints, declaration) = if shader.uniform_joints
+        {
+            (GraphicsContext::create_uniform_binding_type(), format!("var<uniform> joint_matrices : array<mat4x4<f32>, {}>;", Self::MAX_UNIFORM_JOINTS))
+        } else
+        {
+            (GraphicsContext::create_storage_binding_type(true), String::from("var<storage, read> joint_matrices : array<mat4x4<f32>>;"))
+        };
+        let src = format!("{}\n@group(3) @binding(1)\n{}\n\n{}", include_str!("default_3d.wgsl"), declaration, include_str!("default_3d_skinned.wgsl"));
+        shader.module = GraphicsContext::load_shader("Default3D Skinned Shader", &src, window.get_device());
+
+        let entries = vec![
+            GraphicsContext::create_bind_group_layout_entry(0, ShaderStages::VERTEX, GraphicsContext::create_uniform_binding_type()),
+            GraphicsContext::create_bind_group_layout_entry(1, ShaderStages::VERTEX, joints),
+        ];
+        shader.matrix_bind_group_layout = GraphicsContext::create_bind_group_layout(window.get_device(), "Default3D Skinned Matrix Bind Layout", &entries);
+        shader
+    }
+
+    pub fn is_skinned(&self) -> bool
+    {
+        self.skinned
+    }
+
+    /// `Some(MAX_UNIFORM_JOINTS)` when skinning had to fall back to a uniform array
+    pub fn max_joints(&self) -> Option<usize>
+    {
+        (self.skinned && self.uniform_joints).then_some(Self::MAX_UNIFORM_JOINTS)
+    }
+
+    /// Takes ambient light from a `ProspectEnvironment`'s irradiance map instead of a tenth of the point light,
+    /// build the pipeline with the environment's layout in place of the light's and bind `ProspectEnvironment::create_bind_group`
+    pub fn with_environment(mut self) -> Self
+    {
+        self.environment = true;
+        self
+    }
+
+    pub fn uses_environment(&self) -> bool
+    {
+        self.environment
+    }
+
     pub fn new_with_custom_topology(
         window : &ProspectWindow,
         topology : PrimitiveTopology
@@ -70,11 +193,7 @@ impl Default3D {
         let src = include_str!("default_3d.wgsl");
 
         let sampler = GraphicsContext::create_sampler("Default3D Shader Sampler", device, None, None);
-        let entries = vec![
-            GraphicsContext::create_bind_group_layout_entry(0, ShaderStages::FRAGMENT, GraphicsContext::create_texture_binding_type(false, TextureViewDimension::D2, TextureSampleType::Float { filterable: true })),
-            GraphicsContext::create_bind_group_layout_entry(1, ShaderStages::FRAGMENT, GraphicsContext::create_sample_binding_type(wgpu::SamplerBindingType::Filtering))
-        ];
-        let bind_group_layout = GraphicsContext::create_bind_group_layout(device, "Default3D Shader Bind Group", &entries);
+        let textures = TextureBindLayout::new(device, "Default3D Shader Bind Group", sampler);
 
         let matrix_bind_group_layout = vec![
             GraphicsContext::create_bind_group_layout_entry(0, ShaderStages::VERTEX, GraphicsContext::create_uniform_binding_type())
@@ -82,8 +201,7 @@ impl Default3D {
         let matrix_bind_group_layout = GraphicsContext::create_bind_group_layout(device, "Default3D Matrix Bind Layout", &matrix_bind_group_layout);
 
         Self {
-            sampler,
-            bind_layout: bind_group_layout,
+            textures,
             matrix_bind_group_layout,
             module: GraphicsContext::load_shader("Default3D Shader", src.as_ref(), device),
             color_target_state: vec![Some(ColorTargetState {
@@ -92,26 +210,45 @@ impl Default3D {
                 write_mask: ColorWrites::ALL,
             })],
             topology,
+            normal_map : None,
+            environment : false,
+            skinned : false,
+            uniform_joints : false,
         }
     }
 
+    pub fn get_texture_layout(&self) -> &TextureBindLayout
+    {
+        &self.textures
+    }
+
     pub fn create_texture(&self, window : &ProspectWindow, texture : &TextureView, name : &str) -> BindGroup
     {
-        let view_resource = GraphicsContext::create_texture_view_resource(0, texture);
-        let sampler_resource = GraphicsContext::create_sampler_resource(1, &self.sampler);
-        GraphicsContext::create_bind_group(window.get_device(), name, &self.bind_layout, &vec![view_resource, sampler_resource])
+        self.textures.create_texture(window, texture, name)
     }
 
     pub fn register_texture(&self, name: &str, bytes : &[u8], window: &mut ProspectWindow) -> SmartBindGroup
     {
-        let texture_view = HighLevelGraphicsContext::create_texture_from_file(name, bytes, window);
-        let bind_group = self.create_texture(window, &texture_view, name);
-        bind_group.into()
+        self.textures.register_texture(name, bytes, window)
     }
 
     pub fn bind_prospect_texture<T : BindableTexture>(&self, prospect_texture : &T, window: &mut ProspectWindow) -> SmartBindGroup
     {
-        let bind_group = self.create_texture(window, prospect_texture.get_texture_view(), &prospect_texture.get_name());
-        bind_group.into()
-    }  
+        self.textures.bind_prospect_texture(prospect_texture, window)
+    }
+
+    /// Binds a diffuse texture with a normal map, which should be loaded with `ProspectTexture::image_file_from_bytes_linear`.
+    /// Without a normal map the surface is lit as if flat. Fails if the shader wasn't made with `new_normal_mapped`
+    pub fn create_normal_mapped_texture<T : BindableTexture + ?Sized>(&self, window : &ProspectWindow, diffuse : &T, normal : Option<&dyn BindableTexture>, name : &str) -> Result<SmartBindGroup, ()>
+    {
+        let normal_map = self.normal_map.as_ref().ok_or(())?;
+        let normal_view = normal.map(|normal| normal.get_texture_view()).unwrap_or(&normal_map.flat_normal);
+
+        let entries = vec![
+            GraphicsContext::create_texture_view_resource(0, diffuse.get_texture_view()),
+            GraphicsContext::create_sampler_resource(1, self.textures.get_sampler()),
+            GraphicsContext::create_texture_view_resource(2, normal_view),
+        ];
+        Ok(GraphicsContext::create_bind_group(window.get_device(), name, &normal_map.layout, &entries).into())
+    }
 }
@@ -46,33 +46,123 @@ fn vs_main(
     return out;
 }
 
+struct TangentVertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) tex_coords: vec2<f32>,
+    @location(2) normal : vec3<f32>,
+    @location(3) tangent : vec4<f32>
+}
+
+struct TangentVertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+    @location(1) world_normal : vec3<f32>,
+    @location(2) world_position : vec3<f32>,
+    @location(3) world_tangent : vec4<f32>
+}
+
+@vertex
+fn vs_tangent(
+    model : TangentVertexInput
+) -> TangentVertexOutput
+{
+    var out : TangentVertexOutput;
+    out.tex_coords = model.tex_coords;
+    out.world_normal = normalize(model_information.matrix * vec4<f32>(model.normal, 0.0)).xyz;
+    out.world_tangent = vec4<f32>(normalize(model_information.matrix * vec4<f32>(model.tangent.xyz, 0.0)).xyz, model.tangent.w);
+    var world_position : vec4<f32> = model_information.matrix * vec4<f32>(model.position, 1.0);
+    out.world_position = world_position.xyz;
+    out.clip_position = camera.view_proj * world_position;
+    return out;
+}
+
 
 @group(1) @binding(0)
 var t_diffuse: texture_2d<f32>;
 @group(1) @binding(1)
 var s_diffuse: sampler;
 
-@fragment
-fn fs_main(in: VertexOutput) -> @location(0) vec4<f32>
-{
-    let object_col: vec4<f32> = textureSample(t_diffuse, s_diffuse, in.tex_coords);
+// Only bound by the normal mapped variant
+@group(1) @binding(2)
+var t_normal: texture_2d<f32>;
 
-    let ambient_strength = 0.1;
-    let ambient_colour = light.colour * ambient_strength;
+// Only bound by the environment variants, see ProspectEnvironment
+@group(2) @binding(1)
+var t_irradiance: texture_cube<f32>;
+@group(2) @binding(4)
+var s_environment: sampler;
+
+struct EnvironmentUniform {
+    intensity: f32,
+    max_lod: f32,
+};
+@group(2) @binding(5)
+var<uniform> environment : EnvironmentUniform;
 
-    let light_dir = normalize(light.position - in.world_position);
+fn environment_ambient(normal : vec3<f32>) -> vec3<f32>
+{
+    return textureSample(t_irradiance, s_environment, normal).rgb * environment.intensity;
+}
+
+fn shade(object_col : vec4<f32>, normal : vec3<f32>, world_position : vec3<f32>, ambient_colour : vec3<f32>) -> vec4<f32>
+{
+    let light_dir = normalize(light.position - world_position);
 
-    let diffuse_strength = max(dot(in.world_normal, light_dir), 0.0);
+    let diffuse_strength = max(dot(normal, light_dir), 0.0);
     let diffuse_colour = light.colour * diffuse_strength;
 
-    let view_dir = normalize(camera.view_pos.xyz - in.world_position);
+    let view_dir = normalize(camera.view_pos.xyz - world_position);
     let half_dir = normalize(view_dir + light_dir);
-    let reflect_dir = reflect(-light_dir, in.world_normal);
 
-    let specular_strength = pow(max(dot(in.world_normal, half_dir), 0.0), 32.0);
+    let specular_strength = pow(max(dot(normal, half_dir), 0.0), 32.0);
     let specular_colour = light.colour * specular_strength;
 
     let result = (ambient_colour + diffuse_colour + specular_colour) * object_col.xyz; 
 
     return vec4<f32>(result, object_col.a);
-}
\ No newline at end of file
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32>
+{
+    let object_col: vec4<f32> = textureSample(t_diffuse, s_diffuse, in.tex_coords);
+    let ambient_strength = 0.1;
+    return shade(object_col, in.world_normal, in.world_position, light.colour * ambient_strength);
+}
+
+@fragment
+fn fs_environment(in: VertexOutput) -> @location(0) vec4<f32>
+{
+    let object_col: vec4<f32> = textureSample(t_diffuse, s_diffuse, in.tex_coords);
+    let normal = normalize(in.world_normal);
+    return shade(object_col, normal, in.world_position, environment_ambient(normal));
+}
+
+fn normal_mapped(in: TangentVertexOutput) -> vec3<f32>
+{
+    let tangent_normal = textureSample(t_normal, s_diffuse, in.tex_coords).xyz * 2.0 - 1.0;
+
+    let normal = normalize(in.world_normal);
+    // Re-orthogonalise, interpolation pulls the tangent off the normal's plane
+    let tangent = normalize(in.world_tangent.xyz - normal * dot(normal, in.world_tangent.xyz));
+    let bitangent = cross(normal, tangent) * in.world_tangent.w;
+    let tbn = mat3x3<f32>(tangent, bitangent, normal);
+
+    return normalize(tbn * tangent_normal);
+}
+
+@fragment
+fn fs_normal_mapped(in: TangentVertexOutput) -> @location(0) vec4<f32>
+{
+    let object_col: vec4<f32> = textureSample(t_diffuse, s_diffuse, in.tex_coords);
+    let ambient_strength = 0.1;
+    return shade(object_col, normal_mapped(in), in.world_position, light.colour * ambient_strength);
+}
+
+@fragment
+fn fs_normal_mapped_environment(in: TangentVertexOutput) -> @location(0) vec4<f32>
+{
+    let object_col: vec4<f32> = textureSample(t_diffuse, s_diffuse, in.tex_coords);
+    let normal = normal_mapped(in);
+    return shade(object_col, normal, in.world_position, environment_ambient(normal));
+}
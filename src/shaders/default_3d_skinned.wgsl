@@ -0,0 +1,35 @@
+// Appended to default_3d.wgsl by Default3D::new_skinned, after a `joint_matrices` declaration at @group(3) @binding(1)
+// that's a storage buffer where the device allows one in the vertex stage and a fixed size uniform array otherwise
+
+struct SkinnedVertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) tex_coords: vec2<f32>,
+    @location(2) normal : vec3<f32>,
+    @location(3) joints : vec4<u32>,
+    @location(4) weights : vec4<f32>
+}
+
+fn skinned_output(model : SkinnedVertexInput, skin : mat4x4<f32>) -> VertexOutput
+{
+    let matrix = model_information.matrix * skin;
+
+    var out : VertexOutput;
+    out.tex_coords = model.tex_coords;
+    out.world_normal = normalize(matrix * vec4<f32>(model.normal, 0.0)).xyz;
+    var world_position : vec4<f32> = matrix * vec4<f32>(model.position, 1.0);
+    out.world_position = world_position.xyz;
+    out.clip_position = camera.view_proj * world_position;
+    return out;
+}
+
+@vertex
+fn vs_skinned(
+    model : SkinnedVertexInput
+) -> VertexOutput
+{
+    let skin = joint_matrices[model.joints.x] * model.weights.x
+        + joint_matrices[model.joints.y] * model.weights.y
+        + joint_matrices[model.joints.z] * model.weights.z
+        + joint_matrices[model.joints.w] * model.weights.w;
+    return skinned_output(model, skin);
+}
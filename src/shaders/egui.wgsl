@@ -0,0 +1,78 @@
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+    @location(1) tex_coords: vec2<f32>,
+    @location(2) colour: vec4<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+    @location(1) colour: vec4<f32>,
+}
+
+struct ScreenUniform {
+    size: vec2<f32>,
+    padding: vec2<f32>,
+};
+@group(0) @binding(0)
+var<uniform> screen: ScreenUniform;
+
+@group(1) @binding(0)
+var t_diffuse: texture_2d<f32>;
+@group(1) @binding(1)
+var s_diffuse: sampler;
+
+// egui works in premultiplied gamma space, its textures are sRGB so sampling them gives linear colours
+fn linear_from_gamma(gamma : vec3<f32>) -> vec3<f32>
+{
+    let cutoff = gamma < vec3<f32>(0.04045);
+    let lower = gamma / vec3<f32>(12.92);
+    let higher = pow((gamma + vec3<f32>(0.055)) / vec3<f32>(1.055), vec3<f32>(2.4));
+    return select(higher, lower, cutoff);
+}
+
+fn gamma_from_linear(linear : vec3<f32>) -> vec3<f32>
+{
+    let cutoff = linear < vec3<f32>(0.0031308);
+    let lower = linear * vec3<f32>(12.92);
+    let higher = vec3<f32>(1.055) * pow(linear, vec3<f32>(1.0 / 2.4)) - vec3<f32>(0.055);
+    return select(higher, lower, cutoff);
+}
+
+@vertex
+fn vs_main(
+    model : VertexInput
+) -> VertexOutput
+{
+    var out : VertexOutput;
+    // Points from the top left to clip space
+    out.clip_position = vec4<f32>(
+        2.0 * model.position.x / screen.size.x - 1.0,
+        1.0 - 2.0 * model.position.y / screen.size.y,
+        0.0,
+        1.0
+    );
+    out.tex_coords = model.tex_coords;
+    out.colour = model.colour;
+    return out;
+}
+
+fn gamma_colour(in : VertexOutput) -> vec4<f32>
+{
+    let texel = textureSample(t_diffuse, s_diffuse, in.tex_coords);
+    return in.colour * vec4<f32>(gamma_from_linear(texel.rgb), texel.a);
+}
+
+/// For sRGB surfaces, which convert back to gamma space on write
+@fragment
+fn fs_linear(in : VertexOutput) -> @location(0) vec4<f32>
+{
+    let colour = gamma_colour(in);
+    return vec4<f32>(linear_from_gamma(colour.rgb), colour.a);
+}
+
+@fragment
+fn fs_gamma(in : VertexOutput) -> @location(0) vec4<f32>
+{
+    return gamma_colour(in);
+}
@@ -0,0 +1,43 @@
+const SAMPLE_COUNT: u32 = 1024u;
+
+fn geometry_schlick_ggx(n_dot_x: f32, roughness: f32) -> f32
+{
+    // Image based lighting uses a different k to direct lighting
+    let k = (roughness * roughness) / 2.0;
+    return n_dot_x / (n_dot_x * (1.0 - k) + k);
+}
+
+// Split sum BRDF integration, n dot v along x and roughness along y. Red is the scale and green the bias applied to F0
+@fragment
+fn fs_main(in: FullscreenOutput) -> @location(0) vec4<f32>
+{
+    let n_dot_v = max(in.uv.x, 0.001);
+    let roughness = in.uv.y;
+
+    let view = vec3<f32>(sqrt(1.0 - n_dot_v * n_dot_v), 0.0, n_dot_v);
+    let normal = vec3<f32>(0.0, 0.0, 1.0);
+
+    var scale = 0.0;
+    var bias = 0.0;
+    for (var i = 0u; i < SAMPLE_COUNT; i++)
+    {
+        let h = importance_sample_ggx(hammersley(i, SAMPLE_COUNT), normal, roughness);
+        let l = normalize(2.0 * dot(view, h) * h - view);
+
+        let n_dot_l = max(l.z, 0.0);
+        let n_dot_h = max(h.z, 0.0);
+        let v_dot_h = max(dot(view, h), 0.0);
+
+        if (n_dot_l > 0.0)
+        {
+            let g = geometry_schlick_ggx(n_dot_v, roughness) * geometry_schlick_ggx(n_dot_l, roughness);
+            let g_visible = (g * v_dot_h) / (n_dot_h * n_dot_v);
+            let fresnel = pow(1.0 - v_dot_h, 5.0);
+
+            scale += (1.0 - fresnel) * g_visible;
+            bias += fresnel * g_visible;
+        }
+    }
+
+    return vec4<f32>(scale / f32(SAMPLE_COUNT), bias / f32(SAMPLE_COUNT), 0.0, 1.0);
+}
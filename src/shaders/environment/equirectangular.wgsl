@@ -0,0 +1,26 @@
+// Rgba32Float isn't filterable, so the panorama is read with textureLoad and filtered here
+@group(1) @binding(0)
+var t_equirectangular: texture_2d<f32>;
+
+fn load_wrapped(texel: vec2<i32>, size: vec2<i32>) -> vec3<f32>
+{
+    let x = ((texel.x % size.x) + size.x) % size.x;
+    let y = clamp(texel.y, 0, size.y - 1);
+    return textureLoad(t_equirectangular, vec2<i32>(x, y), 0).rgb;
+}
+
+@fragment
+fn fs_main(in: FullscreenOutput) -> @location(0) vec4<f32>
+{
+    let direction = face_direction(pass_info.face, in.uv);
+    let uv = vec2<f32>(atan2(direction.z, direction.x) / (2.0 * PI) + 0.5, acos(clamp(direction.y, -1.0, 1.0)) / PI);
+
+    let size = vec2<i32>(textureDimensions(t_equirectangular));
+    let position = uv * vec2<f32>(size) - 0.5;
+    let base = vec2<i32>(floor(position));
+    let f = fract(position);
+
+    let top = mix(load_wrapped(base, size), load_wrapped(base + vec2<i32>(1, 0), size), f.x);
+    let bottom = mix(load_wrapped(base + vec2<i32>(0, 1), size), load_wrapped(base + vec2<i32>(1, 1), size), f.x);
+    return vec4<f32>(mix(top, bottom, f.y), 1.0);
+}
@@ -0,0 +1,33 @@
+@group(1) @binding(0)
+var t_environment: texture_cube<f32>;
+@group(1) @binding(1)
+var s_environment: sampler;
+
+// Cosine weighted average of the hemisphere around each direction, for diffuse lighting
+@fragment
+fn fs_main(in: FullscreenOutput) -> @location(0) vec4<f32>
+{
+    let normal = face_direction(pass_info.face, in.uv);
+    let up = select(vec3<f32>(1.0, 0.0, 0.0), vec3<f32>(0.0, 1.0, 0.0), abs(normal.y) < 0.999);
+    let right = normalize(cross(up, normal));
+    let forward = cross(normal, right);
+
+    // A blurrier level keeps the sample count down without missing small bright spots
+    let lod = max(log2(pass_info.source_size / 64.0), 0.0);
+    let delta = 0.05;
+
+    var irradiance = vec3<f32>(0.0);
+    var count = 0.0;
+    for (var phi = 0.0; phi < 2.0 * PI; phi += delta)
+    {
+        for (var theta = 0.0; theta < 0.5 * PI; theta += delta)
+        {
+            let tangent_sample = vec3<f32>(sin(theta) * cos(phi), sin(theta) * sin(phi), cos(theta));
+            let direction = tangent_sample.x * right + tangent_sample.y * forward + tangent_sample.z * normal;
+            irradiance += textureSampleLevel(t_environment, s_environment, direction, lod).rgb * cos(theta) * sin(theta);
+            count += 1.0;
+        }
+    }
+
+    return vec4<f32>(PI * irradiance / count, 1.0);
+}
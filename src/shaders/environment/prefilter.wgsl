@@ -0,0 +1,41 @@
+@group(1) @binding(0)
+var t_environment: texture_cube<f32>;
+@group(1) @binding(1)
+var s_environment: sampler;
+
+const SAMPLE_COUNT: u32 = 512u;
+
+// GGX convolution of the environment for one roughness, each mip level of the result gets a rougher one
+@fragment
+fn fs_main(in: FullscreenOutput) -> @location(0) vec4<f32>
+{
+    let normal = face_direction(pass_info.face, in.uv);
+    let view = normal;
+    let roughness = pass_info.roughness;
+
+    // Solid angle of one texel of the source, used to pick a blurrier level for unlikely samples
+    let texel_solid_angle = 4.0 * PI / (6.0 * pass_info.source_size * pass_info.source_size);
+
+    var colour = vec3<f32>(0.0);
+    var weight = 0.0;
+    for (var i = 0u; i < SAMPLE_COUNT; i++)
+    {
+        let h = importance_sample_ggx(hammersley(i, SAMPLE_COUNT), normal, roughness);
+        let l = normalize(2.0 * dot(view, h) * h - view);
+        let n_dot_l = dot(normal, l);
+
+        if (n_dot_l > 0.0)
+        {
+            let n_dot_h = max(dot(normal, h), 0.0);
+            let h_dot_v = max(dot(h, view), 0.0);
+            let pdf = distribution_ggx(n_dot_h, roughness) * n_dot_h / (4.0 * h_dot_v) + 0.0001;
+            let sample_solid_angle = 1.0 / (f32(SAMPLE_COUNT) * pdf + 0.0001);
+            let lod = select(0.5 * log2(sample_solid_angle / texel_solid_angle), 0.0, roughness == 0.0);
+
+            colour += textureSampleLevel(t_environment, s_environment, l, lod).rgb * n_dot_l;
+            weight += n_dot_l;
+        }
+    }
+
+    return vec4<f32>(colour / max(weight, 0.0001), 1.0);
+}
@@ -0,0 +1,47 @@
+struct MipmapParams {
+    // Tile size and padding at the source level, a tile size of zero means the texture isn't an atlas
+    tile_size: vec2<u32>,
+    padding: vec2<u32>,
+};
+
+@group(0) @binding(0)
+var t_source: texture_2d<f32>;
+@group(0) @binding(1)
+var<uniform> params: MipmapParams;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32>
+{
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+fn load(coord: vec2<i32>, low: vec2<i32>, high: vec2<i32>) -> vec4<f32>
+{
+    return textureLoad(t_source, clamp(coord, low, high), 0);
+}
+
+// 2x2 box filter, atlas tiles only ever read from their own inner area so neighbours don't bleed in
+// and the padding around each tile is filled with its edge texels
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32>
+{
+    let base = vec2<i32>(position.xy) * 2;
+    var low = vec2<i32>(0);
+    var high = vec2<i32>(textureDimensions(t_source)) - 1;
+
+    if (params.tile_size.x > 0u && params.tile_size.y > 0u)
+    {
+        let tile = vec2<i32>(params.tile_size);
+        let padding = vec2<i32>(params.padding);
+        let origin = (base / tile) * tile;
+        low = min(origin + padding, high);
+        high = max(min(origin + tile - padding - 1, high), low);
+    }
+
+    let colour = load(base, low, high)
+        + load(base + vec2<i32>(1, 0), low, high)
+        + load(base + vec2<i32>(0, 1), low, high)
+        + load(base + vec2<i32>(1, 1), low, high);
+    return colour * 0.25;
+}
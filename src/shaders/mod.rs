@@ -1,2 +1,3 @@
 pub mod textured_shader;
-pub mod default_3d;
\ No newline at end of file
+pub mod default_3d;
+pub mod pbr;
\ No newline at end of file
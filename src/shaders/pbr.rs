@@ -0,0 +1,255 @@
+use wgpu::{
+    BlendState, ColorTargetState, ColorWrites, Device, FragmentState, ShaderModule, VertexState, RenderPipeline, ShaderStages, TextureViewDimension, TextureSampleType, BindGroupLayout, Sampler, TextureView, PrimitiveTopology, Buffer, BufferUsages, TextureFormat, FilterMode,
+};
+
+use crate::{abstraction::{shader::ProspectShader, vertex::{Vertex, TangentVertex, VertexLayout}, high_level_abstraction::HighLevelGraphicsContext, prospect_window::ProspectWindow, graphics_context::GraphicsContext}, prospect_texture::BindableTexture, smart::SmartBindGroup};
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct PbrFactors
+{
+    pub base_colour : [f32; 4],
+    pub emissive : [f32; 3],
+    pub metallic : f32,
+    pub roughness : f32,
+    pub occlusion_strength : f32,
+    pub normal_scale : f32,
+    // Due to uniforms requiring 16 byte (4 float) spacing, we need to use a padding field here
+    _padding : f32,
+}
+
+impl Default for PbrFactors
+{
+    fn default() -> Self
+    {
+        Self
+        {
+            base_colour : [1.; 4],
+            emissive : [0.; 3],
+            metallic : 1.,
+            roughness : 1.,
+            occlusion_strength : 1.,
+            normal_scale : 1.,
+            _padding : 0.,
+        }
+    }
+}
+
+/// Any texture left as `None` falls back to one that leaves the matching factor unchanged.
+///
+/// Metallic-roughness, normal and occlusion maps should be loaded with `ProspectTexture::image_file_from_bytes_linear`
+#[derive(Default)]
+pub struct PbrTextures<'a>
+{
+    pub base_colour : Option<&'a dyn BindableTexture>,
+    /// Roughness in green, metallic in blue (glTF layout)
+    pub metallic_roughness : Option<&'a dyn BindableTexture>,
+    pub normal : Option<&'a dyn BindableTexture>,
+    pub occlusion : Option<&'a dyn BindableTexture>,
+    pub emissive : Option<&'a dyn BindableTexture>,
+}
+
+pub struct PbrMaterial
+{
+    pub factors : PbrFactors,
+    buffer : Buffer,
+    bind_group : SmartBindGroup,
+}
+
+impl PbrMaterial
+{
+    pub fn get_bind_group(&self) -> SmartBindGroup
+    {
+        self.bind_group.clone()
+    }
+
+    /// Uploads `factors`, call after changing them
+    pub fn process_frame(&self, window : &ProspectWindow)
+    {
+        GraphicsContext::update_buffer(window.get_queue(), &self.buffer, 0, &[self.factors]);
+    }
+}
+
+/// Cook-Torrance metallic-roughness shader, uses the same bind group slots as `Default3D`
+/// (camera at 0, material at 1, `ProspectPointLight` or `ProspectEnvironment` at 2 and the model matrix at 3)
+///
+/// Normal maps are always sampled, without one the default flat normal leaves the surface unchanged
+pub struct PbrShader {
+    module: ShaderModule,
+    color_target_state: Vec<Option<ColorTargetState>>,
+    bind_layout : BindGroupLayout,
+    sampler : Sampler,
+    matrix_bind_group_layout : BindGroupLayout,
+    topology : PrimitiveTopology,
+    white : TextureView,
+    flat_normal : TextureView,
+    vertex_tangents : bool,
+    environment : bool,
+}
+
+impl ProspectShader for PbrShader {
+    fn get_name(&self) -> &str {
+        "PBR Shader"
+    }
+
+    fn get_module(&self) -> &ShaderModule {
+        &self.module
+    }
+
+    fn fragment_state(&self) -> FragmentState {
+        FragmentState {
+            module: &self.module,
+            entry_point: match (self.vertex_tangents, self.environment)
+            {
+                (false, false) => "fs_main",
+                (false, true) => "fs_environment",
+                (true, false) => "fs_tangent",
+                (true, true) => "fs_tangent_environment",
+            },
+            targets: &self.color_target_state,
+        }
+    }
+
+    fn vertex_state(&self) -> VertexState {
+        if self.vertex_tangents
+        {
+            VertexState {
+                module: &self.module,
+                entry_point: "vs_tangent",
+                buffers: &[TangentVertex::VERTEX_BUFFER_LAYOUT],
+            }
+        } else
+        {
+            VertexState {
+                module: &self.module,
+                entry_point: "vs_main",
+                buffers: &[Vertex::VERTEX_BUFFER_LAYOUT],
+            }
+        }
+    }
+
+    fn get_model_matrix_bind_layout(&self) -> Option<&BindGroupLayout> {
+        Some(&self.matrix_bind_group_layout)
+    }
+
+    fn build_render_pipeline(&self, device: &Device, bind_groups : Vec<&BindGroupLayout>) -> RenderPipeline {
+        let mut bind_groups = bind_groups;
+        bind_groups.insert(1, &self.bind_layout);
+        bind_groups.insert(3, &self.matrix_bind_group_layout);
+
+        let mut state = GraphicsContext::DEFAULT_PRIMITIVE_STATE;
+        state.topology = self.topology;
+
+        HighLevelGraphicsContext::create_render_pipeline_with_primitive_state("PBR Shader Render Pipeline", device, self, Some(&bind_groups), state)
+    }
+}
+
+impl PbrShader {
+    pub fn new(window : &ProspectWindow) -> Self
+    {
+        Self::new_with_custom_topology(window, GraphicsContext::DEFAULT_PRIMITIVE_STATE.topology)
+    }
+
+    /// Uses the tangents from `TangentVertex` for normal mapping instead of deriving them per pixel,
+    /// which matches baked normal maps exactly and avoids seams along UV edges. Meshes must be made with `Mesh::from_shape_with_tangents`
+    pub fn new_with_tangents(window : &ProspectWindow) -> Self
+    {
+        let mut shader = Self::new(window);
+        shader.vertex_tangents = true;
+        shader
+    }
+
+    pub fn has_vertex_tangents(&self) -> bool
+    {
+        self.vertex_tangents
+    }
+
+    /// Lights the surface with a `ProspectEnvironment` as well as the point light, replacing the flat ambient term.
+    /// Build the pipeline with the environment's layout in place of the light's and bind `ProspectEnvironment::create_bind_group`
+    pub fn with_environment(mut self) -> Self
+    {
+        self.environment = true;
+        self
+    }
+
+    pub fn uses_environment(&self) -> bool
+    {
+        self.environment
+    }
+
+    pub fn new_with_custom_topology(
+        window : &ProspectWindow,
+        topology : PrimitiveTopology
+    ) -> Self {
+        let surface = window.get_surface_config();
+        let device = window.get_device();
+        let src = include_str!("pbr.wgsl");
+
+        let sampler = GraphicsContext::create_sampler("PBR Shader Sampler", device, Some(FilterMode::Linear), Some(FilterMode::Linear));
+
+        let texture_binding = GraphicsContext::create_texture_binding_type(false, TextureViewDimension::D2, TextureSampleType::Float { filterable: true });
+        let entries = vec![
+            GraphicsContext::create_bind_group_layout_entry(0, ShaderStages::FRAGMENT, GraphicsContext::create_uniform_binding_type()),
+            GraphicsContext::create_bind_group_layout_entry(1, ShaderStages::FRAGMENT, texture_binding),
+            GraphicsContext::create_bind_group_layout_entry(2, ShaderStages::FRAGMENT, texture_binding),
+            GraphicsContext::create_bind_group_layout_entry(3, ShaderStages::FRAGMENT, texture_binding),
+            GraphicsContext::create_bind_group_layout_entry(4, ShaderStages::FRAGMENT, texture_binding),
+            GraphicsContext::create_bind_group_layout_entry(5, ShaderStages::FRAGMENT, texture_binding),
+            GraphicsContext::create_bind_group_layout_entry(6, ShaderStages::FRAGMENT, GraphicsContext::create_sample_binding_type(wgpu::SamplerBindingType::Filtering))
+        ];
+        let bind_group_layout = GraphicsContext::create_bind_group_layout(device, "PBR Shader Bind Group", &entries);
+
+        let matrix_bind_group_layout = vec![
+            GraphicsContext::create_bind_group_layout_entry(0, ShaderStages::VERTEX, GraphicsContext::create_uniform_binding_type())
+        ];
+        let matrix_bind_group_layout = GraphicsContext::create_bind_group_layout(device, "PBR Matrix Bind Layout", &matrix_bind_group_layout);
+
+        let white = GraphicsContext::create_texture_from_image_with_format("PBR Default White", image::RgbaImage::from_pixel(1, 1, image::Rgba([255, 255, 255, 255])), device, window.get_queue(), TextureFormat::Rgba8Unorm);
+        let flat_normal = GraphicsContext::create_texture_from_image_with_format("PBR Default Normal", image::RgbaImage::from_pixel(1, 1, image::Rgba([128, 128, 255, 255])), device, window.get_queue(), TextureFormat::Rgba8Unorm);
+
+        Self {
+            sampler,
+            bind_layout: bind_group_layout,
+            matrix_bind_group_layout,
+            module: GraphicsContext::load_shader("PBR Shader", src.as_ref(), device),
+            color_target_state: vec![Some(ColorTargetState {
+                format: surface.format,
+                blend: Some(BlendState::REPLACE),
+                write_mask: ColorWrites::ALL,
+            })],
+            topology,
+            white : GraphicsContext::create_texture_view(&white),
+            flat_normal : GraphicsContext::create_texture_view(&flat_normal),
+            vertex_tangents : false,
+            environment : false,
+        }
+    }
+
+    fn view_or<'a>(texture : Option<&'a dyn BindableTexture>, default : &'a TextureView) -> &'a TextureView
+    {
+        texture.map(|texture| texture.get_texture_view()).unwrap_or(default)
+    }
+
+    pub fn create_material(&self, window : &ProspectWindow, name : &str, factors : PbrFactors, textures : &PbrTextures) -> PbrMaterial
+    {
+        let buffer = GraphicsContext::create_buffer(window.get_device(), name, &[factors], BufferUsages::UNIFORM | BufferUsages::COPY_DST);
+
+        let entries = vec![
+            GraphicsContext::create_bind_group_entry(0, buffer.as_entire_binding()),
+            GraphicsContext::create_texture_view_resource(1, Self::view_or(textures.base_colour, &self.white)),
+            GraphicsContext::create_texture_view_resource(2, Self::view_or(textures.metallic_roughness, &self.white)),
+            GraphicsContext::create_texture_view_resource(3, Self::view_or(textures.normal, &self.flat_normal)),
+            GraphicsContext::create_texture_view_resource(4, Self::view_or(textures.occlusion, &self.white)),
+            GraphicsContext::create_texture_view_resource(5, Self::view_or(textures.emissive, &self.white)),
+            GraphicsContext::create_sampler_resource(6, &self.sampler),
+        ];
+        let bind_group = GraphicsContext::create_bind_group(window.get_device(), name, &self.bind_layout, &entries);
+
+        PbrMaterial
+        {
+            factors,
+            buffer,
+            bind_group : bind_group.into(),
+        }
+    }
+}
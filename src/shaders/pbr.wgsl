@@ -0,0 +1,297 @@
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) tex_coords: vec2<f32>,
+    @location(2) normal : vec3<f32>
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+    @location(1) world_normal : vec3<f32>,
+    @location(2) world_position : vec3<f32>
+}
+
+struct ModelInformation {
+    matrix : mat4x4<f32>,
+};
+
+@group(3) @binding(0)
+var<uniform> model_information : ModelInformation;
+
+struct CameraUniform {
+    view_proj: mat4x4<f32>,
+    view_pos: vec4<f32>
+};
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+struct LightUniform {
+    position: vec3<f32>,
+    colour: vec3<f32>
+};
+@group(2) @binding(0)
+var<uniform> light : LightUniform;
+
+@vertex
+fn vs_main(
+    model : VertexInput
+) -> VertexOutput
+{
+    var out : VertexOutput;
+    out.tex_coords = model.tex_coords;
+    out.world_normal = normalize(model_information.matrix * vec4<f32>(model.normal, 0.0)).xyz;
+    var world_position : vec4<f32> = model_information.matrix * vec4<f32>(model.position, 1.0);
+    out.world_position = world_position.xyz;
+    out.clip_position = camera.view_proj * world_position;
+    return out;
+}
+
+struct TangentVertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) tex_coords: vec2<f32>,
+    @location(2) normal : vec3<f32>,
+    @location(3) tangent : vec4<f32>
+}
+
+struct TangentVertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+    @location(1) world_normal : vec3<f32>,
+    @location(2) world_position : vec3<f32>,
+    @location(3) world_tangent : vec4<f32>
+}
+
+@vertex
+fn vs_tangent(
+    model : TangentVertexInput
+) -> TangentVertexOutput
+{
+    var out : TangentVertexOutput;
+    out.tex_coords = model.tex_coords;
+    out.world_normal = normalize(model_information.matrix * vec4<f32>(model.normal, 0.0)).xyz;
+    out.world_tangent = vec4<f32>(normalize(model_information.matrix * vec4<f32>(model.tangent.xyz, 0.0)).xyz, model.tangent.w);
+    var world_position : vec4<f32> = model_information.matrix * vec4<f32>(model.position, 1.0);
+    out.world_position = world_position.xyz;
+    out.clip_position = camera.view_proj * world_position;
+    return out;
+}
+
+struct PbrFactors {
+    base_colour: vec4<f32>,
+    emissive: vec3<f32>,
+    metallic: f32,
+    roughness: f32,
+    occlusion_strength: f32,
+    normal_scale: f32,
+};
+
+@group(1) @binding(0)
+var<uniform> factors: PbrFactors;
+@group(1) @binding(1)
+var t_base_colour: texture_2d<f32>;
+// glTF layout, roughness in green and metallic in blue
+@group(1) @binding(2)
+var t_metallic_roughness: texture_2d<f32>;
+@group(1) @binding(3)
+var t_normal: texture_2d<f32>;
+@group(1) @binding(4)
+var t_occlusion: texture_2d<f32>;
+@group(1) @binding(5)
+var t_emissive: texture_2d<f32>;
+@group(1) @binding(6)
+var s_material: sampler;
+
+const PI: f32 = 3.14159265359;
+
+// Builds a tangent frame from screen space derivatives, used when the mesh has no tangents
+fn cotangent_frame(normal: vec3<f32>, position: vec3<f32>, uv: vec2<f32>) -> mat3x3<f32>
+{
+    let dp1 = dpdx(position);
+    let dp2 = dpdy(position);
+    let duv1 = dpdx(uv);
+    let duv2 = dpdy(uv);
+
+    let dp2perp = cross(dp2, normal);
+    let dp1perp = cross(normal, dp1);
+    let tangent = dp2perp * duv1.x + dp1perp * duv2.x;
+    let bitangent = dp2perp * duv1.y + dp1perp * duv2.y;
+
+    let inverse_max = inverseSqrt(max(max(dot(tangent, tangent), dot(bitangent, bitangent)), 0.00000001));
+    return mat3x3<f32>(tangent * inverse_max, bitangent * inverse_max, normal);
+}
+
+fn distribution_ggx(n_dot_h: f32, roughness: f32) -> f32
+{
+    let a = roughness * roughness;
+    let a2 = a * a;
+    let denominator = n_dot_h * n_dot_h * (a2 - 1.0) + 1.0;
+    return a2 / (PI * denominator * denominator);
+}
+
+fn geometry_schlick_ggx(n_dot_x: f32, roughness: f32) -> f32
+{
+    let r = roughness + 1.0;
+    let k = (r * r) / 8.0;
+    return n_dot_x / (n_dot_x * (1.0 - k) + k);
+}
+
+fn geometry_smith(n_dot_v: f32, n_dot_l: f32, roughness: f32) -> f32
+{
+    return geometry_schlick_ggx(n_dot_v, roughness) * geometry_schlick_ggx(n_dot_l, roughness);
+}
+
+fn fresnel_schlick(cos_theta: f32, f0: vec3<f32>) -> vec3<f32>
+{
+    return f0 + (1.0 - f0) * pow(clamp(1.0 - cos_theta, 0.0, 1.0), 5.0);
+}
+
+// Only bound by the environment variants, see ProspectEnvironment
+@group(2) @binding(1)
+var t_irradiance: texture_cube<f32>;
+@group(2) @binding(2)
+var t_prefiltered: texture_cube<f32>;
+@group(2) @binding(3)
+var t_brdf_lut: texture_2d<f32>;
+@group(2) @binding(4)
+var s_environment: sampler;
+
+struct EnvironmentUniform {
+    intensity: f32,
+    // Mip level of the prefiltered map for a roughness of 1
+    max_lod: f32,
+};
+@group(2) @binding(5)
+var<uniform> environment : EnvironmentUniform;
+
+fn fresnel_schlick_roughness(cos_theta: f32, f0: vec3<f32>, roughness: f32) -> vec3<f32>
+{
+    return f0 + (max(vec3<f32>(1.0 - roughness), f0) - f0) * pow(clamp(1.0 - cos_theta, 0.0, 1.0), 5.0);
+}
+
+struct Surface {
+    albedo: vec3<f32>,
+    alpha: f32,
+    metallic: f32,
+    roughness: f32,
+    ambient_occlusion: f32,
+    emissive: vec3<f32>,
+    normal: vec3<f32>,
+    f0: vec3<f32>,
+};
+
+fn surface(tex_coords: vec2<f32>, tbn: mat3x3<f32>) -> Surface
+{
+    let base_colour = textureSample(t_base_colour, s_material, tex_coords) * factors.base_colour;
+    let metallic_roughness = textureSample(t_metallic_roughness, s_material, tex_coords);
+    let tangent_normal = textureSample(t_normal, s_material, tex_coords).xyz * 2.0 - 1.0;
+    let occlusion = textureSample(t_occlusion, s_material, tex_coords).r;
+
+    var out: Surface;
+    out.albedo = base_colour.rgb;
+    out.alpha = base_colour.a;
+    out.metallic = clamp(metallic_roughness.b * factors.metallic, 0.0, 1.0);
+    // Fully smooth surfaces make the specular highlight vanish, so keep a little roughness
+    out.roughness = clamp(metallic_roughness.g * factors.roughness, 0.04, 1.0);
+    out.ambient_occlusion = mix(1.0, occlusion, factors.occlusion_strength);
+    out.emissive = textureSample(t_emissive, s_material, tex_coords).rgb * factors.emissive;
+    out.normal = normalize(tbn * vec3<f32>(tangent_normal.xy * factors.normal_scale, tangent_normal.z));
+    out.f0 = mix(vec3<f32>(0.04), out.albedo, out.metallic);
+    return out;
+}
+
+fn direct_lighting(s: Surface, v: vec3<f32>, world_position: vec3<f32>) -> vec3<f32>
+{
+    let n = s.normal;
+    let l = normalize(light.position - world_position);
+    let h = normalize(v + l);
+
+    let n_dot_v = max(dot(n, v), 0.0001);
+    let n_dot_l = max(dot(n, l), 0.0);
+    let n_dot_h = max(dot(n, h), 0.0);
+    let h_dot_v = max(dot(h, v), 0.0);
+
+    let f = fresnel_schlick(h_dot_v, s.f0);
+    let d = distribution_ggx(n_dot_h, s.roughness);
+    let g = geometry_smith(n_dot_v, n_dot_l, s.roughness);
+
+    let specular = (d * g * f) / (4.0 * n_dot_v * max(n_dot_l, 0.0001));
+    let k_diffuse = (vec3<f32>(1.0) - f) * (1.0 - s.metallic);
+    let diffuse = k_diffuse * s.albedo / PI;
+
+    // The point light has no range, so scale by PI to match the brightness of Default3D's diffuse term
+    let radiance = light.colour * PI;
+    return (diffuse + specular) * radiance * n_dot_l;
+}
+
+// Split sum approximation, diffuse from the irradiance map and specular from the prefiltered map scaled by the BRDF lookup table
+fn environment_lighting(s: Surface, v: vec3<f32>) -> vec3<f32>
+{
+    let n = s.normal;
+    let n_dot_v = max(dot(n, v), 0.0001);
+
+    let f = fresnel_schlick_roughness(n_dot_v, s.f0, s.roughness);
+    let k_diffuse = (vec3<f32>(1.0) - f) * (1.0 - s.metallic);
+    let diffuse = textureSample(t_irradiance, s_environment, n).rgb * s.albedo;
+
+    let r = reflect(-v, n);
+    let prefiltered = textureSampleLevel(t_prefiltered, s_environment, r, s.roughness * environment.max_lod).rgb;
+    let brdf = textureSample(t_brdf_lut, s_environment, vec2<f32>(n_dot_v, s.roughness)).rg;
+    let specular = prefiltered * (f * brdf.x + brdf.y);
+
+    return (k_diffuse * diffuse + specular) * s.ambient_occlusion * environment.intensity;
+}
+
+fn shade(tex_coords: vec2<f32>, world_position: vec3<f32>, tbn: mat3x3<f32>) -> vec4<f32>
+{
+    let s = surface(tex_coords, tbn);
+    let v = normalize(camera.view_pos.xyz - world_position);
+
+    let ambient = vec3<f32>(0.03) * s.albedo * s.ambient_occlusion;
+
+    return vec4<f32>(ambient + direct_lighting(s, v, world_position) + s.emissive, s.alpha);
+}
+
+fn shade_environment(tex_coords: vec2<f32>, world_position: vec3<f32>, tbn: mat3x3<f32>) -> vec4<f32>
+{
+    let s = surface(tex_coords, tbn);
+    let v = normalize(camera.view_pos.xyz - world_position);
+
+    return vec4<f32>(environment_lighting(s, v) + direct_lighting(s, v, world_position) + s.emissive, s.alpha);
+}
+
+fn vertex_tbn(in: TangentVertexOutput) -> mat3x3<f32>
+{
+    let normal = normalize(in.world_normal);
+    // Re-orthogonalise, interpolation pulls the tangent off the normal's plane
+    let tangent = normalize(in.world_tangent.xyz - normal * dot(normal, in.world_tangent.xyz));
+    let bitangent = cross(normal, tangent) * in.world_tangent.w;
+    return mat3x3<f32>(tangent, bitangent, normal);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32>
+{
+    let geometric_normal = normalize(in.world_normal);
+    let tbn = cotangent_frame(geometric_normal, in.world_position, in.tex_coords);
+    return shade(in.tex_coords, in.world_position, tbn);
+}
+
+@fragment
+fn fs_tangent(in: TangentVertexOutput) -> @location(0) vec4<f32>
+{
+    return shade(in.tex_coords, in.world_position, vertex_tbn(in));
+}
+
+@fragment
+fn fs_environment(in: VertexOutput) -> @location(0) vec4<f32>
+{
+    let geometric_normal = normalize(in.world_normal);
+    let tbn = cotangent_frame(geometric_normal, in.world_position, in.tex_coords);
+    return shade_environment(in.tex_coords, in.world_position, tbn);
+}
+
+@fragment
+fn fs_tangent_environment(in: TangentVertexOutput) -> @location(0) vec4<f32>
+{
+    return shade_environment(in.tex_coords, in.world_position, vertex_tbn(in));
+}
@@ -0,0 +1,48 @@
+struct SkyboxUniform {
+    // Camera projection and rotation, without its position
+    view_proj: mat4x4<f32>,
+};
+
+@group(0) @binding(0)
+var t_skybox: texture_cube<f32>;
+@group(0) @binding(1)
+var s_skybox: sampler;
+@group(0) @binding(2)
+var<uniform> skybox: SkyboxUniform;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) direction: vec3<f32>,
+}
+
+// A unit cube from 36 vertex indices, so the skybox needs no vertex buffer
+fn cube_corner(index: u32) -> vec3<f32>
+{
+    var faces = array<vec3<u32>, 12>(
+        vec3<u32>(1u, 5u, 7u), vec3<u32>(1u, 7u, 3u), // +X
+        vec3<u32>(0u, 2u, 6u), vec3<u32>(0u, 6u, 4u), // -X
+        vec3<u32>(2u, 3u, 7u), vec3<u32>(2u, 7u, 6u), // +Y
+        vec3<u32>(0u, 4u, 5u), vec3<u32>(0u, 5u, 1u), // -Y
+        vec3<u32>(4u, 6u, 7u), vec3<u32>(4u, 7u, 5u), // +Z
+        vec3<u32>(0u, 1u, 3u), vec3<u32>(0u, 3u, 2u), // -Z
+    );
+    let corner = faces[index / 3u][index % 3u];
+    return vec3<f32>(f32(corner & 1u), f32((corner >> 1u) & 1u), f32((corner >> 2u) & 1u)) * 2.0 - 1.0;
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput
+{
+    var out : VertexOutput;
+    out.direction = cube_corner(index);
+    let clip = skybox.view_proj * vec4<f32>(out.direction, 1.0);
+    // z = w puts every fragment on the far plane, behind everything else
+    out.clip_position = clip.xyww;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32>
+{
+    return textureSample(t_skybox, s_skybox, normalize(in.direction));
+}
@@ -5,7 +5,7 @@ use wgpu::*;
 use crate::{
     abstraction::{
         graphics_context::GraphicsContext, high_level_abstraction::HighLevelGraphicsContext,
-        prospect_window::ProspectWindow, vertex::Vertex,
+        prospect_window::ProspectWindow, vertex::{Vertex, VertexLayout},
     },
     prospect_camera::ProspectCamera,
     prospect_light::ProspectPointLight,
@@ -0,0 +1,47 @@
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) tex_coords: vec2<f32>,
+    @location(2) colour: vec4<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+    @location(1) colour: vec4<f32>,
+}
+
+struct CameraUniform {
+    view_proj: mat4x4<f32>,
+    view_pos: vec4<f32>
+};
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+@vertex
+fn vs_main(
+    model : VertexInput
+) -> VertexOutput
+{
+    var out : VertexOutput;
+    out.clip_position = camera.view_proj * vec4<f32>(model.position, 1.0);
+    out.tex_coords = model.tex_coords;
+    out.colour = model.colour;
+    return out;
+}
+
+@group(1) @binding(0)
+var t_glyphs: texture_2d<f32>;
+@group(1) @binding(1)
+var s_glyphs: sampler;
+
+// The atlas only holds coverage, so the colour comes entirely from the vertex
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32>
+{
+    let coverage = textureSample(t_glyphs, s_glyphs, in.tex_coords).a;
+    if (coverage * in.colour.a <= 0.0)
+    {
+        discard;
+    }
+    return vec4<f32>(in.colour.rgb, in.colour.a * coverage);
+}
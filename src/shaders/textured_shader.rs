@@ -1,8 +1,8 @@
 use wgpu::{
-    BlendState, ColorTargetState, ColorWrites, Device, FragmentState, ShaderModule, VertexState, RenderPipeline, ShaderStages, TextureViewDimension, TextureSampleType, BindGroupLayout, BindGroup, Sampler, TextureView,
+    BlendState, ColorTargetState, ColorWrites, Device, FragmentState, ShaderModule, VertexState, RenderPipeline, BindGroupLayout, BindGroup, TextureView,
 };
 
-use crate::{abstraction::{high_level_abstraction::HighLevelGraphicsContext, shader::ProspectShader, vertex::Vertex, prospect_window::ProspectWindow, graphics_context::GraphicsContext}, prospect_texture::{ProspectTexture, BindableTexture}, smart::SmartBindGroup};
+use crate::{abstraction::{high_level_abstraction::HighLevelGraphicsContext, shader::ProspectShader, vertex::{Vertex, VertexLayout}, prospect_window::ProspectWindow, graphics_context::GraphicsContext}, prospect_texture::{BindableTexture, TextureBindLayout}, smart::SmartBindGroup};
 
 #[derive(Debug)]
 pub struct TexturedShaderTexture
@@ -13,8 +13,7 @@ pub struct TexturedShaderTexture
 pub struct TexturedShader {
     module: ShaderModule,
     color_target_state: Vec<Option<ColorTargetState>>,
-    bind_layout : BindGroupLayout,
-    sampler : Sampler
+    textures : TextureBindLayout,
 }
 
 impl ProspectShader for TexturedShader {
@@ -44,7 +43,7 @@ impl ProspectShader for TexturedShader {
 
     fn build_render_pipeline(&self, device: &Device, bind_groups : Vec<&BindGroupLayout>) -> RenderPipeline {
         let mut bind_groups = bind_groups;
-        bind_groups.push(&self.bind_layout);
+        bind_groups.push(self.textures.get_layout());
         HighLevelGraphicsContext::create_render_pipeline("Textured Shader Render Pipeline", device, self, Some(&bind_groups))
     }
 }
@@ -58,15 +57,10 @@ impl TexturedShader {
         let src = include_str!("textured_shader.wgsl");
 
         let sampler = GraphicsContext::create_sampler("Textured Shader Sampler", device, Some(wgpu::FilterMode::Nearest), Some(wgpu::FilterMode::Nearest));
-        let entries = vec![
-            GraphicsContext::create_bind_group_layout_entry(0, ShaderStages::FRAGMENT, GraphicsContext::create_texture_binding_type(false, TextureViewDimension::D2, TextureSampleType::Float { filterable: true })),
-            GraphicsContext::create_bind_group_layout_entry(1, ShaderStages::FRAGMENT, GraphicsContext::create_sample_binding_type(wgpu::SamplerBindingType::Filtering))
-        ];
-        let bind_group_layout = GraphicsContext::create_bind_group_layout(device, "Textured Shader Bind Group", &entries);
+        let textures = TextureBindLayout::new(device, "Textured Shader Bind Group", sampler);
 
         Self {
-            sampler,
-            bind_layout: bind_group_layout,
+            textures,
             module: GraphicsContext::load_shader("Textured Shader", src.as_ref(), device),
             color_target_state: vec![Some(ColorTargetState {
                 format: surface.format,
@@ -84,15 +78,10 @@ impl TexturedShader {
         let src = include_str!("textured_shader.wgsl");
 
         let sampler = GraphicsContext::create_sampler("Textured Shader Sampler", device, None, None);
-        let entries = vec![
-            GraphicsContext::create_bind_group_layout_entry(0, ShaderStages::FRAGMENT, GraphicsContext::create_texture_binding_type(false, TextureViewDimension::D2, TextureSampleType::Float { filterable: true })),
-            GraphicsContext::create_bind_group_layout_entry(1, ShaderStages::FRAGMENT, GraphicsContext::create_sample_binding_type(wgpu::SamplerBindingType::Filtering))
-        ];
-        let bind_group_layout = GraphicsContext::create_bind_group_layout(device, "Textured Shader Bind Group", &entries);
+        let textures = TextureBindLayout::new(device, "Textured Shader Bind Group", sampler);
 
         Self {
-            sampler,
-            bind_layout: bind_group_layout,
+            textures,
             module: GraphicsContext::load_shader("Textured Shader", src.as_ref(), device),
             color_target_state: vec![Some(ColorTargetState {
                 format: surface.format,
@@ -102,23 +91,23 @@ impl TexturedShader {
         }
     }
     
+    pub fn get_texture_layout(&self) -> &TextureBindLayout
+    {
+        &self.textures
+    }
+
     pub fn create_texture(&self, window : &ProspectWindow, texture : &TextureView, name : &str) -> BindGroup
     {
-        let view_resource = GraphicsContext::create_texture_view_resource(0, texture);
-        let sampler_resource = GraphicsContext::create_sampler_resource(1, &self.sampler);
-        GraphicsContext::create_bind_group(window.get_device(), name, &self.bind_layout, &vec![view_resource, sampler_resource])
+        self.textures.create_texture(window, texture, name)
     }
 
     pub fn register_texture(&self, name: &str, bytes : &[u8], window: &mut ProspectWindow) -> SmartBindGroup
     {
-        let texture_view = HighLevelGraphicsContext::create_texture_from_file(name, bytes, window);
-        let bind_group = self.create_texture(window, &texture_view, name);
-        bind_group.into()
+        self.textures.register_texture(name, bytes, window)
     }
 
     pub fn bind_prospect_texture<T : BindableTexture>(&self, prospect_texture : &T, window: &mut ProspectWindow) -> SmartBindGroup
     {
-        let bind_group = self.create_texture(window, prospect_texture.get_texture_view(), &prospect_texture.get_name());
-        bind_group.into()
-    }  
+        self.textures.bind_prospect_texture(prospect_texture, window)
+    }
 }
@@ -1,7 +1,9 @@
+mod versions;
 mod smart_render_pipeline;
 mod smart_bind_group;
 mod smart_shader;
 mod smart_model;
 
 pub use smart_bind_group::*;
-pub use smart_render_pipeline::*;
\ No newline at end of file
+pub use smart_render_pipeline::*;
+pub(crate) use versions::Versions;
\ No newline at end of file
@@ -1,11 +1,13 @@
-use std::{sync::Mutex, rc::Rc};
+use std::sync::Mutex;
 
 use wgpu::*;
 
+use super::versions::Versions;
+
 #[derive(Debug)]
 pub struct SmartBindGroup
 {
-    inner : Rc<BindGroup>
+    inner : Versions<BindGroup>
 }
 
 impl SmartBindGroup
@@ -14,13 +16,31 @@ impl SmartBindGroup
     {
         Self
         {
-            inner : Rc::new(bind_group)
+            inner : Versions::new(bind_group)
         }
     }
 
     pub fn set_bind_group<'a>(&'a self, render_pass : &mut RenderPass<'a>, index : u32, offsets : &[DynamicOffset])
     {
-        render_pass.set_bind_group(index, &self.inner, offsets)
+        render_pass.set_bind_group(index, self.inner.latest(), offsets)
+    }
+
+    /// Swaps in a new bind group for this and every clone of it, e.g after the texture it points to is reloaded (see `AssetServer`).
+    /// Clones keep the old one alive, since render passes may still be using it, until they're next `trim`med, replaced or cloned
+    pub fn replace(&mut self, bind_group : BindGroup)
+    {
+        self.inner.push(bind_group)
+    }
+
+    /// Drops this handle's hold on versions older than the latest, for clones that another handle has `replace`d
+    pub fn trim(&mut self)
+    {
+        self.inner.trim()
+    }
+
+    pub(crate) fn is_stale(&self) -> bool
+    {
+        self.inner.is_stale()
     }
 
 }
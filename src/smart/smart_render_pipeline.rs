@@ -1,11 +1,13 @@
-use std::rc::Rc;
+use std::fmt::Debug;
 
 use wgpu::*;
 
+use super::versions::Versions;
+
 #[derive(Debug)]
 pub struct SmartRenderPipeline
 {
-    inner : Rc<RenderPipeline>
+    inner : Versions<RenderPipeline>
 }
 
 impl SmartRenderPipeline
@@ -14,13 +16,31 @@ impl SmartRenderPipeline
     {
         Self
         {
-            inner : Rc::new(pipeline)
+            inner : Versions::new(pipeline)
         }
     }
 
     pub fn apply<'a>(&'a self, pass : &mut RenderPass<'a>)
     {
-        pass.set_pipeline(&self.inner)
+        pass.set_pipeline(self.inner.latest())
+    }
+
+    /// Swaps in a new pipeline for this and every clone of it, e.g after its shader is reloaded (see `ShaderRegistry`).
+    /// Clones keep the old one alive, since render passes may still be using it, until they're next `trim`med, replaced or cloned
+    pub fn replace(&mut self, pipeline : RenderPipeline)
+    {
+        self.inner.push(pipeline)
+    }
+
+    /// Drops this handle's hold on versions older than the latest, for clones that another handle has `replace`d
+    pub fn trim(&mut self)
+    {
+        self.inner.trim()
+    }
+
+    pub(crate) fn is_stale(&self) -> bool
+    {
+        self.inner.is_stale()
     }
 }
 
@@ -36,4 +56,4 @@ impl From<RenderPipeline> for SmartRenderPipeline
     fn from(value: RenderPipeline) -> Self {
         Self::new(value)
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,81 @@
+use std::{cell::OnceCell, fmt::Debug, rc::Rc};
+
+struct Version<T>
+{
+    value : T,
+    next : OnceCell<Rc<Version<T>>>,
+}
+
+/// A handle to a GPU object that can be swapped for newer versions, shared by every clone.
+///
+/// Versions form a chain where each one is only ever followed by a newer one, so references handed to render passes stay valid
+/// after a `push`. A handle keeps the versions from where it starts alive and reads the latest by following the chain from there.
+/// `push`, `trim` and `clone` all move a handle to the latest, so a version is dropped as soon as every handle has been used mutably
+/// since it was replaced, and reading through a handle only walks the versions pushed by other handles in the meantime
+pub(crate) struct Versions<T>
+{
+    head : Rc<Version<T>>
+}
+
+impl<T> Versions<T>
+{
+    pub(crate) fn new(first : T) -> Self
+    {
+        Self
+        {
+            head : Rc::new(Version { value : first, next : OnceCell::new() })
+        }
+    }
+
+    fn latest_version(&self) -> &Rc<Version<T>>
+    {
+        let mut version = &self.head;
+        while let Some(next) = version.next.get()
+        {
+            version = next;
+        }
+        version
+    }
+
+    pub(crate) fn latest(&self) -> &T
+    {
+        &self.latest_version().value
+    }
+
+    /// Adds a version for every handle and moves this one onto it
+    pub(crate) fn push(&mut self, value : T)
+    {
+        let version = Rc::new(Version { value, next : OnceCell::new() });
+        // The latest version has nothing after it so this always sets
+        let _ = self.latest_version().next.set(version.clone());
+        self.head = version;
+    }
+
+    /// Whether a newer version has been pushed since this handle was made or last trimmed
+    pub(crate) fn is_stale(&self) -> bool
+    {
+        self.head.next.get().is_some()
+    }
+
+    /// Lets go of every version before the latest, they're dropped once no other handle starts before them
+    pub(crate) fn trim(&mut self)
+    {
+        self.head = self.latest_version().clone();
+    }
+}
+
+impl<T> Clone for Versions<T>
+{
+    /// Starts at the latest version so clones don't hold older ones alive
+    fn clone(&self) -> Self
+    {
+        Self { head : self.latest_version().clone() }
+    }
+}
+
+impl<T : Debug> Debug for Versions<T>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Versions").field("latest", self.latest()).finish()
+    }
+}
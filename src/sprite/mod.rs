@@ -0,0 +1,7 @@
+mod sprite_batch;
+mod sprite_sheet;
+mod nine_slice;
+
+pub use sprite_batch::*;
+pub use sprite_sheet::*;
+pub use nine_slice::*;
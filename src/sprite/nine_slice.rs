@@ -0,0 +1,80 @@
+/// A panel texture cut into a 3x3 grid, the corners are drawn at a fixed size, the edges stretch along one axis and the centre along both
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NineSlice
+{
+    /// The whole panel within its texture (min u, min v, max u, max v)
+    pub uv : [f32; 4],
+    /// Size of the panel's region of the texture in texels
+    pub texel_size : (u32, u32),
+    /// Left, right, top and bottom borders in texels
+    pub border : [u32; 4],
+    /// World units per texel of border
+    pub scale : f32,
+}
+
+impl NineSlice
+{
+    /// A panel taking up the whole texture
+    pub fn new(texture_size : (u32, u32), border : [u32; 4]) -> Self
+    {
+        Self
+        {
+            uv : [0., 0., 1., 1.],
+            texel_size : texture_size,
+            border,
+            scale : 1.,
+        }
+    }
+
+    /// A panel taking up part of a texture, such as a region of an atlas
+    pub fn from_region(uv : [f32; 4], texel_size : (u32, u32), border : [u32; 4]) -> Self
+    {
+        Self
+        {
+            uv,
+            texel_size,
+            border,
+            scale : 1.,
+        }
+    }
+
+    pub fn with_scale(mut self, scale : f32) -> Self
+    {
+        self.scale = scale;
+        self
+    }
+
+    /// Min, max and UV rectangle of each of the nine pieces for a panel with its bottom left at `position`.
+    /// Borders shrink evenly if `size` is too small to fit them
+    pub fn slices(&self, position : (f32, f32), size : (f32, f32)) -> [((f32, f32), (f32, f32), [f32; 4]); 9]
+    {
+        let [left, right, top, bottom] = self.border.map(|border| border as f32 * self.scale);
+        let fit_x = if left + right > size.0 && left + right > 0. { size.0 / (left + right) } else { 1. };
+        let fit_y = if top + bottom > size.1 && top + bottom > 0. { size.1 / (top + bottom) } else { 1. };
+
+        // World space cuts from left to right and bottom to top
+        let xs = [position.0, position.0 + left * fit_x, position.0 + size.0 - right * fit_x, position.0 + size.0];
+        let ys = [position.1, position.1 + bottom * fit_y, position.1 + size.1 - top * fit_y, position.1 + size.1];
+
+        // UV cuts, v runs down the texture so the bottom of the panel is the largest v
+        let [u0, v0, u1, v1] = self.uv;
+        let du = (u1 - u0) / self.texel_size.0.max(1) as f32;
+        let dv = (v1 - v0) / self.texel_size.1.max(1) as f32;
+        let us = [u0, u0 + self.border[0] as f32 * du, u1 - self.border[1] as f32 * du, u1];
+        let vs = [v1, v1 - self.border[3] as f32 * dv, v0 + self.border[2] as f32 * dv, v0];
+
+        let mut slices = [((0., 0.), (0., 0.), [0.; 4]); 9];
+        for row in 0..3
+        {
+            for column in 0..3
+            {
+                slices[row * 3 + column] = (
+                    (xs[column], ys[row]),
+                    (xs[column + 1], ys[row + 1]),
+                    [us[column], vs[row + 1], us[column + 1], vs[row]],
+                );
+            }
+        }
+        slices
+    }
+}
@@ -0,0 +1,332 @@
+use wgpu::*;
+
+use crate::{abstraction::{graphics_context::GraphicsContext, prospect_window::ProspectWindow, vertex::VertexLayout}, prospect_camera::ProspectCamera, prospect_texture::{BindableTexture, TextureBindLayout}, smart::SmartBindGroup};
+
+use super::nine_slice::NineSlice;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable, VertexLayout)]
+struct SpriteVertex
+{
+    position : [f32; 2],
+    uv : [f32; 2],
+    colour : [f32; 4],
+}
+
+/// A texture registered with a `SpriteBatch`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SpriteTexture(usize);
+
+/// A textured quad, positions and sizes are in the units of the orthographic camera it's drawn with
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sprite
+{
+    /// Where `origin` ends up
+    pub position : (f32, f32),
+    pub size : (f32, f32),
+    /// The point rotated around, (0, 0) is the bottom left and (1, 1) the top right
+    pub origin : (f32, f32),
+    /// Anticlockwise, in radians
+    pub rotation : f32,
+    /// (min u, min v, max u, max v)
+    pub uv : [f32; 4],
+    /// Multiplies the texture
+    pub colour : [f32; 4],
+    /// Higher layers are drawn on top
+    pub layer : i32,
+    pub flip_x : bool,
+    pub flip_y : bool,
+}
+
+impl Sprite
+{
+    /// The whole texture, centred on `position`
+    pub fn new(position : (f32, f32), size : (f32, f32)) -> Self
+    {
+        Self
+        {
+            position,
+            size,
+            origin : (0.5, 0.5),
+            rotation : 0.,
+            uv : [0., 0., 1., 1.],
+            colour : [1.; 4],
+            layer : 0,
+            flip_x : false,
+            flip_y : false,
+        }
+    }
+
+    pub fn with_uv(mut self, uv : [f32; 4]) -> Self
+    {
+        self.uv = uv;
+        self
+    }
+
+    pub fn with_colour(mut self, colour : [f32; 4]) -> Self
+    {
+        self.colour = colour;
+        self
+    }
+
+    pub fn with_rotation(mut self, rotation : f32) -> Self
+    {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn with_origin(mut self, origin : (f32, f32)) -> Self
+    {
+        self.origin = origin;
+        self
+    }
+
+    pub fn with_layer(mut self, layer : i32) -> Self
+    {
+        self.layer = layer;
+        self
+    }
+
+    pub fn with_flip(mut self, flip_x : bool, flip_y : bool) -> Self
+    {
+        self.flip_x = flip_x;
+        self.flip_y = flip_y;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct QueuedQuad
+{
+    texture : SpriteTexture,
+    layer : i32,
+    /// Bottom left, bottom right, top left, top right
+    vertices : [SpriteVertex; 4],
+}
+
+struct DrawCall
+{
+    texture : SpriteTexture,
+    indices : std::ops::Range<u32>,
+}
+
+/// Collects sprites each frame into one streaming vertex buffer, drawn with a call per run of the same texture.
+///
+/// Sprites are sorted by layer and then by texture, so keep related sprites on a shared texture (see `AtlasBuilder`) to keep the calls down.
+/// Submission order is kept between sprites with the same layer and texture. Queue sprites, call `process_frame` and then `draw`
+/// inside a render pass using the window's depth buffer, with an orthographic camera (see `ProspectCamera::new_orthographic`)
+pub struct SpriteBatch
+{
+    pipeline : RenderPipeline,
+    textures : TextureBindLayout,
+    bind_groups : Vec<SmartBindGroup>,
+    vertex_buffer : Buffer,
+    index_buffer : Buffer,
+    capacity : u32,
+    quads : Vec<QueuedQuad>,
+    draw_calls : Vec<DrawCall>,
+}
+
+impl SpriteBatch
+{
+    /// `camera_layout` is `ProspectCamera::get_layout` of the camera the sprites are drawn with
+    pub fn new(window : &ProspectWindow, camera_layout : &BindGroupLayout) -> Self
+    {
+        let device = window.get_device();
+        let module = GraphicsContext::load_shader("Sprite Shader", include_str!("../shaders/sprite.wgsl"), device);
+
+        let sampler = GraphicsContext::create_sampler("Sprite Batch Sampler", device, Some(FilterMode::Nearest), Some(FilterMode::Nearest));
+        let textures = TextureBindLayout::new(device, "Sprite Batch Bind Group", sampler);
+        let pipeline_layout = GraphicsContext::create_pipeline_layout("Sprite Batch Pipeline Layout", device, &vec![camera_layout, textures.get_layout()]);
+
+        let targets = [Some(ColorTargetState {
+            format: window.get_surface_config().format,
+            blend: Some(BlendState::ALPHA_BLENDING),
+            write_mask: ColorWrites::ALL,
+        })];
+
+        let fragment_state = FragmentState {
+            module: &module,
+            entry_point: "fs_main",
+            targets: &targets,
+        };
+
+        let vertex_state = VertexState {
+            module: &module,
+            entry_point: "vs_main",
+            buffers: &[SpriteVertex::VERTEX_BUFFER_LAYOUT],
+        };
+
+        // Flipped sprites are wound the other way
+        let mut state = GraphicsContext::DEFAULT_PRIMITIVE_STATE;
+        state.cull_mode = None;
+
+        // Layers are ordered by drawing, not depth
+        let depth_stencil = DepthStencilState {
+            format: GraphicsContext::DEPTH_FORMAT,
+            depth_write_enabled: false,
+            depth_compare: CompareFunction::Always,
+            stencil: StencilState::default(),
+            bias: DepthBiasState::default(),
+        };
+
+        let pipeline = GraphicsContext::create_render_pipeline_with_depth_stencil("Sprite Batch Pipeline", &pipeline_layout, fragment_state, vertex_state, device, state, depth_stencil);
+
+        let capacity = 256;
+        let (vertex_buffer, index_buffer) = Self::create_buffers(device, capacity);
+
+        Self
+        {
+            pipeline,
+            textures,
+            bind_groups : Vec::new(),
+            vertex_buffer,
+            index_buffer,
+            capacity,
+            quads : Vec::new(),
+            draw_calls : Vec::new(),
+        }
+    }
+
+    fn create_buffers(device : &Device, capacity : u32) -> (Buffer, Buffer)
+    {
+        let vertices = vec![SpriteVertex { position : [0.; 2], uv : [0.; 2], colour : [0.; 4] }; capacity as usize * 4];
+        let indices : Vec<u32> = (0..capacity).flat_map(|quad| [0, 1, 2, 2, 1, 3].map(|index| quad * 4 + index)).collect();
+
+        (
+            GraphicsContext::create_buffer(device, "Sprite Vertex Buffer", &vertices, BufferUsages::VERTEX | BufferUsages::COPY_DST),
+            GraphicsContext::create_buffer(device, "Sprite Index Buffer", &indices, BufferUsages::INDEX),
+        )
+    }
+
+    /// Sprites are filtered with nearest sampling, unless the texture has its own sampler (see `TextureOptions`)
+    pub fn add_texture<T : BindableTexture + ?Sized>(&mut self, window : &ProspectWindow, texture : &T) -> SpriteTexture
+    {
+        self.bind_groups.push(self.textures.bind_prospect_texture(texture, window));
+        SpriteTexture(self.bind_groups.len() - 1)
+    }
+
+    /// Swaps the texture behind `sprite_texture`, every sprite drawn with it afterwards uses the new one
+    pub fn replace_texture<T : BindableTexture + ?Sized>(&mut self, window : &ProspectWindow, sprite_texture : SpriteTexture, texture : &T)
+    {
+        self.bind_groups[sprite_texture.0] = self.textures.bind_prospect_texture(texture, window);
+    }
+
+    pub fn draw_sprite(&mut self, texture : SpriteTexture, sprite : &Sprite)
+    {
+        let (sin, cos) = sprite.rotation.sin_cos();
+        let corner = |x : f32, y : f32|
+        {
+            let local = ((x - sprite.origin.0) * sprite.size.0, (y - sprite.origin.1) * sprite.size.1);
+            [sprite.position.0 + local.0 * cos - local.1 * sin, sprite.position.1 + local.0 * sin + local.1 * cos]
+        };
+
+        let [mut u0, mut v0, mut u1, mut v1] = sprite.uv;
+        if sprite.flip_x
+        {
+            std::mem::swap(&mut u0, &mut u1);
+        }
+        if sprite.flip_y
+        {
+            std::mem::swap(&mut v0, &mut v1);
+        }
+
+        // Textures have v going down, so the top of the quad gets the smaller v
+        self.quads.push(QueuedQuad
+        {
+            texture,
+            layer : sprite.layer,
+            vertices : [
+                SpriteVertex { position : corner(0., 0.), uv : [u0, v1], colour : sprite.colour },
+                SpriteVertex { position : corner(1., 0.), uv : [u1, v1], colour : sprite.colour },
+                SpriteVertex { position : corner(0., 1.), uv : [u0, v0], colour : sprite.colour },
+                SpriteVertex { position : corner(1., 1.), uv : [u1, v0], colour : sprite.colour },
+            ],
+        });
+    }
+
+    /// An axis aligned, unrotated quad from `min` to `max`
+    pub fn draw_rect(&mut self, texture : SpriteTexture, min : (f32, f32), max : (f32, f32), uv : [f32; 4], colour : [f32; 4], layer : i32)
+    {
+        let sprite = Sprite
+        {
+            position : min,
+            size : (max.0 - min.0, max.1 - min.1),
+            origin : (0., 0.),
+            uv,
+            colour,
+            layer,
+            ..Sprite::new((0., 0.), (0., 0.))
+        };
+        self.draw_sprite(texture, &sprite);
+    }
+
+    /// A panel whose corners keep their size while the edges and centre stretch, `position` is the bottom left
+    pub fn draw_nine_slice(&mut self, texture : SpriteTexture, nine_slice : &NineSlice, position : (f32, f32), size : (f32, f32), colour : [f32; 4], layer : i32)
+    {
+        for (min, max, uv) in nine_slice.slices(position, size)
+        {
+            if max.0 > min.0 && max.1 > min.1
+            {
+                self.draw_rect(texture, min, max, uv, colour, layer);
+            }
+        }
+    }
+
+    /// Sorts and uploads everything drawn since the last call
+    pub fn process_frame(&mut self, window : &ProspectWindow)
+    {
+        // Stable, so sprites sharing a layer and texture keep their order
+        self.quads.sort_by_key(|quad| (quad.layer, quad.texture));
+
+        self.draw_calls.clear();
+        for (index, quad) in self.quads.iter().enumerate()
+        {
+            let index = index as u32;
+            match self.draw_calls.last_mut()
+            {
+                Some(call) if call.texture == quad.texture => call.indices.end = (index + 1) * 6,
+                _ => self.draw_calls.push(DrawCall { texture : quad.texture, indices : index * 6..(index + 1) * 6 }),
+            }
+        }
+
+        let quads = self.quads.len() as u32;
+        if quads > self.capacity
+        {
+            self.capacity = quads.next_power_of_two();
+            (self.vertex_buffer, self.index_buffer) = Self::create_buffers(window.get_device(), self.capacity);
+        }
+
+        if !self.quads.is_empty()
+        {
+            let vertices : Vec<SpriteVertex> = self.quads.iter().flat_map(|quad| quad.vertices).collect();
+            GraphicsContext::update_buffer(window.get_queue(), &self.vertex_buffer, 0, &vertices);
+        }
+        self.quads.clear();
+    }
+
+    /// The number of draw calls the last `process_frame` produced
+    pub fn get_draw_call_count(&self) -> usize
+    {
+        self.draw_calls.len()
+    }
+
+    pub fn draw<'a>(&'a self, render_pass : &mut RenderPass<'a>, camera : &'a ProspectCamera)
+    {
+        if self.draw_calls.is_empty()
+        {
+            return;
+        }
+
+        render_pass.set_pipeline(&self.pipeline);
+        camera.bind(render_pass, 0);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), IndexFormat::Uint32);
+
+        for call in &self.draw_calls
+        {
+            self.bind_groups[call.texture.0].set_bind_group(render_pass, 1, &[]);
+            render_pass.draw_indexed(call.indices.clone(), 0, 0..1);
+        }
+    }
+}
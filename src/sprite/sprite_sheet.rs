@@ -0,0 +1,130 @@
+/// A texture split into a grid of equally sized frames, numbered row by row from the top left
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpriteSheet
+{
+    pub columns : u32,
+    pub rows : u32,
+    /// Texels around each frame that aren't part of it, as made by `AtlasLayout::pad_image`
+    pub padding : u32,
+    /// Needed to convert `padding` to UVs
+    pub texture_size : (u32, u32),
+}
+
+impl SpriteSheet
+{
+    pub fn new(columns : u32, rows : u32, texture_size : (u32, u32)) -> Self
+    {
+        Self
+        {
+            columns,
+            rows,
+            padding : 0,
+            texture_size,
+        }
+    }
+
+    pub fn with_padding(mut self, padding : u32) -> Self
+    {
+        self.padding = padding;
+        self
+    }
+
+    pub fn frame_count(&self) -> u32
+    {
+        self.columns * self.rows
+    }
+
+    /// The UV rectangle (min u, min v, max u, max v) of `frame`, wrapping past the last frame
+    pub fn frame_uv(&self, frame : u32) -> [f32; 4]
+    {
+        let frame = frame % self.frame_count().max(1);
+        let column = frame % self.columns.max(1);
+        let row = frame / self.columns.max(1);
+
+        let width = self.texture_size.0 as f32;
+        let height = self.texture_size.1 as f32;
+        let frame_width = width / self.columns.max(1) as f32;
+        let frame_height = height / self.rows.max(1) as f32;
+        let padding = self.padding as f32;
+
+        let x = column as f32 * frame_width + padding;
+        let y = row as f32 * frame_height + padding;
+        [x / width, y / height, (x + frame_width - padding * 2.) / width, (y + frame_height - padding * 2.) / height]
+    }
+}
+
+/// Steps through frames of a `SpriteSheet` at a fixed rate
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpriteAnimation
+{
+    pub frames : Vec<u32>,
+    /// Seconds each frame is shown for
+    pub frame_time : f32,
+    pub looping : bool,
+    time : f32,
+}
+
+impl SpriteAnimation
+{
+    pub fn new(frames : Vec<u32>, frame_time : f32, looping : bool) -> Self
+    {
+        Self
+        {
+            frames,
+            frame_time,
+            looping,
+            time : 0.,
+        }
+    }
+
+    /// `count` frames in a row starting at `first`
+    pub fn from_range(first : u32, count : u32, frame_time : f32, looping : bool) -> Self
+    {
+        Self::new((first..first + count).collect(), frame_time, looping)
+    }
+
+    /// Advance by `delta` seconds
+    pub fn update(&mut self, delta : f32)
+    {
+        self.time += delta;
+
+        let duration = self.duration();
+        if self.looping && duration > 0.
+        {
+            self.time %= duration;
+        }
+    }
+
+    pub fn reset(&mut self)
+    {
+        self.time = 0.;
+    }
+
+    pub fn duration(&self) -> f32
+    {
+        self.frame_time * self.frames.len() as f32
+    }
+
+    /// True once a non-looping animation has reached its last frame
+    pub fn is_finished(&self) -> bool
+    {
+        !self.looping && self.time >= self.duration()
+    }
+
+    /// The frame of the sheet to show, non-looping animations stay on their last frame
+    pub fn current_frame(&self) -> u32
+    {
+        if self.frames.is_empty()
+        {
+            return 0;
+        }
+
+        let index = if self.frame_time > 0. { (self.time / self.frame_time) as usize } else { 0 };
+        self.frames[index.min(self.frames.len() - 1)]
+    }
+
+    pub fn current_uv(&self, sheet : &SpriteSheet) -> [f32; 4]
+    {
+        sheet.frame_uv(self.current_frame())
+    }
+}
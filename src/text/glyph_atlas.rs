@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+
+use ab_glyph::{Font, FontArc, GlyphId};
+use image::{Rgba, RgbaImage};
+
+use crate::{abstraction::prospect_window::ProspectWindow, prospect_dynamic_texture::DynamicTexture};
+
+/// Empty texels left between glyphs so linear filtering doesn't pick up the neighbours
+const GLYPH_PADDING : u32 = 1;
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct CachedGlyph
+{
+    /// (min u, min v, max u, max v)
+    pub uv : [f32; 4],
+    /// Top left of the bitmap relative to the pen position on the baseline
+    pub offset : (f32, f32),
+    pub size : (f32, f32),
+}
+
+/// Rasterised glyphs shelf packed into a `DynamicTexture`, white with the coverage in alpha
+pub(crate) struct GlyphAtlas
+{
+    texture : DynamicTexture,
+    /// `None` for glyphs with nothing to draw, such as spaces
+    glyphs : HashMap<(GlyphId, u32), Option<CachedGlyph>>,
+    cursor : (u32, u32),
+    shelf_height : u32,
+}
+
+impl GlyphAtlas
+{
+    pub fn new(size : u32, window : &ProspectWindow) -> Self
+    {
+        Self
+        {
+            texture : DynamicTexture::new("Glyph Atlas", size, size, window),
+            glyphs : HashMap::new(),
+            cursor : (0, 0),
+            shelf_height : 0,
+        }
+    }
+
+    pub fn get_texture(&self) -> &DynamicTexture
+    {
+        &self.texture
+    }
+
+    /// Forgets every glyph, for when the atlas fills up
+    pub fn clear(&mut self)
+    {
+        self.glyphs.clear();
+        self.cursor = (0, 0);
+        self.shelf_height = 0;
+        let (width, height) = (self.texture.width(), self.texture.height());
+        self.texture.fill_region(0, 0, width, height, Rgba([255, 255, 255, 0]));
+    }
+
+    /// Rasterises the glyph at `px` pixels high if it isn't cached yet, fails if there's no room left
+    pub fn get(&mut self, font : &FontArc, id : GlyphId, px : u32) -> Result<Option<CachedGlyph>, ()>
+    {
+        if let Some(glyph) = self.glyphs.get(&(id, px))
+        {
+            return Ok(*glyph);
+        }
+
+        let outline = match font.outline_glyph(id.with_scale(px as f32))
+        {
+            Some(outline) => outline,
+            None =>
+            {
+                self.glyphs.insert((id, px), None);
+                return Ok(None);
+            }
+        };
+
+        let bounds = outline.px_bounds();
+        let width = bounds.width() as u32;
+        let height = bounds.height() as u32;
+        if width == 0 || height == 0
+        {
+            self.glyphs.insert((id, px), None);
+            return Ok(None);
+        }
+
+        let (x, y) = self.allocate(width + GLYPH_PADDING, height + GLYPH_PADDING)?;
+
+        let mut bitmap = RgbaImage::from_pixel(width, height, Rgba([255, 255, 255, 0]));
+        outline.draw(|gx, gy, coverage|
+        {
+            if gx < width && gy < height
+            {
+                bitmap.put_pixel(gx, gy, Rgba([255, 255, 255, (coverage.clamp(0., 1.) * 255.).round() as u8]));
+            }
+        });
+        self.texture.write_region(x, y, &bitmap);
+
+        let atlas_width = self.texture.width() as f32;
+        let atlas_height = self.texture.height() as f32;
+        let glyph = CachedGlyph
+        {
+            uv : [x as f32 / atlas_width, y as f32 / atlas_height, (x + width) as f32 / atlas_width, (y + height) as f32 / atlas_height],
+            offset : (bounds.min.x, bounds.min.y),
+            size : (width as f32, height as f32),
+        };
+        self.glyphs.insert((id, px), Some(glyph));
+        Ok(Some(glyph))
+    }
+
+    fn allocate(&mut self, width : u32, height : u32) -> Result<(u32, u32), ()>
+    {
+        if self.cursor.0 + width > self.texture.width()
+        {
+            self.cursor = (0, self.cursor.1 + self.shelf_height);
+            self.shelf_height = 0;
+        }
+
+        if self.cursor.0 + width > self.texture.width() || self.cursor.1 + height > self.texture.height()
+        {
+            return Err(());
+        }
+
+        let position = self.cursor;
+        self.cursor.0 += width;
+        self.shelf_height = self.shelf_height.max(height);
+        Ok(position)
+    }
+
+    pub fn process_frame(&mut self, window : &ProspectWindow)
+    {
+        self.texture.process_frame(window);
+    }
+}
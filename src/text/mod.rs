@@ -0,0 +1,6 @@
+mod glyph_atlas;
+mod text_layout;
+mod text_renderer;
+
+pub use text_layout::*;
+pub use text_renderer::*;
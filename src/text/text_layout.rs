@@ -0,0 +1,169 @@
+use ab_glyph::{Font, FontArc, GlyphId, ScaleFont};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextAlign
+{
+    #[default]
+    Left,
+    Centre,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextStyle
+{
+    /// Font size in pixels for screen text, or in world units for world text
+    pub scale : f32,
+    pub colour : [f32; 4],
+    /// Lines are aligned within `max_width` when wrapping, otherwise within the widest line
+    pub align : TextAlign,
+    /// Wraps at spaces (or mid word if a single word is too long) past this width, in the same units as `scale`
+    pub max_width : Option<f32>,
+    /// Multiplies the distance between lines
+    pub line_spacing : f32,
+}
+
+impl Default for TextStyle
+{
+    fn default() -> Self
+    {
+        Self
+        {
+            scale : 16.,
+            colour : [1.; 4],
+            align : TextAlign::Left,
+            max_width : None,
+            line_spacing : 1.,
+        }
+    }
+}
+
+impl TextStyle
+{
+    pub fn new(scale : f32, colour : [f32; 4]) -> Self
+    {
+        Self
+        {
+            scale,
+            colour,
+            ..Default::default()
+        }
+    }
+}
+
+/// A glyph's pen position on its line's baseline, relative to the top left of the text
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LaidOutGlyph
+{
+    pub id : GlyphId,
+    pub x : f32,
+    pub y : f32,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TextLayout
+{
+    pub glyphs : Vec<LaidOutGlyph>,
+    pub width : f32,
+    pub height : f32,
+}
+
+struct Line
+{
+    glyphs : Vec<(GlyphId, f32, char)>,
+    width : f32,
+}
+
+/// Positions every glyph of `text` in pixels at `scale`, applying kerning, wrapping and alignment.
+/// `max_width` is in pixels, regardless of what `style.scale` is measured in
+pub fn layout_text(font : &FontArc, text : &str, scale : f32, max_width : Option<f32>, align : TextAlign, line_spacing : f32) -> TextLayout
+{
+    let scaled = font.as_scaled(scale);
+    let mut lines = Vec::new();
+
+    let finish_line = |glyphs : Vec<(GlyphId, f32, char)>, lines : &mut Vec<Line>|
+    {
+        // Trailing spaces don't count towards the width, otherwise right aligned text looks off
+        let width = glyphs.iter().rev().find(|(_, _, c)| !c.is_whitespace()).map(|(id, x, _)| x + scaled.h_advance(*id)).unwrap_or(0.);
+        lines.push(Line { glyphs, width });
+    };
+
+    for paragraph in text.split('\n')
+    {
+        let mut line : Vec<(GlyphId, f32, char)> = Vec::new();
+        let mut pen = 0.;
+        let mut previous = None;
+        let mut last_space = None;
+
+        for c in paragraph.chars()
+        {
+            let id = font.glyph_id(c);
+            if let Some(previous) = previous
+            {
+                pen += scaled.kern(previous, id);
+            }
+            let advance = scaled.h_advance(id);
+
+            if let Some(max_width) = max_width
+            {
+                if pen + advance > max_width && !c.is_whitespace() && !line.is_empty()
+                {
+                    // Wrap after the last space, or right here if the word alone is too wide
+                    let split = last_space.map(|index| index + 1).unwrap_or(line.len());
+                    let mut rest = line.split_off(split);
+                    finish_line(std::mem::take(&mut line), &mut lines);
+
+                    let offset = rest.first().map(|(_, x, _)| *x).unwrap_or(pen);
+                    for glyph in rest.iter_mut()
+                    {
+                        glyph.1 -= offset;
+                    }
+                    line = rest;
+                    pen -= offset;
+                    last_space = None;
+                }
+            }
+
+            line.push((id, pen, c));
+            if c.is_whitespace()
+            {
+                last_space = Some(line.len() - 1);
+            }
+            pen += advance;
+            previous = Some(id);
+        }
+
+        finish_line(line, &mut lines);
+    }
+
+    let widest = lines.iter().fold(0f32, |widest, line| widest.max(line.width));
+    let block_width = max_width.unwrap_or(widest);
+    let line_height = (scaled.ascent() - scaled.descent() + scaled.line_gap()) * line_spacing;
+
+    let mut layout = TextLayout
+    {
+        glyphs : Vec::new(),
+        width : widest,
+        height : line_height * lines.len() as f32,
+    };
+
+    for (index, line) in lines.into_iter().enumerate()
+    {
+        let offset = match align
+        {
+            TextAlign::Left => 0.,
+            TextAlign::Centre => (block_width - line.width) / 2.,
+            TextAlign::Right => block_width - line.width,
+        };
+        let baseline = scaled.ascent() + line_height * index as f32;
+
+        layout.glyphs.extend(line.glyphs.into_iter().filter(|(_, _, c)| !c.is_whitespace()).map(|(id, x, _)| LaidOutGlyph
+        {
+            id,
+            x : x + offset,
+            y : baseline,
+        }));
+    }
+
+    layout
+}
@@ -0,0 +1,361 @@
+use ab_glyph::{Font, FontArc, ScaleFont};
+use vecto_rs::linear::Vector;
+use wgpu::*;
+
+use crate::{abstraction::{graphics_context::GraphicsContext, prospect_window::ProspectWindow, vertex::VertexLayout}, prospect_camera::ProspectCamera, prospect_texture::BindableTexture};
+
+use super::{glyph_atlas::GlyphAtlas, text_layout::{layout_text, TextLayout, TextStyle}};
+
+const ATLAS_SIZE : u32 = 1024;
+/// World text is rasterised at this many pixels high and scaled, so the atlas isn't filled with every distance it's seen from
+const WORLD_GLYPH_SIZE : u32 = 64;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable, VertexLayout)]
+struct TextVertex
+{
+    position : [f32; 3],
+    uv : [f32; 2],
+    colour : [f32; 4],
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct ScreenUniform
+{
+    view_proj : [f32; 16],
+    view_pos : [f32; 4],
+}
+
+enum TextPlacement
+{
+    /// Top left in pixels from the top left of the window
+    Screen(f32, f32),
+    /// Top left and the directions of the text's x and y axes
+    World(Vector, Vector, Vector),
+}
+
+struct TextSection
+{
+    text : String,
+    style : TextStyle,
+    placement : TextPlacement,
+}
+
+/// Draws text from a TrueType or OpenType font, glyphs are rasterised into an atlas the first time they're used.
+///
+/// Queue text every frame with `queue_screen` or `queue_world`, then call `process_frame` and `draw`.
+/// Draw it inside a render pass with the window's depth buffer after everything else, world text is depth tested and screen text is always on top
+pub struct TextRenderer
+{
+    font : FontArc,
+    atlas : GlyphAtlas,
+    world_pipeline : RenderPipeline,
+    screen_pipeline : RenderPipeline,
+    atlas_bind_group : BindGroup,
+    screen_buffer : Buffer,
+    screen_bind_group : BindGroup,
+    vertex_buffer : Buffer,
+    index_buffer : Buffer,
+    capacity : u32,
+    sections : Vec<TextSection>,
+    world_quads : u32,
+    screen_quads : u32,
+}
+
+impl TextRenderer
+{
+    /// `camera_layout` is `ProspectCamera::get_layout` of the camera world text is drawn with
+    pub fn new(window : &ProspectWindow, camera_layout : &BindGroupLayout, font_bytes : Vec<u8>) -> Result<Self, ()>
+    {
+        let font = FontArc::try_from_vec(font_bytes).map_err(|e|
+        {
+            log::error!("Failed to load font: {}", e);
+        })?;
+
+        let device = window.get_device();
+        let module = GraphicsContext::load_shader("Text Shader", include_str!("../shaders/text.wgsl"), device);
+
+        let entries = vec![
+            GraphicsContext::create_bind_group_layout_entry(0, ShaderStages::FRAGMENT, GraphicsContext::create_texture_binding_type(false, TextureViewDimension::D2, TextureSampleType::Float { filterable: true })),
+            GraphicsContext::create_bind_group_layout_entry(1, ShaderStages::FRAGMENT, GraphicsContext::create_sample_binding_type(SamplerBindingType::Filtering)),
+        ];
+        let atlas_layout = GraphicsContext::create_bind_group_layout(device, "Text Atlas Bind Group Layout", &entries);
+        let pipeline_layout = GraphicsContext::create_pipeline_layout("Text Pipeline Layout", device, &vec![camera_layout, &atlas_layout]);
+
+        let atlas = GlyphAtlas::new(ATLAS_SIZE, window);
+        let atlas_texture = atlas.get_texture();
+        let atlas_bind_group = GraphicsContext::create_bind_group(device, "Text Atlas Bind Group", &atlas_layout, &vec![
+            GraphicsContext::create_texture_view_resource(0, atlas_texture.get_texture_view()),
+            GraphicsContext::create_sampler_resource(1, atlas_texture.get_sampler().unwrap()),
+        ]);
+
+        // World text is see-through, so it's depth tested without hiding what's behind it
+        let world_pipeline = Self::create_pipeline(window, &module, &pipeline_layout, "Text World Pipeline", CompareFunction::Less);
+        let screen_pipeline = Self::create_pipeline(window, &module, &pipeline_layout, "Text Screen Pipeline", CompareFunction::Always);
+
+        let screen_buffer = GraphicsContext::create_buffer(device, "Text Screen Uniform Buffer", &[ScreenUniform { view_proj : [0.; 16], view_pos : [0.; 4] }], BufferUsages::UNIFORM | BufferUsages::COPY_DST);
+        let screen_bind_group = GraphicsContext::create_bind_group(device, "Text Screen Bind Group", camera_layout, &vec![
+            GraphicsContext::create_bind_group_entry(0, screen_buffer.as_entire_binding()),
+        ]);
+
+        let capacity = 256;
+        let (vertex_buffer, index_buffer) = Self::create_buffers(device, capacity);
+
+        Ok(Self
+        {
+            font,
+            atlas,
+            world_pipeline,
+            screen_pipeline,
+            atlas_bind_group,
+            screen_buffer,
+            screen_bind_group,
+            vertex_buffer,
+            index_buffer,
+            capacity,
+            sections : Vec::new(),
+            world_quads : 0,
+            screen_quads : 0,
+        })
+    }
+
+    /// Uses Monocraft, which ships with the crate
+    pub fn with_default_font(window : &ProspectWindow, camera_layout : &BindGroupLayout) -> Self
+    {
+        Self::new(window, camera_layout, include_bytes!("../../res/Monocraft.ttf").to_vec()).unwrap()
+    }
+
+    fn create_pipeline(window : &ProspectWindow, module : &ShaderModule, layout : &PipelineLayout, name : &str, depth_compare : CompareFunction) -> RenderPipeline
+    {
+        let targets = [Some(ColorTargetState {
+            format: window.get_surface_config().format,
+            blend: Some(BlendState::ALPHA_BLENDING),
+            write_mask: ColorWrites::ALL,
+        })];
+
+        let fragment_state = FragmentState {
+            module,
+            entry_point: "fs_main",
+            targets: &targets,
+        };
+
+        let vertex_state = VertexState {
+            module,
+            entry_point: "vs_main",
+            buffers: &[TextVertex::VERTEX_BUFFER_LAYOUT],
+        };
+
+        // Readable from behind too
+        let mut state = GraphicsContext::DEFAULT_PRIMITIVE_STATE;
+        state.cull_mode = None;
+
+        let depth_stencil = DepthStencilState {
+            format: GraphicsContext::DEPTH_FORMAT,
+            depth_write_enabled: false,
+            depth_compare,
+            stencil: StencilState::default(),
+            bias: DepthBiasState::default(),
+        };
+
+        GraphicsContext::create_render_pipeline_with_depth_stencil(name, layout, fragment_state, vertex_state, window.get_device(), state, depth_stencil)
+    }
+
+    fn create_buffers(device : &Device, capacity : u32) -> (Buffer, Buffer)
+    {
+        let vertices = vec![TextVertex { position : [0.; 3], uv : [0.; 2], colour : [0.; 4] }; capacity as usize * 4];
+        let indices : Vec<u32> = (0..capacity).flat_map(|quad| [0, 1, 2, 2, 1, 3].map(|index| quad * 4 + index)).collect();
+
+        (
+            GraphicsContext::create_buffer(device, "Text Vertex Buffer", &vertices, BufferUsages::VERTEX | BufferUsages::COPY_DST),
+            GraphicsContext::create_buffer(device, "Text Index Buffer", &indices, BufferUsages::INDEX),
+        )
+    }
+
+    pub fn get_font(&self) -> &FontArc
+    {
+        &self.font
+    }
+
+    /// `position` is the top left of the text in pixels from the top left of the window
+    pub fn queue_screen(&mut self, text : &str, position : (f32, f32), style : TextStyle)
+    {
+        self.sections.push(TextSection
+        {
+            text : text.to_string(),
+            style,
+            placement : TextPlacement::Screen(position.0, position.1),
+        });
+    }
+
+    /// Text in the world with its top left at `position`, running along `right` with lines going down `-up`.
+    /// `style.scale` and `style.max_width` are in world units
+    pub fn queue_world(&mut self, text : &str, position : Vector, right : Vector, up : Vector, style : TextStyle)
+    {
+        self.sections.push(TextSection
+        {
+            text : text.to_string(),
+            style,
+            placement : TextPlacement::World(position, right, up),
+        });
+    }
+
+    /// The size of `text` in the units of `style.scale`
+    pub fn measure(&self, text : &str, style : &TextStyle) -> (f32, f32)
+    {
+        let layout = layout_text(&self.font, text, style.scale, style.max_width, style.align, style.line_spacing);
+        (layout.width, layout.height)
+    }
+
+    /// Lays out and uploads everything queued since the last call
+    pub fn process_frame(&mut self, window : &ProspectWindow)
+    {
+        let (width, height) = (window.size.0.max(1) as f32, window.size.1.max(1) as f32);
+        // Pixels with the origin at the top left, column major
+        let view_proj = [
+            2. / width, 0., 0., 0.,
+            0., -2. / height, 0., 0.,
+            0., 0., 1., 0.,
+            -1., 1., 0., 1.,
+        ];
+        GraphicsContext::update_buffer(window.get_queue(), &self.screen_buffer, 0, &[ScreenUniform { view_proj, view_pos : [0.; 4] }]);
+
+        let sections = std::mem::take(&mut self.sections);
+        let vertices = match self.build_vertices(&sections)
+        {
+            Ok(vertices) => vertices,
+            Err(_) =>
+            {
+                // Full, start again with only what this frame needs
+                self.atlas.clear();
+                self.build_vertices(&sections).unwrap_or_else(|vertices|
+                {
+                    log::warn!("The text glyph atlas is full, some text won't be drawn");
+                    vertices
+                })
+            }
+        };
+        self.atlas.process_frame(window);
+
+        let quads = (vertices.len() / 4) as u32;
+        if quads > self.capacity
+        {
+            self.capacity = quads.next_power_of_two();
+            (self.vertex_buffer, self.index_buffer) = Self::create_buffers(window.get_device(), self.capacity);
+        }
+        if !vertices.is_empty()
+        {
+            GraphicsContext::update_buffer(window.get_queue(), &self.vertex_buffer, 0, &vertices);
+        }
+    }
+
+    /// World quads first and then screen quads, fails with whatever fit if the atlas filled up
+    fn build_vertices(&mut self, sections : &[TextSection]) -> Result<Vec<TextVertex>, Vec<TextVertex>>
+    {
+        let mut world = Vec::new();
+        let mut screen = Vec::new();
+        let mut full = false;
+
+        for section in sections
+        {
+            match &section.placement
+            {
+                TextPlacement::Screen(x, y) =>
+                {
+                    let px = section.style.scale.round().max(1.) as u32;
+                    let mut layout = layout_text(&self.font, &section.text, px as f32, section.style.max_width, section.style.align, section.style.line_spacing);
+                    // Glyph bitmaps are whole pixels, so snapping the pen positions keeps them sharp
+                    for glyph in layout.glyphs.iter_mut()
+                    {
+                        glyph.x = glyph.x.round();
+                        glyph.y = glyph.y.round();
+                    }
+                    let origin = (x.round(), y.round());
+                    full |= self.push_glyphs(&layout, px, section.style.colour, &mut screen, |gx, gy| [origin.0 + gx, origin.1 + gy, 0.]);
+                },
+                TextPlacement::World(position, right, up) =>
+                {
+                    let ratio = section.style.scale / WORLD_GLYPH_SIZE as f32;
+                    let max_width = section.style.max_width.map(|max_width| max_width / ratio);
+                    let layout = layout_text(&self.font, &section.text, WORLD_GLYPH_SIZE as f32, max_width, section.style.align, section.style.line_spacing);
+                    let place = |gx : f32, gy : f32|
+                    {
+                        let point = *position + *right * (gx * ratio) - *up * (gy * ratio);
+                        [point.x, point.y, point.z]
+                    };
+                    full |= self.push_glyphs(&layout, WORLD_GLYPH_SIZE, section.style.colour, &mut world, place);
+                },
+            }
+        }
+
+        self.world_quads = (world.len() / 4) as u32;
+        self.screen_quads = (screen.len() / 4) as u32;
+        world.append(&mut screen);
+
+        if full { Err(world) } else { Ok(world) }
+    }
+
+    /// `place` turns a position in the layout's pixels into a vertex position, returns true if the atlas is full
+    fn push_glyphs<F : Fn(f32, f32) -> [f32; 3]>(&mut self, layout : &TextLayout, px : u32, colour : [f32; 4], vertices : &mut Vec<TextVertex>, place : F) -> bool
+    {
+        let mut full = false;
+        for glyph in &layout.glyphs
+        {
+            let cached = match self.atlas.get(&self.font, glyph.id, px)
+            {
+                Ok(Some(cached)) => cached,
+                Ok(None) => continue,
+                Err(_) =>
+                {
+                    full = true;
+                    continue;
+                }
+            };
+
+            let place = |x : f32, y : f32| place(glyph.x + x, glyph.y + y);
+            let (left, top) = cached.offset;
+            let (width, height) = cached.size;
+            let [u0, v0, u1, v1] = cached.uv;
+            vertices.push(TextVertex { position : place(left, top), uv : [u0, v0], colour });
+            vertices.push(TextVertex { position : place(left + width, top), uv : [u1, v0], colour });
+            vertices.push(TextVertex { position : place(left, top + height), uv : [u0, v1], colour });
+            vertices.push(TextVertex { position : place(left + width, top + height), uv : [u1, v1], colour });
+        }
+        full
+    }
+
+    /// `camera` must be the one whose layout was passed to `new`
+    pub fn draw<'a>(&'a self, render_pass : &mut RenderPass<'a>, camera : &'a ProspectCamera)
+    {
+        if self.world_quads + self.screen_quads == 0
+        {
+            return;
+        }
+
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), IndexFormat::Uint32);
+        render_pass.set_bind_group(1, &self.atlas_bind_group, &[]);
+
+        if self.world_quads > 0
+        {
+            render_pass.set_pipeline(&self.world_pipeline);
+            camera.bind(render_pass, 0);
+            render_pass.draw_indexed(0..self.world_quads * 6, 0, 0..1);
+        }
+
+        if self.screen_quads > 0
+        {
+            render_pass.set_pipeline(&self.screen_pipeline);
+            render_pass.set_bind_group(0, &self.screen_bind_group, &[]);
+            let start = self.world_quads * 6;
+            render_pass.draw_indexed(start..start + self.screen_quads * 6, 0, 0..1);
+        }
+    }
+
+    /// Height of a line of text at `scale`, in the same units
+    pub fn line_height(&self, scale : f32) -> f32
+    {
+        let scaled = self.font.as_scaled(scale);
+        scaled.ascent() - scaled.descent() + scaled.line_gap()
+    }
+}
@@ -0,0 +1,38 @@
+//! Small helpers for `[f32; 3]` vectors, for code working on vertex data rather than `vecto_rs` types
+
+pub(crate) fn add(a : [f32; 3], b : [f32; 3]) -> [f32; 3]
+{
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+pub(crate) fn sub(a : [f32; 3], b : [f32; 3]) -> [f32; 3]
+{
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+pub(crate) fn scale(a : [f32; 3], s : f32) -> [f32; 3]
+{
+    [a[0] * s, a[1] * s, a[2] * s]
+}
+
+pub(crate) fn dot(a : [f32; 3], b : [f32; 3]) -> f32
+{
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+pub(crate) fn cross(a : [f32; 3], b : [f32; 3]) -> [f32; 3]
+{
+    [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]]
+}
+
+pub(crate) fn length(a : [f32; 3]) -> f32
+{
+    dot(a, a).sqrt()
+}
+
+/// `None` for vectors too short to have a direction, callers pick what to fall back to
+pub(crate) fn normalize(a : [f32; 3]) -> Option<[f32; 3]>
+{
+    let length = length(a);
+    if length > f32::EPSILON {Some(scale(a, 1. / length))} else {None}
+}
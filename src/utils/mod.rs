@@ -1 +1,2 @@
-pub mod prospect_fs;
\ No newline at end of file
+pub mod prospect_fs;
+pub(crate) mod math;
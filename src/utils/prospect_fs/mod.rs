@@ -1,4 +1,6 @@
 mod read;
 pub use read::*;
 mod path;
-pub use path::*;
\ No newline at end of file
+pub use path::*;
+mod watch;
+pub(crate) use watch::FileWatcher;
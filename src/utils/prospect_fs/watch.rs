@@ -0,0 +1,70 @@
+use std::{collections::HashSet, path::{Path, PathBuf}, sync::mpsc::{channel, Receiver}};
+
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+/// Watches loaded files for changes on disk, used by `ShaderRegistry` and `AssetServer` for hot reloading.
+/// Files still load if the watcher can't be started, they just won't reload
+pub(crate) struct FileWatcher
+{
+    /// What's being watched, for log messages
+    name : &'static str,
+    watcher : Option<RecommendedWatcher>,
+    watched_directories : HashSet<PathBuf>,
+    events : Receiver<notify::Result<notify::Event>>,
+}
+
+impl FileWatcher
+{
+    pub(crate) fn new(name : &'static str) -> Self
+    {
+        let (sender, events) = channel();
+        let watcher = match notify::recommended_watcher(sender)
+        {
+            Ok(watcher) => Some(watcher),
+            Err(error) =>
+            {
+                log::error!("Unable to watch {} for changes: {}", name, error);
+                None
+            }
+        };
+
+        Self
+        {
+            name,
+            watcher,
+            watched_directories : HashSet::new(),
+            events,
+        }
+    }
+
+    pub(crate) fn watch(&mut self, path : &Path)
+    {
+        // Editors often save by replacing the file, which ends a watch on the file itself, so the directory is watched instead
+        if let (Some(watcher), Some(directory)) = (self.watcher.as_mut(), path.parent())
+        {
+            if self.watched_directories.insert(directory.to_path_buf())
+            {
+                if let Err(error) = watcher.watch(directory, RecursiveMode::NonRecursive)
+                {
+                    log::error!("Unable to watch {} for changes: {}", directory.display(), error);
+                }
+            }
+        }
+    }
+
+    /// Files created or modified since the last call, including ones in watched directories that were never loaded
+    pub(crate) fn changed_paths(&self) -> HashSet<PathBuf>
+    {
+        let mut changed = HashSet::new();
+        for event in self.events.try_iter()
+        {
+            match event
+            {
+                Ok(event) if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) => changed.extend(event.paths),
+                Ok(_) => {}
+                Err(error) => log::warn!("Watcher error for {}: {}", self.name, error),
+            }
+        }
+        changed
+    }
+}